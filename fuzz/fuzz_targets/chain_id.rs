@@ -0,0 +1,12 @@
+#![no_main]
+
+use std::io::ErrorKind;
+
+use evm_dex_index::uniswap;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|chain_id: &str| {
+    if let Err(err) = uniswap::parse_chain_id(chain_id) {
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+});
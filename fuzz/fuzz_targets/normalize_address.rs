@@ -0,0 +1,21 @@
+#![no_main]
+
+use std::collections::{BTreeMap, HashMap};
+
+use evm_dex_index::{address, normalize, types::ProtocolDeployments};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|value: &str| {
+    let contracts = BTreeMap::from([("Contract".to_string(), value.to_string())]);
+    let mut protocol_deployments =
+        ProtocolDeployments::new("fuzz", HashMap::from([(1, contracts)]));
+
+    match normalize::addresses(&mut protocol_deployments) {
+        Ok(_) => {
+            // Anything rewritten must have become a plain address
+            let normalized = &protocol_deployments.chains[&1]["Contract"];
+            assert!(normalized == value || address::is_valid_address(normalized));
+        }
+        Err(err) => assert!(address::is_bytes32(&err.value)),
+    }
+});
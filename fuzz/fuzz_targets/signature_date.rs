@@ -0,0 +1,11 @@
+#![no_main]
+
+use evm_dex_index::balancer::{self, ParseError};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|signature: &str| {
+    match balancer::parse_data_from_signature(signature.to_string(), 1) {
+        Ok(_) | Err(ParseError::NoDateInSignature { .. }) | Err(ParseError::DateParseError(_)) => {}
+        Err(err) => panic!("Undocumented error for '{}': {}", signature, err),
+    }
+});
@@ -0,0 +1,84 @@
+use crate::types::ProtocolDeployments;
+
+pub const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// `0x`-prefixed 20-byte hex string, any case
+pub fn is_valid_address(address: &str) -> bool {
+    address
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// `0x`-prefixed 32-byte hex string, e.g. an address padded to `bytes32`
+pub fn is_bytes32(value: &str) -> bool {
+    value
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+pub fn is_zero_address(address: &str) -> bool {
+    address.eq_ignore_ascii_case(ZERO_ADDRESS)
+}
+
+/// `<protocol> on chain <chain_id>: <contract>` of every contract at the zero address, sorted
+pub fn zero_address_contracts(protocols: &[ProtocolDeployments]) -> Vec<String> {
+    let mut contracts: Vec<String> = protocols
+        .iter()
+        .flat_map(|protocol_deployments| {
+            protocol_deployments
+                .chains
+                .iter()
+                .flat_map(move |(chain_id, contracts)| {
+                    contracts
+                        .iter()
+                        .filter(|(_, address)| is_zero_address(address))
+                        .map(move |(name, _)| {
+                            format!(
+                                "{} on chain {}: {}",
+                                protocol_deployments.protocol_name, chain_id, name
+                            )
+                        })
+                })
+        })
+        .collect();
+    contracts.sort();
+
+    contracts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_address() {
+        assert!(is_valid_address(
+            "0x000000000022D473030F116dDEE9F6B43aC78BA3"
+        ));
+        assert!(is_valid_address(ZERO_ADDRESS));
+    }
+
+    #[test]
+    fn test_is_valid_address_invalid() {
+        assert!(!is_valid_address(""));
+        assert!(!is_valid_address("0x"));
+        assert!(!is_valid_address("0xnotanaddress"));
+        assert!(!is_valid_address(
+            "000000000022D473030F116dDEE9F6B43aC78BA3"
+        ));
+        assert!(!is_valid_address(
+            "0x000000000022D473030F116dDEE9F6B43aC78BA"
+        ));
+        assert!(!is_valid_address(
+            "0x000000000022D473030F116dDEE9F6B43aC78BAZ"
+        ));
+    }
+
+    #[test]
+    fn test_is_zero_address() {
+        assert!(is_zero_address(ZERO_ADDRESS));
+        assert!(!is_zero_address(
+            "0x000000000022D473030F116dDEE9F6B43aC78BA3"
+        ));
+    }
+}
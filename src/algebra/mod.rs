@@ -0,0 +1,183 @@
+use std::{collections::HashMap, path::Path};
+
+use thiserror::Error;
+use tracing::debug;
+
+use crate::{
+    git, meta,
+    source_files::{self, ReadError},
+    types::{ChainContracts, ContractAddress, ProtocolDeployments},
+};
+
+/// Core contract set shared by every Algebra engine deployment
+pub const CONTRACTS: &[&str] = &[
+    "AlgebraFactory",
+    "AlgebraPoolDeployer",
+    "SwapRouter",
+    "NonfungiblePositionManager",
+    "QuoterV2",
+];
+
+/// Keys of Algebra's `deploys.json` → canonical contract name
+const ALIASES: &[(&str, &str)] = &[
+    ("factory", "AlgebraFactory"),
+    ("poolDeployer", "AlgebraPoolDeployer"),
+    ("swapRouter", "SwapRouter"),
+    ("nonfungiblePositionManager", "NonfungiblePositionManager"),
+    ("quoterV2", "QuoterV2"),
+];
+
+/// An Algebra-based DEX, read from `<algebra sources>/<source_dir>`
+pub struct AlgebraDex {
+    pub protocol_name: &'static str,
+    pub source_dir: &'static str,
+}
+
+/// Algebra-based DEXes parsed when their source folder is present
+pub const ALGEBRA_DEXES: &[AlgebraDex] = &[
+    AlgebraDex {
+        protocol_name: "quickswap-v3",
+        source_dir: "quickswap",
+    },
+    AlgebraDex {
+        protocol_name: "camelot-v3",
+        source_dir: "camelot",
+    },
+    AlgebraDex {
+        protocol_name: "stellaswap-v3",
+        source_dir: "stellaswap",
+    },
+    AlgebraDex {
+        protocol_name: "zyberswap-v3",
+        source_dir: "zyberswap",
+    },
+];
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Serde error in '{path}': {source}")]
+    SerdeError {
+        path: String,
+        source: serde_json::Error,
+    },
+}
+
+impl From<ReadError> for ParseError {
+    fn from(err: ReadError) -> Self {
+        match err {
+            ReadError::IoError(err) => Self::IoError(err),
+            ReadError::SerdeError { path, source } => Self::SerdeError { path, source },
+        }
+    }
+}
+
+fn canonical_name(source_name: &str) -> Option<&'static str> {
+    CONTRACTS
+        .iter()
+        .copied()
+        .find(|contract| *contract == source_name)
+        .or_else(|| {
+            ALIASES
+                .iter()
+                .find(|(from, _)| *from == source_name)
+                .map(|(_, to)| *to)
+        })
+}
+
+/// Parses `<path>/<chain_id>.json` files, each a map of contract name to address,
+/// keeping only the Algebra contract set under `protocol_name`
+pub fn parse(protocol_name: &str, path: &str) -> Result<ProtocolDeployments, ParseError> {
+    let files = source_files::read_json_files(path, |file_path| {
+        source_files::chain_id_of(file_path).is_some()
+    })?;
+
+    let chains = files
+        .into_iter()
+        .map(|(file_path, source)| {
+            let chain_id = source_files::chain_id_of(&file_path).expect("Accepted by chain id");
+            (chain_id, parse_chain(protocol_name, source))
+        })
+        .collect();
+
+    let mut deployments = ProtocolDeployments::new(protocol_name, chains);
+    deployments.required_contracts = CONTRACTS.iter().map(|s| s.to_string()).collect();
+    meta::apply(path, &mut deployments)?;
+
+    Ok(deployments)
+}
+
+fn parse_chain(protocol_name: &str, source: HashMap<String, ContractAddress>) -> ChainContracts {
+    let mut contracts = ChainContracts::new();
+
+    for (source_name, address) in source {
+        match canonical_name(&source_name) {
+            Some(name) => {
+                contracts.insert(name.to_string(), address);
+            }
+            None => debug!(
+                protocol = protocol_name,
+                contract = source_name,
+                "Skipping contract outside the Algebra contract set"
+            ),
+        }
+    }
+
+    contracts
+}
+
+/// Parses every configured Algebra DEX whose folder exists under `sources_path`
+pub fn parse_all(sources_path: &str) -> Result<Vec<ProtocolDeployments>, ParseError> {
+    let mut protocols = Vec::new();
+
+    for dex in ALGEBRA_DEXES {
+        let path = format!("{}/{}", sources_path, dex.source_dir);
+        if !Path::new(&path).exists() {
+            debug!(
+                protocol = dex.protocol_name,
+                path, "Algebra source not found"
+            );
+            continue;
+        }
+
+        let mut protocol_deployments = parse(dex.protocol_name, &path)?;
+        protocol_deployments.source = Some(git::source_info(&path, None));
+        protocols.push(protocol_deployments);
+    }
+
+    Ok(protocols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_two_algebra_dexes() {
+        let alpha = parse("alpha-v3", "tests/fixtures/algebra/alpha").unwrap();
+        let beta = parse("beta-v3", "tests/fixtures/algebra/beta").unwrap();
+
+        assert_eq!(alpha.protocol_name, "alpha-v3");
+        assert_eq!(
+            alpha.chains[&137]["AlgebraFactory"],
+            "0x411b0fAcC3489691f28ad58c47006AF5E3Ab3A28"
+        );
+        assert_eq!(alpha.chains[&137].len(), CONTRACTS.len());
+        assert!(!alpha.chains[&137].contains_key("LimitOrderPlugin"));
+
+        assert_eq!(beta.protocol_name, "beta-v3");
+        assert_eq!(
+            beta.chains[&42161]["AlgebraFactory"],
+            "0x1a3c9B1d2F0529D97f2afC5136Cc23e58f1FD35B"
+        );
+        assert!(!beta.chains.contains_key(&137));
+        assert_eq!(beta.required_contracts.len(), CONTRACTS.len());
+    }
+
+    #[test]
+    fn test_parse_all_skips_missing_sources() {
+        assert!(parse_all("tests/fixtures/algebra").unwrap().is_empty());
+    }
+}
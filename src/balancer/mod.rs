@@ -1,210 +1,1141 @@
 use std::{
-    collections::{HashMap, hash_map::Entry},
-    fmt::{self, Display},
-    fs::File,
-    io::BufReader,
+    collections::{BTreeSet, HashMap, HashSet, hash_map::Entry},
+    fmt,
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::Path,
 };
 
 use chrono::NaiveDate;
-use serde::Deserialize;
+use flate2::read::GzDecoder;
+use rayon::prelude::*;
+use serde::{
+    Deserialize, Deserializer,
+    de::{DeserializeOwned, MapAccess, Visitor},
+};
+use tar::Archive;
 use thiserror::Error;
+use tracing::{debug, warn};
+
+use crate::{
+    ignore::{IGNORE_FILE_NAME, IgnoreList},
+    latest::LatestWinsMap,
+    meta, pattern, shared, source_files,
+    types::{
+        ChainCandidates, ChainContracts, ChainDeployments, ChainId, ChainMetadata, ChainSignatures,
+        ContractAddress, ContractMetadata, ContractName, ContractStatus, Create2,
+        ProtocolDeployments,
+    },
+    warnings::{self, Warning, WarningKind},
+};
+
+/// Protocol name of v2 and v3 combined by [`merge_versions`]
+pub const MERGED_PROTOCOL_NAME: &str = "balancer";
+
+pub const V2_PROTOCOL_NAME: &str = "balancer-v2";
+
+pub const V3_PROTOCOL_NAME: &str = "balancer-v3";
+
+/// Name patterns of balancer v3 contracts deployed per chain only as pools need them, e.g.
+/// `StableSurgeHook`. They never count against a chain's completeness.
+const V3_OPTIONAL_PATTERNS: &[&str] = &["*Hook"];
+
+/// Suffix balancer uses for contracts kept in an active deployment after being superseded
+const DEPRECATED_SUFFIX: &str = "_deprecated";
+
+/// What to do with `<Name>_deprecated` contracts of active deployments
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum DeprecatedContracts {
+    /// Emit them as `<Name>`, marked deprecated, unless a live `<Name>` exists
+    #[default]
+    Strip,
+    /// Leave them out of the output
+    Drop,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    pub deprecated_contracts: DeprecatedContracts,
+    /// Also take contracts from `SCRIPT` deployments, which are left out by default
+    pub include_script_deployments: bool,
+    /// Only read these networks, e.g. `mainnet`, to reparse just their files. Every
+    /// supported network is read when `None`.
+    pub networks: Option<Vec<String>>,
+}
+
+/// A contract as found in one deployment, competing to be the latest of its name
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct LatestContract {
+    /// Signature of the deployment, which same-day deployments are tie-broken by
+    signature: String,
+    address: ContractAddress,
+    create2: Option<Create2>,
+    compiler_version: Option<String>,
+    block_created: Option<u64>,
+    relayer_version: Option<String>,
+    /// Dropped after the selection, so an older live deployment doesn't take its place
+    disabled: bool,
+}
 
-use crate::types::{ChainContracts, ChainDeployments, ContractName, ProtocolDeployments};
+struct ProcessedContracts {
+    contracts: ChainContracts,
+    metadata: ChainMetadata,
+    candidates: ChainCandidates,
+    signatures: ChainSignatures,
+}
 
+/// Published either as a map of network name to info, or as an array of infos with a `name`
 #[derive(Debug, Deserialize)]
-struct SupportedNetworks {
-    #[serde(flatten)]
+#[serde(from = "SupportedNetworksShape")]
+pub(crate) struct SupportedNetworks {
     pub networks: HashMap<String, NetworkInfo>,
 }
 
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SupportedNetworksShape {
+    Map(HashMap<String, NetworkInfo>),
+    Array(Vec<NamedNetworkInfo>),
+}
+
+#[derive(Deserialize)]
+struct NamedNetworkInfo {
+    name: String,
+    #[serde(flatten)]
+    info: NetworkInfo,
+}
+
+impl From<SupportedNetworksShape> for SupportedNetworks {
+    fn from(shape: SupportedNetworksShape) -> Self {
+        let networks = match shape {
+            SupportedNetworksShape::Map(networks) => networks,
+            SupportedNetworksShape::Array(networks) => networks
+                .into_iter()
+                .map(|network| (network.name, network.info))
+                .collect(),
+        };
+
+        Self { networks }
+    }
+}
+
 #[derive(Debug, Deserialize)]
-struct NetworkInfo {
+pub(crate) struct NetworkInfo {
     #[serde(rename = "chainId")]
     chain_id: u64,
+    #[serde(default)]
+    enabled: Option<bool>,
+    #[serde(default)]
+    status: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+impl NetworkInfo {
+    /// Marked `"enabled": false` or with a `disabled` / `retired` status
+    pub(crate) fn is_disabled(&self) -> bool {
+        let disabled_status = self.status.as_deref().is_some_and(|status| {
+            status.eq_ignore_ascii_case("disabled") || status.eq_ignore_ascii_case("retired")
+        });
+
+        self.enabled == Some(false) || disabled_status
+    }
+}
+
+#[derive(Debug)]
 struct NetworkDeployments {
-    #[serde(flatten)]
     deployments: HashMap<String, Deployment>,
 }
 
+/// Deployments by signature at the root of the file, or nested under a wrapper key when the
+/// root has none
+impl<'de> Deserialize<'de> for NetworkDeployments {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NetworkVisitor;
+
+        impl<'de> Visitor<'de> for NetworkVisitor {
+            type Value = NetworkDeployments;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of deployments by signature")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut deployments = HashMap::new();
+                let mut nested = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    if shared::WRAPPER_KEYS.contains(&key.as_str()) && nested.is_none() {
+                        nested = Some(map.next_value::<HashMap<String, Deployment>>()?);
+                    } else {
+                        deployments.insert(key, map.next_value()?);
+                    }
+                }
+
+                if deployments.is_empty() {
+                    deployments = nested.unwrap_or_default();
+                }
+
+                Ok(NetworkDeployments { deployments })
+            }
+        }
+
+        deserializer.deserialize_map(NetworkVisitor)
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 struct Deployment {
+    #[serde(default)]
     version: DeploymentVersion,
     status: DeploymentStatus,
     contracts: Vec<Contract>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Clone)]
 struct Contract {
     name: String,
-    address: String,
+    /// `None` for placeholders of planned deployments
+    #[serde(default, alias = "deployedTo", alias = "contractAddress")]
+    address: Option<String>,
+    #[serde(default)]
+    create2: Option<Create2>,
+    #[serde(default, rename = "compilerVersion", alias = "solcVersion")]
+    compiler_version: Option<String>,
+    #[serde(
+        default,
+        rename = "blockCreated",
+        alias = "blockNumber",
+        alias = "startBlock"
+    )]
+    block_created: Option<u64>,
+    /// Pool factories no longer creating pools, independent of the deployment status
+    #[serde(default, alias = "deprecated")]
+    disabled: bool,
 }
 
+/// `ACTIVE`, `DEPRECATED` or `SCRIPT`, in any case
 #[derive(Debug, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[serde(from = "String")]
 enum DeploymentStatus {
-    #[serde(rename = "ACTIVE")]
     Active,
-    #[serde(rename = "DEPRECATED")]
     Deprecated,
     /// The only SCRIPT known is Avalanche's 20250411-balancer-registry-initializer-v2
-    #[serde(rename = "SCRIPT")]
     Script,
+    /// A status this parser doesn't know, such deployments are left out with a warning
+    Other(String),
+}
+
+impl From<String> for DeploymentStatus {
+    fn from(status: String) -> Self {
+        match status.to_uppercase().as_str() {
+            "ACTIVE" => Self::Active,
+            "DEPRECATED" => Self::Deprecated,
+            "SCRIPT" => Self::Script,
+            _ => Self::Other(status),
+        }
+    }
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Default)]
+#[serde(from = "String")]
 enum DeploymentVersion {
-    #[serde(rename = "v2")]
     V2,
-    #[serde(rename = "v3")]
     V3,
+    /// A version released after this parser, e.g. `v4`, parsed like the known ones with a
+    /// warning. Ordered by number, so `v10` comes after `v4`.
+    Other(u32),
+    /// A `version` that isn't `v<number>`, such deployments are quarantined with a warning
+    Invalid(String),
+    /// No `version` field, such deployments are quarantined with a warning
+    #[default]
+    Missing,
+}
+
+impl From<String> for DeploymentVersion {
+    fn from(version: String) -> Self {
+        let lowercase = version.to_lowercase();
+        match lowercase.as_str() {
+            "v2" => Self::V2,
+            "v3" => Self::V3,
+            other => other
+                .strip_prefix('v')
+                .filter(|number| {
+                    !number.is_empty() && number.bytes().all(|byte| byte.is_ascii_digit())
+                })
+                .and_then(|number| number.parse().ok())
+                .map(Self::Other)
+                .unwrap_or(Self::Invalid(version)),
+        }
+    }
+}
+
+impl DeploymentVersion {
+    /// The Vault version, e.g. `v2`
+    fn label(&self) -> Option<String> {
+        match self {
+            Self::V2 => Some("v2".to_string()),
+            Self::V3 => Some("v3".to_string()),
+            Self::Other(number) => Some(format!("v{}", number)),
+            Self::Invalid(_) | Self::Missing => None,
+        }
+    }
+
+    /// Protocol the deployments of this version are emitted under, none for quarantined ones
+    fn protocol_name(&self) -> Option<String> {
+        match self {
+            Self::V2 => Some(V2_PROTOCOL_NAME.to_string()),
+            Self::V3 => Some(V3_PROTOCOL_NAME.to_string()),
+            Self::Other(_) => self.label().map(|label| format!("balancer-{}", label)),
+            Self::Invalid(_) | Self::Missing => None,
+        }
+    }
 }
 
 #[derive(Debug, Error)]
 pub enum ParseError {
+    #[error("Chain id {chain_id} is defined by multiple networks")]
     ChainIdAlreadyExists { chain_id: u64 },
 
+    #[error("Network '{network}' has chain id {} in one repo and {} in another", chain_ids.0, chain_ids.1)]
+    ConflictingNetwork {
+        network: String,
+        chain_ids: (ChainId, ChainId),
+    },
+
+    #[error("Repos disagree on deployments: {}", conflicts.join("; "))]
+    ConflictingDeployments { conflicts: Vec<String> },
+
+    #[error("No date in deployment signature '{signature}' on chain {chain_id}")]
     NoDateInSignature { chain_id: u64, signature: String },
 
+    #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
 
-    SerdeError(#[from] serde_json::Error),
+    #[error("Serde error in '{path}': {source}")]
+    SerdeError {
+        path: String,
+        source: serde_json::Error,
+    },
 
+    #[error("Date error: {0}")]
     DateParseError(#[from] chrono::ParseError),
 }
 
-impl Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self)
-    }
+/// `path_to_repo` is either a repo checkout or a `.tar.gz` holding its `addresses/` folder
+pub fn parse(path_to_repo: &str) -> Result<(ProtocolDeployments, ProtocolDeployments), ParseError> {
+    parse_with_options(path_to_repo, &ParseOptions::default())
 }
 
-pub fn parse(path_to_repo: &str) -> Result<(ProtocolDeployments, ProtocolDeployments), ParseError> {
-    let path_to_folder = format!("{}/addresses", path_to_repo);
+pub fn parse_with_options(
+    path_to_repo: &str,
+    options: &ParseOptions,
+) -> Result<(ProtocolDeployments, ProtocolDeployments), ParseError> {
+    parse_repos(&[path_to_repo], options, &mut Vec::new())
+}
 
-    let supported_networks = read_supported_networks(&path_to_folder)?;
+/// Parses several repos as one, e.g. the monorepo and a standalone v3 repo, into v2 and v3.
+/// Deployments of a chain are pooled across repos, so the latest one wins by date.
+/// Deployments without a version are left out with a warning.
+pub fn parse_repos(
+    paths_to_repos: &[&str],
+    options: &ParseOptions,
+    warnings: &mut Vec<Warning>,
+) -> Result<(ProtocolDeployments, ProtocolDeployments), ParseError> {
+    let mut versions = parse_versions(paths_to_repos, options, warnings)?.into_iter();
+    let v2_deployments = versions.next().expect("v2 is always parsed");
+    let v3_deployments = versions.next().expect("v3 is always parsed");
+
+    Ok((v2_deployments, v3_deployments))
+}
 
-    let mut v2_chains: ChainDeployments = HashMap::new();
-    let mut v3_chains: ChainDeployments = HashMap::new();
+/// Like `parse_repos`, with a protocol per version: `balancer-v2` and `balancer-v3`, followed
+/// by `balancer-<version>` for every version newer than this parser, e.g. `balancer-v4`,
+/// each of whose deployments is warned about
+pub fn parse_versions(
+    paths_to_repos: &[&str],
+    options: &ParseOptions,
+    warnings: &mut Vec<Warning>,
+) -> Result<Vec<ProtocolDeployments>, ParseError> {
+    let mut quarantined = Vec::new();
+    let networks = read_networks(
+        paths_to_repos,
+        options.networks.as_deref(),
+        &mut quarantined,
+    )?;
+
+    let mut other_versions = BTreeSet::new();
+    for (info, deployments) in networks.values() {
+        quarantined.extend(quarantine_unversioned(info.chain_id, deployments));
+        quarantined.extend(unknown_versions(info.chain_id, deployments));
+        quarantined.extend(unknown_statuses(info.chain_id, deployments));
+        other_versions.extend(
+            deployments
+                .deployments
+                .values()
+                .filter(|deployment| matches!(deployment.version, DeploymentVersion::Other(_)))
+                .map(|deployment| deployment.version.clone()),
+        );
+    }
+    quarantined.sort();
+    warnings.extend(quarantined);
+
+    let newest_deployments: HashMap<ChainId, NaiveDate> = networks
+        .values()
+        .filter_map(|(info, deployments)| {
+            deployments
+                .deployments
+                .keys()
+                .filter_map(|signature| {
+                    parse_data_from_signature(signature.clone(), info.chain_id).ok()
+                })
+                .max()
+                .map(|newest_deployment| (info.chain_id, newest_deployment))
+        })
+        .collect();
+
+    let versions: Vec<DeploymentVersion> = [DeploymentVersion::V2, DeploymentVersion::V3]
+        .into_iter()
+        .chain(other_versions)
+        .collect();
+
+    let mut networks: Vec<_> = networks.into_iter().collect();
+    networks.sort_by_key(|(chain_id, _)| *chain_id);
+
+    // Networks are processed in parallel on the current rayon pool, then combined in chain order
+    let processed = networks
+        .into_par_iter()
+        .map(|(_, (info, deployments))| {
+            let process = |version: &DeploymentVersion| {
+                let mut active_deployments =
+                    filter_active_deployments_by_version(&deployments, version);
+                if options.include_script_deployments {
+                    active_deployments.extend(filter_deployments_by_version_and_status(
+                        &deployments,
+                        version,
+                        DeploymentStatus::Script,
+                    ));
+                }
+                if active_deployments.is_empty() {
+                    return Ok(None);
+                }
+                process_contracts_with_latest_deployments(
+                    active_deployments,
+                    info.chain_id,
+                    options,
+                )
+                .map(Some)
+            };
+
+            let processed = versions
+                .iter()
+                .map(process)
+                .collect::<Result<Vec<_>, ParseError>>()?;
+
+            Ok((info.chain_id, processed))
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
 
-    for (network, info) in supported_networks.networks {
-        let deployments = read_deployments_from_network_file(&path_to_folder, &network)?;
+    let mut protocols: Vec<ProtocolDeployments> = versions
+        .iter()
+        .filter_map(DeploymentVersion::protocol_name)
+        .map(|protocol_name| ProtocolDeployments::new(protocol_name, HashMap::new()))
+        .collect();
+
+    for (chain_id, processed_versions) in processed {
+        for (protocol_deployments, processed) in protocols.iter_mut().zip(processed_versions) {
+            let Some(processed) = processed else {
+                continue;
+            };
+
+            protocol_deployments
+                .chains
+                .insert(chain_id, processed.contracts);
+
+            protocol_deployments
+                .candidates
+                .insert(chain_id, processed.candidates);
+            protocol_deployments
+                .signatures
+                .insert(chain_id, processed.signatures);
+
+            if !processed.metadata.is_empty() {
+                protocol_deployments
+                    .metadata
+                    .insert(chain_id, processed.metadata);
+            }
+        }
+    }
 
-        let active_v2_deployments =
-            filter_active_deployments_by_version(&deployments, DeploymentVersion::V2);
-        let active_v3_deployments =
-            filter_active_deployments_by_version(&deployments, DeploymentVersion::V3);
+    for (version, protocol_deployments) in versions.iter().zip(&mut protocols) {
+        protocol_deployments.newest_deployments = newest_deployments.clone();
+        if *version == DeploymentVersion::V3 {
+            protocol_deployments.required_contracts = v3_required_contracts(protocol_deployments);
+        }
 
-        if !active_v2_deployments.is_empty() {
-            let v2_contracts =
-                process_contracts_with_latest_deployments(active_v2_deployments, info.chain_id)?;
+        warnings.extend(warnings::missing_contracts(protocol_deployments));
 
-            match v2_chains.entry(info.chain_id) {
-                Entry::Occupied(_) => {
-                    return Err(ParseError::ChainIdAlreadyExists {
-                        chain_id: info.chain_id,
-                    });
+        for path_to_repo in paths_to_repos {
+            meta::apply(path_to_repo, protocol_deployments)?;
+        }
+    }
+
+    Ok(protocols)
+}
+
+/// Contracts every balancer v3 chain is expected to have: each live one v3 has on any chain,
+/// except those matching [`V3_OPTIONAL_PATTERNS`]
+fn v3_required_contracts(v3: &ProtocolDeployments) -> Vec<ContractName> {
+    let is_deprecated = |chain_id: &ChainId, name: &ContractName| {
+        v3.metadata
+            .get(chain_id)
+            .and_then(|metadata| metadata.get(name))
+            .is_some_and(|metadata| metadata.status == Some(ContractStatus::Deprecated))
+    };
+
+    let required: BTreeSet<&ContractName> = v3
+        .chains
+        .iter()
+        .flat_map(|(chain_id, contracts)| {
+            contracts
+                .keys()
+                .filter(move |name| !is_deprecated(chain_id, name))
+        })
+        .filter(|name| {
+            !V3_OPTIONAL_PATTERNS
+                .iter()
+                .any(|optional| pattern::matches(optional, name))
+        })
+        .collect();
+
+    required.into_iter().cloned().collect()
+}
+
+/// Combines balancer v2 and v3 into a single `balancer` protocol, tagging every contract
+/// with its version. A name both versions have on a chain is kept as `<name>-<version>` twice.
+pub fn merge_versions(v2: ProtocolDeployments, v3: ProtocolDeployments) -> ProtocolDeployments {
+    let mut merged = ProtocolDeployments::new(MERGED_PROTOCOL_NAME, ChainDeployments::new());
+    merged.source_ref = v2.source_ref.clone().or_else(|| v3.source_ref.clone());
+    merged.source = v2.source.clone().or_else(|| v3.source.clone());
+    merged.newest_deployments = v2.newest_deployments.clone();
+    for (chain_id, newest_deployment) in &v3.newest_deployments {
+        let merged_newest = merged.newest_deployments.entry(*chain_id).or_default();
+        *merged_newest = (*merged_newest).max(*newest_deployment);
+    }
+
+    let shared: HashSet<(ChainId, ContractName)> = v2
+        .chains
+        .iter()
+        .flat_map(|(chain_id, contracts)| {
+            contracts
+                .keys()
+                .filter(|name| {
+                    v3.chains
+                        .get(chain_id)
+                        .is_some_and(|v3| v3.contains_key(*name))
+                })
+                .map(|name| (*chain_id, name.clone()))
+        })
+        .collect();
+
+    for (version, mut deployments) in [("v2", v2), ("v3", v3)] {
+        for (chain_id, contracts) in deployments.chains {
+            let mut chain_metadata = deployments.metadata.remove(&chain_id).unwrap_or_default();
+            let mut chain_candidates = deployments.candidates.remove(&chain_id).unwrap_or_default();
+            let mut chain_signatures = deployments.signatures.remove(&chain_id).unwrap_or_default();
+
+            for (name, address) in contracts {
+                let key = if shared.contains(&(chain_id, name.clone())) {
+                    format!("{}-{}", name, version)
+                } else {
+                    name.clone()
+                };
+
+                let mut contract_metadata = chain_metadata.remove(&name).unwrap_or_default();
+                contract_metadata.version = Some(version.to_string());
+                merged
+                    .metadata
+                    .entry(chain_id)
+                    .or_default()
+                    .insert(key.clone(), contract_metadata);
+
+                if let Some(count) = chain_candidates.remove(&name) {
+                    merged
+                        .candidates
+                        .entry(chain_id)
+                        .or_default()
+                        .insert(key.clone(), count);
                 }
-                Entry::Vacant(entry) => {
-                    entry.insert(v2_contracts);
+                if let Some(signature) = chain_signatures.remove(&name) {
+                    merged
+                        .signatures
+                        .entry(chain_id)
+                        .or_default()
+                        .insert(key.clone(), signature);
                 }
+
+                merged
+                    .chains
+                    .entry(chain_id)
+                    .or_default()
+                    .insert(key, address);
             }
         }
+    }
+
+    merged
+}
 
-        if !active_v3_deployments.is_empty() {
-            let v3_contracts =
-                process_contracts_with_latest_deployments(active_v3_deployments, info.chain_id)?;
+/// Chain id of every network the repos list, disabled ones included
+pub fn network_chain_ids(paths_to_repos: &[&str]) -> Result<HashMap<String, ChainId>, ParseError> {
+    let mut chain_ids = HashMap::new();
+    for path_to_repo in paths_to_repos {
+        let folder = AddressesFolder::open(path_to_repo)?;
+        for (network, info) in read_supported_networks(&folder)?.networks {
+            chain_ids.insert(network, info.chain_id);
+        }
+    }
 
-            match v3_chains.entry(info.chain_id) {
-                Entry::Occupied(_) => {
-                    return Err(ParseError::ChainIdAlreadyExists {
-                        chain_id: info.chain_id,
-                    });
+    Ok(chain_ids)
+}
+
+/// Enabled networks of every repo with their deployments pooled by chain id, warning about
+/// disabled ones. Repos may name a chain differently, but a deployment both of them have must
+/// hold the same addresses.
+fn read_networks(
+    paths_to_repos: &[&str],
+    selected: Option<&[String]>,
+    warnings: &mut Vec<Warning>,
+) -> Result<HashMap<ChainId, (NetworkInfo, NetworkDeployments)>, ParseError> {
+    let mut networks: HashMap<ChainId, (NetworkInfo, NetworkDeployments)> = HashMap::new();
+    let mut network_chain_ids: HashMap<String, ChainId> = HashMap::new();
+    let mut conflicts = Vec::new();
+
+    for path_to_repo in paths_to_repos {
+        let folder = AddressesFolder::open(path_to_repo)?;
+        let supported_networks = read_supported_networks(&folder)?;
+        let ignore_list = folder.ignore_list()?;
+        let mut repo_chain_ids = HashSet::new();
+
+        for (network, info) in supported_networks.networks {
+            if selected.is_some_and(|selected| !selected.contains(&network)) {
+                continue;
+            }
+
+            if info.is_disabled() {
+                debug!(network = %network, chain_id = info.chain_id, "Skipping disabled network");
+                warnings.push(Warning {
+                    kind: WarningKind::SkippedNetwork,
+                    protocol: None,
+                    chain_id: Some(info.chain_id),
+                    detail: format!("balancer network '{}' is disabled", network),
+                });
+                continue;
+            }
+
+            if ignore_list.is_ignored(&format!("{}.json", network)) {
+                debug!(network = %network, chain_id = info.chain_id, "Skipping ignored network file");
+                continue;
+            }
+
+            let chain_id = info.chain_id;
+            if !repo_chain_ids.insert(chain_id) {
+                return Err(ParseError::ChainIdAlreadyExists { chain_id });
+            }
+            let named_chain_id = *network_chain_ids.entry(network.clone()).or_insert(chain_id);
+            if named_chain_id != chain_id {
+                return Err(ParseError::ConflictingNetwork {
+                    network,
+                    chain_ids: (named_chain_id, chain_id),
+                });
+            }
+
+            let deployments = read_deployments_from_network_file(&folder, &network)?;
+
+            match networks.entry(chain_id) {
+                Entry::Occupied(mut entry) => {
+                    let (_, pooled) = entry.get_mut();
+                    pool_deployments(chain_id, pooled, deployments, &mut conflicts);
                 }
                 Entry::Vacant(entry) => {
-                    entry.insert(v3_contracts);
+                    entry.insert((info, deployments));
                 }
             }
         }
     }
 
-    Ok((
-        ProtocolDeployments {
-            protocol_name: "balancer-v2".to_string(),
-            chains: v2_chains,
-        },
-        ProtocolDeployments {
-            protocol_name: "balancer-v3".to_string(),
-            chains: v3_chains,
-        },
-    ))
+    if !conflicts.is_empty() {
+        return Err(ParseError::ConflictingDeployments { conflicts });
+    }
+
+    Ok(networks)
+}
+
+/// Adds the deployments of another repo to those of the chain so far. A deployment both
+/// have is kept once, recording in `conflicts` a version differing between them and every
+/// contract whose address differs (checksum case aside). Repos are compared deployment by
+/// deployment, so a repo lacking the newer deployments of another doesn't conflict with it.
+fn pool_deployments(
+    chain_id: ChainId,
+    pooled: &mut NetworkDeployments,
+    deployments: NetworkDeployments,
+    conflicts: &mut Vec<String>,
+) {
+    let mut signatures: Vec<_> = deployments.deployments.into_iter().collect();
+    signatures.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (signature, deployment) in signatures {
+        let Some(existing) = pooled.deployments.get(&signature) else {
+            pooled.deployments.insert(signature, deployment);
+            continue;
+        };
+
+        if existing.version != deployment.version {
+            let label = |version: &DeploymentVersion| match version {
+                DeploymentVersion::Invalid(version) => format!("'{}'", version),
+                version => version.label().unwrap_or_else(|| "unversioned".to_string()),
+            };
+            conflicts.push(format!(
+                "deployment '{}' on chain {}: version {} vs {}",
+                signature,
+                chain_id,
+                label(&existing.version),
+                label(&deployment.version)
+            ));
+        }
+
+        for contract in &deployment.contracts {
+            let existing_address = existing
+                .contracts
+                .iter()
+                .find(|existing| existing.name == contract.name)
+                .and_then(|existing| existing.address.as_deref());
+            let (Some(existing_address), Some(address)) =
+                (existing_address, contract.address.as_deref())
+            else {
+                continue;
+            };
+
+            if !existing_address.eq_ignore_ascii_case(address) {
+                conflicts.push(format!(
+                    "{} of deployment '{}' on chain {}: '{}' vs '{}'",
+                    contract.name, signature, chain_id, existing_address, address
+                ));
+            }
+        }
+    }
 }
 
 fn process_contracts_with_latest_deployments(
     active_deployments: HashMap<String, Deployment>,
     chain_id: u64,
-) -> Result<ChainContracts, ParseError> {
-    let mut contracts: ChainContracts = ChainContracts::new();
-    let mut deployment_dates: HashMap<ContractName, NaiveDate> = HashMap::new();
+    options: &ParseOptions,
+) -> Result<ProcessedContracts, ParseError> {
+    // Same-day deployments are tie-broken by signature
+    let mut latest: LatestWinsMap<ContractName, LatestContract> = LatestWinsMap::new();
+    let mut latest_deprecated: LatestWinsMap<ContractName, LatestContract> = LatestWinsMap::new();
 
     for (signature, deployment) in active_deployments {
-        let date = parse_data_from_signature(signature, chain_id)?;
+        let date = parse_data_from_signature(signature.clone(), chain_id)?;
 
         for contract in deployment.contracts {
-            let should_update = deployment_dates
-                .get(&contract.name)
-                .map_or(true, |existing_date| date >= *existing_date);
-
-            if should_update {
-                contracts.insert(contract.name.clone(), contract.address);
-                deployment_dates.insert(contract.name, date);
+            let Some(address) = contract.address else {
+                debug!(
+                    chain_id,
+                    signature,
+                    contract = contract.name,
+                    "Skipping contract without address"
+                );
+                continue;
+            };
+            let value = LatestContract {
+                signature: signature.clone(),
+                address,
+                create2: contract.create2,
+                compiler_version: contract.compiler_version,
+                block_created: contract.block_created,
+                relayer_version: relayer_version(&contract.name, &deployment.version),
+                disabled: contract.disabled,
+            };
+
+            match contract.name.strip_suffix(DEPRECATED_SUFFIX) {
+                Some(_) if options.deprecated_contracts == DeprecatedContracts::Drop => {}
+                Some(name) => latest_deprecated.insert(name.to_string(), date, value),
+                None => latest.insert(contract.name, date, value),
             }
         }
     }
 
-    Ok(contracts)
+    let mut contracts = ChainContracts::new();
+    let mut metadata = ChainMetadata::new();
+    let mut candidates = ChainCandidates::new();
+    let mut signatures = ChainSignatures::new();
+
+    for (name, contract, count) in latest.into_latest_with_candidates() {
+        if contract.disabled {
+            debug!(
+                chain_id,
+                signature = contract.signature,
+                contract = name,
+                "Skipping disabled contract"
+            );
+            continue;
+        }
+
+        candidates.insert(name.clone(), count);
+        let contract_metadata = ContractMetadata {
+            create2: contract.create2,
+            relayer_version: contract.relayer_version,
+            compiler_version: contract.compiler_version,
+            block_created: contract.block_created,
+            ..ContractMetadata::default()
+        };
+        if contract_metadata != ContractMetadata::default() {
+            metadata.insert(name.clone(), contract_metadata);
+        }
+        signatures.insert(name.clone(), contract.signature);
+        contracts.insert(name, contract.address);
+    }
+
+    for (name, contract, count) in latest_deprecated.into_latest_with_candidates() {
+        if contracts.contains_key(&name) || contract.disabled {
+            continue;
+        }
+
+        candidates.insert(name.clone(), count);
+        metadata.insert(
+            name.clone(),
+            ContractMetadata {
+                status: Some(ContractStatus::Deprecated),
+                create2: contract.create2,
+                relayer_version: contract.relayer_version,
+                compiler_version: contract.compiler_version,
+                block_created: contract.block_created,
+                ..ContractMetadata::default()
+            },
+        );
+        signatures.insert(name.clone(), contract.signature);
+        contracts.insert(name, contract.address);
+    }
+
+    Ok(ProcessedContracts {
+        contracts,
+        metadata,
+        candidates,
+        signatures,
+    })
+}
+
+/// Vault version a relayer contract pairs with, that of the deployment shipping it
+fn relayer_version(name: &str, version: &DeploymentVersion) -> Option<String> {
+    if !name.contains("Relayer") {
+        return None;
+    }
+
+    version.label()
+}
+
+/// Warnings for the deployments of a network lacking a `version` field, or whose `version`
+/// isn't `v<number>`
+fn quarantine_unversioned(chain_id: ChainId, deployments: &NetworkDeployments) -> Vec<Warning> {
+    deployments
+        .deployments
+        .iter()
+        .filter_map(|(signature, deployment)| match &deployment.version {
+            DeploymentVersion::Missing => {
+                warn!(
+                    chain_id,
+                    signature, "Skipping balancer deployment without version"
+                );
+                Some(format!(
+                    "balancer deployment '{}' has no version",
+                    signature
+                ))
+            }
+            DeploymentVersion::Invalid(version) => {
+                warn!(
+                    chain_id,
+                    signature, version, "Skipping balancer deployment of invalid version"
+                );
+                Some(format!(
+                    "balancer deployment '{}' has invalid version '{}'",
+                    signature, version
+                ))
+            }
+            _ => None,
+        })
+        .map(|detail| Warning {
+            kind: WarningKind::Quarantined,
+            protocol: None,
+            chain_id: Some(chain_id),
+            detail,
+        })
+        .collect()
+}
+
+/// Warnings for the deployments of a network of a version this parser doesn't know
+fn unknown_versions(chain_id: ChainId, deployments: &NetworkDeployments) -> Vec<Warning> {
+    deployments
+        .deployments
+        .iter()
+        .filter(|(_, deployment)| matches!(deployment.version, DeploymentVersion::Other(_)))
+        .filter_map(|(signature, deployment)| {
+            Some((
+                signature,
+                deployment.version.label()?,
+                deployment.version.protocol_name()?,
+            ))
+        })
+        .map(|(signature, version, protocol_name)| {
+            warn!(
+                chain_id,
+                signature, version, "Parsing balancer deployment of unknown version"
+            );
+            Warning {
+                kind: WarningKind::UnknownVersion,
+                protocol: Some(protocol_name),
+                chain_id: Some(chain_id),
+                detail: format!(
+                    "balancer deployment '{}' has unknown version '{}'",
+                    signature, version
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Warnings for the deployments of a network with a status this parser doesn't know
+fn unknown_statuses(chain_id: ChainId, deployments: &NetworkDeployments) -> Vec<Warning> {
+    deployments
+        .deployments
+        .iter()
+        .filter_map(|(signature, deployment)| match &deployment.status {
+            DeploymentStatus::Other(status) => Some((signature, status)),
+            _ => None,
+        })
+        .map(|(signature, status)| {
+            warn!(
+                chain_id,
+                signature, status, "Skipping balancer deployment of unknown status"
+            );
+            Warning {
+                kind: WarningKind::UnknownStatus,
+                protocol: None,
+                chain_id: Some(chain_id),
+                detail: format!(
+                    "balancer deployment '{}' has unknown status '{}'",
+                    signature, status
+                ),
+            }
+        })
+        .collect()
 }
 
 fn filter_active_deployments_by_version(
     deployments: &NetworkDeployments,
-    version: DeploymentVersion,
+    version: &DeploymentVersion,
+) -> HashMap<String, Deployment> {
+    filter_deployments_by_version_and_status(deployments, version, DeploymentStatus::Active)
+}
+
+fn filter_deployments_by_version_and_status(
+    deployments: &NetworkDeployments,
+    version: &DeploymentVersion,
+    status: DeploymentStatus,
 ) -> HashMap<String, Deployment> {
     deployments
         .deployments
         .iter()
-        .filter(|(_, deployment)| {
-            deployment.version == version && deployment.status == DeploymentStatus::Active
-        })
+        .filter(|(_, deployment)| deployment.version == *version && deployment.status == status)
         .map(|(k, v)| (k.clone(), v.clone()))
         .collect::<HashMap<String, Deployment>>()
 }
 
-fn read_supported_networks(path_to_folder: &str) -> Result<SupportedNetworks, ParseError> {
-    let supported_networks = File::open(format!("{}/.supported-networks.json", path_to_folder))?;
-    let reader = BufReader::new(supported_networks);
-    let supported_networks: SupportedNetworks = serde_json::from_reader(reader)?;
-
-    Ok(supported_networks)
+fn read_supported_networks(folder: &AddressesFolder) -> Result<SupportedNetworks, ParseError> {
+    folder.read_json(".supported-networks.json")
 }
 
+/// Deployments of `<network>.json`, or without it, of the `<network>/*.json` fragments
+/// merged in file name order
 fn read_deployments_from_network_file(
-    path_to_folder: &str,
+    folder: &AddressesFolder,
     network: &str,
 ) -> Result<NetworkDeployments, ParseError> {
-    let file = File::open(format!("{}/{}.json", path_to_folder, network))?;
-    let reader = BufReader::new(file);
-    let deployments: NetworkDeployments = serde_json::from_reader(reader)?;
+    let file_name = format!("{}.json", network);
+    let fragments = if folder.contains(&file_name) {
+        Vec::new()
+    } else {
+        folder.fragments(network)?
+    };
+    if fragments.is_empty() {
+        return folder.read_json(&file_name);
+    }
+
+    let mut deployments = NetworkDeployments {
+        deployments: HashMap::new(),
+    };
+    for fragment in fragments {
+        let fragment_deployments: NetworkDeployments = folder.read_json(&fragment)?;
+        deployments
+            .deployments
+            .extend(fragment_deployments.deployments);
+    }
 
     Ok(deployments)
 }
 
-fn parse_data_from_signature(signature: String, chain_id: u64) -> Result<NaiveDate, ParseError> {
+/// `addresses/` folder of a repo checkout, or its files read in-memory from a `.tar.gz`
+enum AddressesFolder {
+    Dir(String),
+    Archive {
+        path: String,
+        /// Contents of the files inside `addresses/` and its network folders, by path
+        /// relative to `addresses/`
+        files: HashMap<String, Vec<u8>>,
+    },
+}
+
+impl AddressesFolder {
+    fn open(path_to_repo: &str) -> Result<Self, ParseError> {
+        if !source_files::is_tarball(path_to_repo) {
+            return Ok(Self::Dir(format!("{}/addresses", path_to_repo)));
+        }
+
+        let file = File::open(path_to_repo)?;
+        let mut archive = Archive::new(GzDecoder::new(BufReader::new(file)));
+        let mut files = HashMap::new();
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.into_owned();
+            let is_addresses = |path: Option<&Path>| {
+                path.and_then(|path| path.file_name())
+                    .is_some_and(|name| name == "addresses")
+            };
+            let parent = path.parent();
+            let relative_to = if is_addresses(parent) {
+                parent
+            } else if is_addresses(parent.and_then(Path::parent)) {
+                parent.and_then(Path::parent)
+            } else {
+                None
+            };
+            let Some(relative_to) = relative_to else {
+                continue;
+            };
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let Some(file_name) = path
+                .strip_prefix(relative_to)
+                .ok()
+                .and_then(|relative| relative.to_str())
+            else {
+                continue;
+            };
+
+            let file_name = file_name.to_string();
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+            files.insert(file_name, contents);
+        }
+
+        Ok(Self::Archive {
+            path: path_to_repo.to_string(),
+            files,
+        })
+    }
+
+    fn ignore_list(&self) -> Result<IgnoreList, ParseError> {
+        match self {
+            Self::Dir(path) => Ok(IgnoreList::read(path)?),
+            Self::Archive { files, .. } => Ok(files
+                .get(IGNORE_FILE_NAME)
+                .map(|contents| IgnoreList::parse(&String::from_utf8_lossy(contents)))
+                .unwrap_or_default()),
+        }
+    }
+
+    fn contains(&self, file_name: &str) -> bool {
+        match self {
+            Self::Dir(path) => Path::new(path).join(file_name).is_file(),
+            Self::Archive { files, .. } => files.contains_key(file_name),
+        }
+    }
+
+    /// `<network>/*.json` files, relative to `addresses/` and sorted
+    fn fragments(&self, network: &str) -> Result<Vec<String>, ParseError> {
+        let mut fragments: Vec<String> = match self {
+            Self::Dir(path) => {
+                let dir = Path::new(path).join(network);
+                if !dir.is_dir() {
+                    return Ok(Vec::new());
+                }
+                fs::read_dir(dir)?
+                    .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+                    .collect::<Result<Vec<_>, std::io::Error>>()?
+                    .into_iter()
+                    .map(|file_name| format!("{}/{}", network, file_name))
+                    .collect()
+            }
+            Self::Archive { files, .. } => files
+                .keys()
+                .filter(|file_name| {
+                    file_name
+                        .strip_prefix(network)
+                        .is_some_and(|rest| rest.starts_with('/'))
+                })
+                .cloned()
+                .collect(),
+        };
+        fragments.retain(|fragment| fragment.ends_with(".json"));
+        fragments.sort();
+
+        Ok(fragments)
+    }
+
+    fn read_json<T: DeserializeOwned>(&self, file_name: &str) -> Result<T, ParseError> {
+        let (path, files) = match self {
+            Self::Dir(path) => return read_json(&format!("{}/{}", path, file_name)),
+            Self::Archive { path, files } => (path, files),
+        };
+        let path = format!("{}/addresses/{}", path, file_name);
+
+        let contents = files.get(file_name).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{} not found in archive", path),
+            )
+        })?;
+
+        serde_json::from_slice(contents).map_err(|source| ParseError::SerdeError { path, source })
+    }
+}
+
+/// Deserializes a source file, naming it in the error
+fn read_json<T: DeserializeOwned>(path: &str) -> Result<T, ParseError> {
+    let reader = BufReader::new(File::open(path)?);
+
+    serde_json::from_reader(reader).map_err(|source| ParseError::SerdeError {
+        path: path.to_string(),
+        source,
+    })
+}
+
+/// Whether the newest deployment is more than `max_age_days` older than `today`,
+/// suggesting the source mirror hasn't been synced
+pub fn is_stale(newest_deployment: NaiveDate, today: NaiveDate, max_age_days: u64) -> bool {
+    (today - newest_deployment).num_days() > i64::try_from(max_age_days).unwrap_or(i64::MAX)
+}
+
+/// Date a deployment signature starts with, e.g. 2025-04-11 for `20250411-v3-vault`
+pub fn parse_data_from_signature(
+    signature: String,
+    chain_id: u64,
+) -> Result<NaiveDate, ParseError> {
     // yyyymmdd format, example: 20250411
 
     let date_str = signature
         .split('-')
-        .nth(0)
+        .next()
         .ok_or(ParseError::NoDateInSignature {
             chain_id,
             signature: signature.to_owned(),
@@ -220,6 +1151,14 @@ mod tests {
     use super::*;
     use chrono::Datelike;
 
+    fn contract(name: &str, address: &str) -> Contract {
+        Contract {
+            name: name.to_string(),
+            address: Some(address.to_string()),
+            ..Contract::default()
+        }
+    }
+
     #[test]
     fn test_parse_balancer() {
         let path = "source/balancer";
@@ -318,7 +1257,7 @@ mod tests {
         };
 
         let result =
-            filter_active_deployments_by_version(&network_deployments, DeploymentVersion::V2);
+            filter_active_deployments_by_version(&network_deployments, &DeploymentVersion::V2);
         assert_eq!(result.len(), 2);
     }
 
@@ -355,12 +1294,12 @@ mod tests {
         };
 
         let v2_result =
-            filter_active_deployments_by_version(&network_deployments, DeploymentVersion::V2);
+            filter_active_deployments_by_version(&network_deployments, &DeploymentVersion::V2);
         assert_eq!(v2_result.len(), 1);
         assert!(v2_result.contains_key("20250101-deploy1"));
 
         let v3_result =
-            filter_active_deployments_by_version(&network_deployments, DeploymentVersion::V3);
+            filter_active_deployments_by_version(&network_deployments, &DeploymentVersion::V3);
         assert_eq!(v3_result.len(), 1);
         assert!(v3_result.contains_key("20250103-deploy3"));
     }
@@ -372,7 +1311,7 @@ mod tests {
         };
 
         let result =
-            filter_active_deployments_by_version(&network_deployments, DeploymentVersion::V2);
+            filter_active_deployments_by_version(&network_deployments, &DeploymentVersion::V2);
         assert_eq!(result.len(), 0);
     }
 
@@ -384,23 +1323,15 @@ mod tests {
             Deployment {
                 version: DeploymentVersion::V2,
                 status: DeploymentStatus::Active,
-                contracts: vec![
-                    Contract {
-                        name: "Vault".to_string(),
-                        address: "0x1234".to_string(),
-                    },
-                    Contract {
-                        name: "Router".to_string(),
-                        address: "0x5678".to_string(),
-                    },
-                ],
+                contracts: vec![contract("Vault", "0x1234"), contract("Router", "0x5678")],
             },
         );
 
-        let result = process_contracts_with_latest_deployments(deployments, 1);
+        let result =
+            process_contracts_with_latest_deployments(deployments, 1, &ParseOptions::default());
         assert!(result.is_ok());
 
-        let contracts = result.unwrap();
+        let contracts = result.unwrap().contracts;
         assert_eq!(contracts.len(), 2);
         assert_eq!(contracts.get("Vault"), Some(&"0x1234".to_string()));
         assert_eq!(contracts.get("Router"), Some(&"0x5678".to_string()));
@@ -415,10 +1346,7 @@ mod tests {
             Deployment {
                 version: DeploymentVersion::V2,
                 status: DeploymentStatus::Active,
-                contracts: vec![Contract {
-                    name: "Vault".to_string(),
-                    address: "0xOLD".to_string(),
-                }],
+                contracts: vec![contract("Vault", "0xOLD")],
             },
         );
 
@@ -427,17 +1355,15 @@ mod tests {
             Deployment {
                 version: DeploymentVersion::V2,
                 status: DeploymentStatus::Active,
-                contracts: vec![Contract {
-                    name: "Vault".to_string(),
-                    address: "0xNEW".to_string(),
-                }],
+                contracts: vec![contract("Vault", "0xNEW")],
             },
         );
 
-        let result = process_contracts_with_latest_deployments(deployments, 1);
+        let result =
+            process_contracts_with_latest_deployments(deployments, 1, &ParseOptions::default());
         assert!(result.is_ok());
 
-        let contracts = result.unwrap();
+        let contracts = result.unwrap().contracts;
         assert_eq!(contracts.len(), 1);
         assert_eq!(contracts.get("Vault"), Some(&"0xNEW".to_string()));
     }
@@ -451,10 +1377,7 @@ mod tests {
             Deployment {
                 version: DeploymentVersion::V2,
                 status: DeploymentStatus::Active,
-                contracts: vec![Contract {
-                    name: "Vault".to_string(),
-                    address: "0xNEW".to_string(),
-                }],
+                contracts: vec![contract("Vault", "0xNEW")],
             },
         );
 
@@ -463,17 +1386,15 @@ mod tests {
             Deployment {
                 version: DeploymentVersion::V2,
                 status: DeploymentStatus::Active,
-                contracts: vec![Contract {
-                    name: "Vault".to_string(),
-                    address: "0xOLD".to_string(),
-                }],
+                contracts: vec![contract("Vault", "0xOLD")],
             },
         );
 
-        let result = process_contracts_with_latest_deployments(deployments, 1);
+        let result =
+            process_contracts_with_latest_deployments(deployments, 1, &ParseOptions::default());
         assert!(result.is_ok());
 
-        let contracts = result.unwrap();
+        let contracts = result.unwrap().contracts;
         assert_eq!(contracts.len(), 1);
         assert_eq!(contracts.get("Vault"), Some(&"0xNEW".to_string()));
     }
@@ -488,14 +1409,8 @@ mod tests {
                 version: DeploymentVersion::V2,
                 status: DeploymentStatus::Active,
                 contracts: vec![
-                    Contract {
-                        name: "Vault".to_string(),
-                        address: "0xVaultOld".to_string(),
-                    },
-                    Contract {
-                        name: "Router".to_string(),
-                        address: "0xRouterOld".to_string(),
-                    },
+                    contract("Vault", "0xVaultOld"),
+                    contract("Router", "0xRouterOld"),
                 ],
             },
         );
@@ -505,17 +1420,15 @@ mod tests {
             Deployment {
                 version: DeploymentVersion::V2,
                 status: DeploymentStatus::Active,
-                contracts: vec![Contract {
-                    name: "Vault".to_string(),
-                    address: "0xVaultNew".to_string(),
-                }],
+                contracts: vec![contract("Vault", "0xVaultNew")],
             },
         );
 
-        let result = process_contracts_with_latest_deployments(deployments, 1);
+        let result =
+            process_contracts_with_latest_deployments(deployments, 1, &ParseOptions::default());
         assert!(result.is_ok());
 
-        let contracts = result.unwrap();
+        let contracts = result.unwrap().contracts;
         assert_eq!(contracts.len(), 2);
         assert_eq!(contracts.get("Vault"), Some(&"0xVaultNew".to_string()));
         assert_eq!(contracts.get("Router"), Some(&"0xRouterOld".to_string()));
@@ -524,10 +1437,714 @@ mod tests {
     #[test]
     fn test_process_contracts_empty_deployments() {
         let deployments = HashMap::new();
-        let result = process_contracts_with_latest_deployments(deployments, 1);
+        let result =
+            process_contracts_with_latest_deployments(deployments, 1, &ParseOptions::default());
 
         assert!(result.is_ok());
-        let contracts = result.unwrap();
+        let contracts = result.unwrap().contracts;
         assert_eq!(contracts.len(), 0);
     }
+
+    fn deployments_with_deprecated_vault() -> HashMap<String, Deployment> {
+        HashMap::from([(
+            "20250101-deploy".to_string(),
+            Deployment {
+                version: DeploymentVersion::V2,
+                status: DeploymentStatus::Active,
+                contracts: vec![
+                    contract("Vault_deprecated", "0xOLD"),
+                    contract("Router", "0xROUTER"),
+                ],
+            },
+        )])
+    }
+
+    #[test]
+    fn test_process_contracts_strips_deprecated_suffix() {
+        let options = ParseOptions {
+            deprecated_contracts: DeprecatedContracts::Strip,
+            ..ParseOptions::default()
+        };
+
+        let processed = process_contracts_with_latest_deployments(
+            deployments_with_deprecated_vault(),
+            1,
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(processed.contracts.get("Vault"), Some(&"0xOLD".to_string()));
+        assert!(!processed.contracts.contains_key("Vault_deprecated"));
+        assert_eq!(
+            processed.metadata["Vault"].status,
+            Some(ContractStatus::Deprecated)
+        );
+        assert!(!processed.metadata.contains_key("Router"));
+    }
+
+    #[test]
+    fn test_process_contracts_drops_deprecated_suffix() {
+        let options = ParseOptions {
+            deprecated_contracts: DeprecatedContracts::Drop,
+            ..ParseOptions::default()
+        };
+
+        let processed = process_contracts_with_latest_deployments(
+            deployments_with_deprecated_vault(),
+            1,
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(processed.contracts.len(), 1);
+        assert!(processed.contracts.contains_key("Router"));
+        assert!(processed.metadata.is_empty());
+    }
+
+    #[test]
+    fn test_process_contracts_live_contract_wins_over_stripped_deprecated() {
+        let mut deployments = deployments_with_deprecated_vault();
+        deployments.insert(
+            "20240101-vault".to_string(),
+            Deployment {
+                version: DeploymentVersion::V2,
+                status: DeploymentStatus::Active,
+                contracts: vec![contract("Vault", "0xLIVE")],
+            },
+        );
+
+        let processed =
+            process_contracts_with_latest_deployments(deployments, 1, &ParseOptions::default())
+                .unwrap();
+
+        assert_eq!(
+            processed.contracts.get("Vault"),
+            Some(&"0xLIVE".to_string())
+        );
+        assert!(processed.metadata.is_empty());
+    }
+
+    #[test]
+    fn test_parse_accepts_address_field_aliases() {
+        let (v2, v3) = parse("tests/fixtures/balancer").unwrap();
+        let (aliased_v2, aliased_v3) = parse("tests/fixtures/aliases/balancer").unwrap();
+
+        assert!(!v2.chains.is_empty());
+        assert_eq!(aliased_v2.chains, v2.chains);
+        assert_eq!(aliased_v3.chains, v3.chains);
+    }
+
+    #[test]
+    fn test_parse_carries_create2_into_enriched_output() {
+        let (v2, _) = parse("tests/fixtures/create2/balancer").unwrap();
+
+        let enriched = v2.enriched_chain(1).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&enriched["Vault"]).unwrap(),
+            serde_json::json!({
+                "address": "0xBA12222222228d8Ba445958a75a0704d566BF2C8",
+                "create2": {
+                    "deployer": "0x4e59b44847b379578588920cA78FbF26c0B4956C",
+                    "salt": "0x0000000000000000000000000000000000000000000000000000000000000001"
+                }
+            })
+        );
+        assert_eq!(enriched["BalancerHelpers"].metadata.create2, None);
+    }
+
+    #[test]
+    fn test_parse_skips_disabled_networks() {
+        let mut warnings = Vec::new();
+
+        let (v2, _) = parse_repos(
+            &["tests/fixtures/disabled-network/balancer"],
+            &ParseOptions::default(),
+            &mut warnings,
+        )
+        .unwrap();
+
+        assert_eq!(v2.chains.keys().collect::<Vec<_>>(), vec![&1]);
+        let skipped: Vec<_> = warnings
+            .iter()
+            .map(|warning| (warning.kind, warning.chain_id))
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![
+                (WarningKind::SkippedNetwork, Some(5)),
+                (WarningKind::SkippedNetwork, Some(250))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_repos_merges_v2_and_v3_of_same_chain() {
+        let (v2, v3) = parse_repos(
+            &[
+                "tests/fixtures/multi-repo/monorepo",
+                "tests/fixtures/multi-repo/v3",
+            ],
+            &ParseOptions::default(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            v2.chains[&1]["Vault"],
+            "0xBA12222222228d8Ba445958a75a0704d566BF2C8"
+        );
+        assert_eq!(
+            v3.chains[&1]["Vault"],
+            "0xbA1333333333a1BA1108E8412f11850A5C319bA9"
+        );
+    }
+
+    #[test]
+    fn test_parse_repos_pools_chain_named_differently() {
+        let (v2, _) = parse_repos(
+            &[
+                "tests/fixtures/multi-repo/monorepo",
+                "tests/fixtures/multi-repo/renamed-network",
+            ],
+            &ParseOptions::default(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(v2.chains.keys().collect::<Vec<_>>(), vec![&1]);
+        assert_eq!(
+            v2.chains[&1]["Vault"],
+            "0xBA12222222228d8Ba445958a75a0704d566BF2C8"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_chain_id_defined_twice_in_a_repo() {
+        let result = parse("tests/fixtures/multi-repo/duplicate-chain-id");
+
+        assert!(matches!(
+            result,
+            Err(ParseError::ChainIdAlreadyExists { chain_id: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_parse_repos_rejects_network_on_different_chains() {
+        let result = parse_repos(
+            &[
+                "tests/fixtures/multi-repo/monorepo",
+                "tests/fixtures/multi-repo/other-chain",
+            ],
+            &ParseOptions::default(),
+            &mut Vec::new(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(ParseError::ConflictingNetwork {
+                chain_ids: (1, 5),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_repos_rejects_same_deployment_at_different_addresses() {
+        let result = parse_repos(
+            &[
+                "tests/fixtures/multi-repo/monorepo",
+                "tests/fixtures/multi-repo/mirror",
+            ],
+            &ParseOptions::default(),
+            &mut Vec::new(),
+        );
+
+        let Err(ParseError::ConflictingDeployments { conflicts }) = result else {
+            panic!("Expected ConflictingDeployments, got {:?}", result);
+        };
+        assert_eq!(
+            conflicts,
+            vec![
+                "Vault of deployment '20210418-vault' on chain 1: \
+                 '0xBA12222222228d8Ba445958a75a0704d566BF2C8' vs \
+                 '0xBA12222222228d8Ba445958a75a0704d566BF2C9'"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_repos_pools_repo_lacking_newer_deployments() {
+        let (v2, _) = parse_repos(
+            &[
+                "tests/fixtures/multi-repo/monorepo",
+                "tests/fixtures/multi-repo/ahead",
+            ],
+            &ParseOptions::default(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            v2.chains[&1]["Vault"],
+            "0x00000000000000000000000000000000000000aa"
+        );
+    }
+
+    #[test]
+    fn test_parse_repos_rejects_same_deployment_of_different_versions() {
+        let result = parse_repos(
+            &[
+                "tests/fixtures/multi-repo/monorepo",
+                "tests/fixtures/multi-repo/relabeled",
+            ],
+            &ParseOptions::default(),
+            &mut Vec::new(),
+        );
+
+        let Err(ParseError::ConflictingDeployments { conflicts }) = result else {
+            panic!("Expected ConflictingDeployments, got {:?}", result);
+        };
+        assert_eq!(
+            conflicts,
+            vec!["deployment '20210418-vault' on chain 1: version v2 vs v3"]
+        );
+    }
+
+    #[test]
+    fn test_process_contracts_newer_disabled_contract_hides_older_live_one() {
+        let factory = |contract: Contract| Deployment {
+            version: DeploymentVersion::V2,
+            status: DeploymentStatus::Active,
+            contracts: vec![contract],
+        };
+        let deployments = HashMap::from([
+            (
+                "20220101-weighted-pool".to_string(),
+                factory(contract("WeightedPoolFactory", "0x01")),
+            ),
+            (
+                "20230101-weighted-pool-v2".to_string(),
+                factory(Contract {
+                    disabled: true,
+                    ..contract("WeightedPoolFactory", "0x02")
+                }),
+            ),
+        ]);
+
+        let processed =
+            process_contracts_with_latest_deployments(deployments, 1, &ParseOptions::default())
+                .unwrap();
+
+        assert!(!processed.contracts.contains_key("WeightedPoolFactory"));
+        assert!(!processed.signatures.contains_key("WeightedPoolFactory"));
+    }
+
+    #[test]
+    fn test_process_contracts_counts_candidates() {
+        let vault = |address: &str| Deployment {
+            version: DeploymentVersion::V2,
+            status: DeploymentStatus::Active,
+            contracts: vec![contract("Vault", address)],
+        };
+        let deployments = HashMap::from([
+            ("20210418-vault".to_string(), vault("0x01")),
+            ("20220101-vault".to_string(), vault("0x02")),
+            ("20230101-vault".to_string(), vault("0x03")),
+        ]);
+
+        let processed =
+            process_contracts_with_latest_deployments(deployments, 1, &ParseOptions::default())
+                .unwrap();
+
+        assert_eq!(processed.contracts["Vault"], "0x03");
+        assert_eq!(processed.candidates["Vault"], 3);
+    }
+
+    #[test]
+    fn test_old_deployments_are_stale() {
+        let (v2, v3) = parse("tests/fixtures/balancer").unwrap();
+        let newest_deployment = NaiveDate::from_ymd_opt(2024, 12, 5).unwrap();
+
+        assert_eq!(v2.newest_deployment(), Some(newest_deployment));
+        assert_eq!(v3.newest_deployment(), Some(newest_deployment));
+        assert_eq!(
+            v2.newest_deployments[&8453],
+            NaiveDate::from_ymd_opt(2024, 12, 4).unwrap()
+        );
+
+        let date = |month, day| NaiveDate::from_ymd_opt(2025, month, day).unwrap();
+        assert!(is_stale(newest_deployment, date(1, 5), 30));
+        assert!(!is_stale(newest_deployment, date(1, 4), 30));
+        // A max age beyond what days fit in an i64 never makes a source stale
+        assert!(!is_stale(newest_deployment, date(1, 5), u64::MAX));
+    }
+
+    #[test]
+    fn test_merge_versions_keeps_both_vaults() {
+        let chain = |contracts: &[(&str, &str)]| {
+            HashMap::from([(
+                1,
+                contracts
+                    .iter()
+                    .map(|(name, address)| (name.to_string(), address.to_string()))
+                    .collect::<ChainContracts>(),
+            )])
+        };
+        let v2 = ProtocolDeployments::new("balancer-v2", chain(&[("Vault", "0x02")]));
+        let v3 = ProtocolDeployments::new(
+            "balancer-v3",
+            chain(&[("Vault", "0x03"), ("Router", "0x04")]),
+        );
+
+        let merged = merge_versions(v2, v3);
+
+        assert_eq!(merged.protocol_name, MERGED_PROTOCOL_NAME);
+        let contracts = &merged.chains[&1];
+        assert_eq!(contracts["Vault-v2"], "0x02");
+        assert_eq!(contracts["Vault-v3"], "0x03");
+        assert_eq!(contracts["Router"], "0x04");
+        assert!(!contracts.contains_key("Vault"));
+
+        let metadata = &merged.metadata[&1];
+        assert_eq!(metadata["Vault-v2"].version.as_deref(), Some("v2"));
+        assert_eq!(metadata["Router"].version.as_deref(), Some("v3"));
+    }
+
+    #[test]
+    fn test_parse_quarantines_unversioned_deployment() {
+        let mut warnings = Vec::new();
+
+        let (v2, v3) = parse_repos(
+            &["tests/fixtures/unversioned/balancer"],
+            &ParseOptions::default(),
+            &mut warnings,
+        )
+        .unwrap();
+
+        assert_eq!(
+            v2.chains[&1]["Vault"],
+            "0xBA12222222228d8Ba445958a75a0704d566BF2C8"
+        );
+        assert!(!v2.chains[&1].contains_key("MysteryRouter"));
+        assert!(v3.chains.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::Quarantined);
+        assert!(warnings[0].detail.contains("20250101-mystery-router"));
+    }
+
+    #[test]
+    fn test_parse_versions_emits_unknown_version_as_own_protocol() {
+        let mut warnings = Vec::new();
+
+        let protocols = parse_versions(
+            &["tests/fixtures/v4/balancer"],
+            &ParseOptions::default(),
+            &mut warnings,
+        )
+        .unwrap();
+
+        let names: Vec<_> = protocols
+            .iter()
+            .map(|protocol_deployments| protocol_deployments.protocol_name.as_str())
+            .collect();
+        assert_eq!(names, ["balancer-v2", "balancer-v3", "balancer-v4"]);
+        assert_eq!(
+            protocols[0].chains[&1]["Vault"],
+            "0xBA12222222228d8Ba445958a75a0704d566BF2C8"
+        );
+        assert_eq!(
+            protocols[2].chains[&1]["Vault"],
+            "0xBA14444444444444444444444444444444444444"
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::UnknownVersion);
+        assert_eq!(warnings[0].protocol.as_deref(), Some("balancer-v4"));
+
+        let (v2, v3) = parse_repos(
+            &["tests/fixtures/v4/balancer"],
+            &ParseOptions::default(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(v2.chains, protocols[0].chains);
+        assert!(v3.chains.is_empty());
+    }
+
+    #[test]
+    fn test_parse_versions_quarantines_invalid_versions_and_orders_by_number() {
+        let mut warnings = Vec::new();
+
+        let protocols = parse_versions(
+            &["tests/fixtures/invalid-versions/balancer"],
+            &ParseOptions::default(),
+            &mut warnings,
+        )
+        .unwrap();
+
+        let names: Vec<_> = protocols
+            .iter()
+            .map(|protocol_deployments| protocol_deployments.protocol_name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            ["balancer-v2", "balancer-v3", "balancer-v4", "balancer-v10"]
+        );
+        assert!(protocols.iter().all(|protocol_deployments| {
+            protocol_deployments
+                .chains
+                .values()
+                .all(|contracts| !contracts.contains_key("Router"))
+        }));
+        assert_eq!(
+            protocols[3].chains[&1]["Vault"],
+            "0xBA10101010101010101010101010101010101010"
+        );
+
+        let quarantined: Vec<_> = warnings
+            .iter()
+            .filter(|warning| warning.kind == WarningKind::Quarantined)
+            .map(|warning| warning.detail.as_str())
+            .collect();
+        assert_eq!(
+            quarantined,
+            [
+                "balancer deployment '20280101-blank-router' has invalid version ''",
+                "balancer deployment '20280102-beta-router' has invalid version 'v4 beta'",
+                "balancer deployment '20280103-traversal-router' has invalid version '/../../x'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deployment_version_accepts_only_v_number_as_other() {
+        let version = |version: &str| DeploymentVersion::from(version.to_string());
+
+        assert_eq!(version("V2"), DeploymentVersion::V2);
+        assert_eq!(version("v4"), DeploymentVersion::Other(4));
+        assert_eq!(
+            version("v10").protocol_name().as_deref(),
+            Some("balancer-v10")
+        );
+        assert!(version("v4") < version("v10"));
+        for invalid in ["", "v", "v4 beta", "/../../x", "v-1"] {
+            assert_eq!(
+                version(invalid),
+                DeploymentVersion::Invalid(invalid.to_string())
+            );
+            assert_eq!(version(invalid).protocol_name(), None);
+        }
+    }
+
+    #[test]
+    fn test_parse_skips_null_addresses() {
+        let (v2, _) = parse("tests/fixtures/placeholders/balancer").unwrap();
+
+        assert_eq!(
+            v2.chains[&1]["Vault"],
+            "0xBA12222222228d8Ba445958a75a0704d566BF2C8"
+        );
+        assert!(!v2.chains[&1].contains_key("BalancerHelpers"));
+    }
+
+    #[test]
+    fn test_parse_skips_disabled_factories() {
+        let (v2, _) = parse("tests/fixtures/disabled-factory/balancer").unwrap();
+
+        let contracts = &v2.chains[&1];
+        assert!(contracts.contains_key("Vault"));
+        assert!(contracts.contains_key("ComposableStablePoolFactory"));
+        assert!(!contracts.contains_key("WeightedPoolFactory"));
+        assert!(!contracts.contains_key("LiquidityBootstrappingPoolFactory"));
+    }
+
+    #[test]
+    fn test_parse_supported_networks_array_matches_map() {
+        let (map_v2, map_v3) = parse("tests/fixtures/balancer").unwrap();
+        let (array_v2, array_v3) = parse("tests/fixtures/networks-array/balancer").unwrap();
+
+        assert!(!array_v2.chains.is_empty());
+        assert_eq!(array_v2.chains, map_v2.chains);
+        assert_eq!(array_v3.chains, map_v3.chains);
+    }
+
+    #[test]
+    fn test_v3_hooks_are_optional_and_grouped() {
+        let (_, v3) = parse("tests/fixtures/v3-hooks/balancer").unwrap();
+
+        assert!(v3.chains[&1].contains_key("StableSurgeHook"));
+        assert!(!v3.chains[&8453].contains_key("StableSurgeHook"));
+        assert_eq!(v3.required_contracts, ["Router", "Vault"]);
+        assert_eq!(v3.completeness(1), Some(1.0));
+        // Only the router, which mainnet has, counts against base
+        assert_eq!(v3.missing_contracts(8453), ["Router"]);
+        assert_eq!(v3.completeness(8453), Some(0.5));
+
+        let groups = crate::groups::group(&v3.protocol_name, &v3.chains[&1]);
+        assert_eq!(
+            groups["hooks"].keys().collect::<Vec<_>>(),
+            ["StableSurgeHook"]
+        );
+        assert_eq!(groups["routers"].keys().collect::<Vec<_>>(), ["Router"]);
+    }
+
+    #[test]
+    fn test_parse_sharded_network_matches_consolidated_file() {
+        let (v2, v3) = parse("tests/fixtures/balancer").unwrap();
+        let (sharded_v2, sharded_v3) = parse("tests/fixtures/sharded/balancer").unwrap();
+
+        assert!(sharded_v2.chains[&1].contains_key("Vault"));
+        assert_eq!(sharded_v2.chains, v2.chains);
+        assert_eq!(sharded_v3.chains, v3.chains);
+        assert_eq!(sharded_v3.signatures, v3.signatures);
+    }
+
+    #[test]
+    fn test_parse_tarball_matches_directory() {
+        let (dir_v2, dir_v3) = parse("tests/fixtures/balancer").unwrap();
+        let (tar_v2, tar_v3) = parse("tests/fixtures/balancer.tar.gz").unwrap();
+
+        assert!(!tar_v2.chains.is_empty());
+        assert_eq!(tar_v2.chains, dir_v2.chains);
+        assert_eq!(tar_v3.chains, dir_v3.chains);
+        assert_eq!(tar_v3.metadata, dir_v3.metadata);
+    }
+
+    #[test]
+    fn test_parse_nested_deployments_matches_root() {
+        let (v2, v3) = parse("tests/fixtures/balancer").unwrap();
+        let (nested_v2, nested_v3) = parse("tests/fixtures/nested/balancer").unwrap();
+
+        assert!(!nested_v2.chains.is_empty());
+        assert_eq!(nested_v2.chains, v2.chains);
+        assert_eq!(nested_v3.chains, v3.chains);
+    }
+
+    #[test]
+    fn test_wrapper_key_is_only_read_when_root_has_no_deployments() {
+        let deployment = r#"{"status": "ACTIVE", "version": "v2", "contracts": []}"#;
+        let nested: NetworkDeployments = serde_json::from_str(&format!(
+            r#"{{"deployment": {{"20210418-vault": {}}}}}"#,
+            deployment
+        ))
+        .unwrap();
+        let both: NetworkDeployments = serde_json::from_str(&format!(
+            r#"{{"20210418-vault": {0}, "addresses": {{"20220101-other": {0}}}}}"#,
+            deployment
+        ))
+        .unwrap();
+
+        assert!(nested.deployments.contains_key("20210418-vault"));
+        assert_eq!(
+            both.deployments.keys().collect::<Vec<_>>(),
+            ["20210418-vault"]
+        );
+    }
+
+    #[test]
+    fn test_parse_keeps_compiler_version() {
+        let (v2, _) = parse("tests/fixtures/compiler/balancer").unwrap();
+
+        let metadata = &v2.metadata[&1];
+        assert_eq!(
+            metadata["Vault"].compiler_version.as_deref(),
+            Some("v0.7.1+commit.f4a555be")
+        );
+        assert_eq!(
+            metadata["BalancerHelpers"].compiler_version.as_deref(),
+            Some("0.7.1")
+        );
+        assert!(!metadata.contains_key("ProtocolFeesCollector"));
+    }
+
+    #[test]
+    fn test_parse_keeps_block_created() {
+        let (v2, _) = parse("tests/fixtures/compiler/balancer").unwrap();
+
+        let metadata = &v2.metadata[&1];
+        assert_eq!(metadata["Vault"].block_created, Some(12272146));
+        assert_eq!(metadata["BalancerHelpers"].block_created, None);
+    }
+
+    #[test]
+    fn test_relayer_is_tagged_with_its_vault_version() {
+        let relayer = |version: DeploymentVersion, address: &str| Deployment {
+            version,
+            status: DeploymentStatus::Active,
+            contracts: vec![contract("BalancerRelayer", address)],
+        };
+        let v2_deployments = HashMap::from([
+            (
+                "20230314-batch-relayer-v5".to_string(),
+                relayer(DeploymentVersion::V2, "0x05"),
+            ),
+            (
+                "20231031-batch-relayer-v6".to_string(),
+                relayer(DeploymentVersion::V2, "0x06"),
+            ),
+        ]);
+        let v3_deployments = HashMap::from([(
+            "20241205-v3-batch-router".to_string(),
+            relayer(DeploymentVersion::V3, "0x07"),
+        )]);
+
+        let v2 =
+            process_contracts_with_latest_deployments(v2_deployments, 1, &ParseOptions::default())
+                .unwrap();
+        let v3 =
+            process_contracts_with_latest_deployments(v3_deployments, 1, &ParseOptions::default())
+                .unwrap();
+
+        assert_eq!(v2.contracts["BalancerRelayer"], "0x06");
+        assert_eq!(
+            v2.metadata["BalancerRelayer"].relayer_version.as_deref(),
+            Some("v2")
+        );
+        assert_eq!(
+            v3.metadata["BalancerRelayer"].relayer_version.as_deref(),
+            Some("v3")
+        );
+        assert_eq!(relayer_version("Vault", &DeploymentVersion::V2), None);
+    }
+
+    #[test]
+    fn test_parse_skips_deployignored_network_file() {
+        let (v2, _) = parse("tests/fixtures/deployignore/balancer").unwrap();
+
+        assert_eq!(v2.chains.keys().collect::<Vec<_>>(), vec![&1]);
+    }
+
+    #[test]
+    fn test_malformed_file_error_names_the_file() {
+        let err = parse("tests/fixtures/malformed/balancer").unwrap_err();
+
+        let message = err.to_string();
+        assert!(message.contains("tests/fixtures/malformed/balancer/addresses/mainnet.json"));
+        assert!(message.contains("line 7"));
+    }
+
+    #[test]
+    fn test_parse_lowercase_statuses() {
+        let mut warnings = Vec::new();
+
+        let (v2, _) = parse_repos(
+            &["tests/fixtures/lowercase-status/balancer"],
+            &ParseOptions::default(),
+            &mut warnings,
+        )
+        .unwrap();
+
+        assert_eq!(
+            v2.chains[&1]["Vault"],
+            "0xBA12222222228d8Ba445958a75a0704d566BF2C8"
+        );
+        assert!(!v2.chains[&1].contains_key("Authorizer"));
+        assert!(!v2.chains[&1].contains_key("WeightedPoolFactory"));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::UnknownStatus);
+        assert_eq!(
+            warnings[0].detail,
+            "balancer deployment '20210418-weighted-pool' has unknown status 'paused'"
+        );
+    }
 }
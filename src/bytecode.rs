@@ -0,0 +1,81 @@
+use crate::types::ChainId;
+
+const PUSH1: u8 = 0x60;
+const PUSH4: u8 = 0x63;
+const PUSH32: u8 = 0x7f;
+
+/// Source of deployed contract bytecode, e.g. an RPC endpoint per chain
+pub trait CodeProvider: Sync {
+    /// Runtime bytecode at `address`, `None` when the chain isn't covered
+    fn code(&self, chain_id: ChainId, address: &str) -> Result<Option<Vec<u8>>, std::io::Error>;
+}
+
+/// Whether the bytecode pushes `selector` as a `PUSH4` operand, as function dispatchers do.
+/// Push data is skipped, so the selector bytes appearing inside other operands don't count.
+pub fn exposes_selector(code: &[u8], selector: [u8; 4]) -> bool {
+    let mut position = 0;
+    while position < code.len() {
+        let opcode = code[position];
+        if opcode == PUSH4 && code.get(position + 1..position + 5) == Some(&selector[..]) {
+            return true;
+        }
+
+        position += 1;
+        if (PUSH1..=PUSH32).contains(&opcode) {
+            position += (opcode - PUSH1 + 1) as usize;
+        }
+    }
+
+    false
+}
+
+/// Bytes of a hex string, with or without `0x` prefix
+pub fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+/// A 4-byte function selector, e.g. `0xa1671295`
+pub fn parse_selector(hex: &str) -> Option<[u8; 4]> {
+    decode_hex(hex)?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exposes_selector_only_as_push4_operand() {
+        let selector = parse_selector("0xa1671295").unwrap();
+
+        // PUSH4 a1671295 EQ
+        assert!(exposes_selector(
+            &decode_hex("63a167129514").unwrap(),
+            selector
+        ));
+        // PUSH5 00a1671295: the selector bytes are data of another push
+        assert!(!exposes_selector(
+            &decode_hex("6400a1671295").unwrap(),
+            selector
+        ));
+        // Truncated PUSH4
+        assert!(!exposes_selector(
+            &decode_hex("63a16712").unwrap(),
+            selector
+        ));
+    }
+
+    #[test]
+    fn test_parse_selector_requires_four_bytes() {
+        assert_eq!(parse_selector("c9c65396"), Some([0xc9, 0xc6, 0x53, 0x96]));
+        assert_eq!(parse_selector("0xc9c653"), None);
+        assert_eq!(parse_selector("0xzzc65396"), None);
+    }
+}
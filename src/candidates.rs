@@ -0,0 +1,31 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::types::{ChainCandidates, ChainId, ProtocolDeployments};
+
+pub const CANDIDATES_FILE_NAME: &str = "candidates.json";
+
+/// Candidate deployment counts per protocol, chain and contract, as written to
+/// `candidates.json`
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct CandidatesReport(BTreeMap<String, BTreeMap<ChainId, ChainCandidates>>);
+
+/// Counts of the protocols tracking them
+pub fn report(protocols: &[ProtocolDeployments]) -> CandidatesReport {
+    let protocols = protocols
+        .iter()
+        .filter(|protocol_deployments| !protocol_deployments.candidates.is_empty())
+        .map(|protocol_deployments| {
+            let chains = protocol_deployments
+                .candidates
+                .iter()
+                .map(|(chain_id, candidates)| (*chain_id, candidates.clone()))
+                .collect();
+            (protocol_deployments.protocol_name.clone(), chains)
+        })
+        .collect();
+
+    CandidatesReport(protocols)
+}
@@ -0,0 +1,210 @@
+use std::{collections::BTreeMap, fs::File, io::BufReader};
+
+use crate::types::ChainId;
+
+/// Aliased chain id → canonical chain id its contracts are folded into
+pub type ChainAliases = BTreeMap<ChainId, ChainId>;
+
+/// Chains known at the time of writing, ordered by id. New chains appear regularly,
+/// so an id missing here isn't necessarily wrong.
+pub const KNOWN_CHAINS: &[(ChainId, &str)] = &[
+    (1, "Ethereum"),
+    (10, "OP Mainnet"),
+    (56, "BNB Smart Chain"),
+    (97, "BNB Smart Chain Testnet"),
+    (100, "Gnosis"),
+    (130, "Unichain"),
+    (137, "Polygon"),
+    (143, "Monad"),
+    (146, "Sonic"),
+    (196, "X Layer"),
+    (250, "Fantom"),
+    (252, "Fraxtal"),
+    (288, "Boba"),
+    (324, "zkSync Era"),
+    (480, "World Chain"),
+    (999, "HyperEVM"),
+    (1101, "Polygon zkEVM"),
+    (1135, "Lisk"),
+    (1301, "Unichain Sepolia"),
+    (1329, "Sei"),
+    (1868, "Soneium"),
+    (1923, "Swellchain"),
+    (2020, "Ronin"),
+    (2741, "Abstract"),
+    (5000, "Mantle"),
+    (8453, "Base"),
+    (9745, "Plasma"),
+    (10143, "Monad Testnet"),
+    (17000, "Holesky"),
+    (34443, "Mode"),
+    (42161, "Arbitrum One"),
+    (42220, "Celo"),
+    (43111, "Hemi"),
+    (43113, "Avalanche Fuji"),
+    (43114, "Avalanche"),
+    (57073, "Ink"),
+    (59144, "Linea"),
+    (60808, "BOB"),
+    (80002, "Polygon Amoy"),
+    (80094, "Berachain"),
+    (81457, "Blast"),
+    (84532, "Base Sepolia"),
+    (98866, "Plume"),
+    (421614, "Arbitrum Sepolia"),
+    (534352, "Scroll"),
+    (747474, "Katana"),
+    (7777777, "Zora"),
+    (11155111, "Sepolia"),
+    (11155420, "OP Sepolia"),
+];
+
+/// EIP-3770 short names of known chains, as registered in ethereum-lists/chains
+pub const SHORT_NAMES: &[(&str, ChainId)] = &[
+    ("eth", 1),
+    ("oeth", 10),
+    ("bnb", 56),
+    ("gno", 100),
+    ("unichain", 130),
+    ("pol", 137),
+    ("matic", 137),
+    ("sonic", 146),
+    ("zksync", 324),
+    ("mantle", 5000),
+    ("base", 8453),
+    ("mode", 34443),
+    ("arb1", 42161),
+    ("celo", 42220),
+    ("avax", 43114),
+    ("ink", 57073),
+    ("linea", 59144),
+    ("blastmainnet", 81457),
+    ("scr", 534352),
+    ("zora", 7777777),
+    ("sep", 11155111),
+];
+
+/// Network ids The Graph names chains by in subgraph manifests, ordered by chain id
+pub const GRAPH_NETWORKS: &[(ChainId, &str)] = &[
+    (1, "mainnet"),
+    (10, "optimism"),
+    (56, "bsc"),
+    (100, "gnosis"),
+    (130, "unichain"),
+    (137, "matic"),
+    (146, "sonic"),
+    (250, "fantom"),
+    (252, "fraxtal"),
+    (324, "zksync-era"),
+    (1101, "polygon-zkevm"),
+    (5000, "mantle"),
+    (8453, "base"),
+    (17000, "holesky"),
+    (34443, "mode-mainnet"),
+    (42161, "arbitrum-one"),
+    (42220, "celo"),
+    (43114, "avalanche"),
+    (59144, "linea"),
+    (80094, "berachain"),
+    (81457, "blast-mainnet"),
+    (84532, "base-sepolia"),
+    (421614, "arbitrum-sepolia"),
+    (534352, "scroll"),
+    (11155111, "sepolia"),
+    (11155420, "optimism-sepolia"),
+];
+
+/// Chain id of an EIP-3770 short name
+pub fn chain_id_for_short_name(short_name: &str) -> Option<ChainId> {
+    SHORT_NAMES
+        .iter()
+        .find(|(name, _)| *name == short_name)
+        .map(|(_, chain_id)| *chain_id)
+}
+
+/// Name of a known chain
+pub fn name(chain_id: ChainId) -> Option<&'static str> {
+    KNOWN_CHAINS
+        .binary_search_by_key(&chain_id, |(id, _)| *id)
+        .ok()
+        .map(|index| KNOWN_CHAINS[index].1)
+}
+
+/// Lowercase, dash-separated network name of a chain, as used by hardhat configs, e.g.
+/// `mainnet` for Ethereum and `arbitrum-one` for Arbitrum One. `chain-<id>` when unknown.
+pub fn network_name(chain_id: ChainId) -> String {
+    if chain_id == 1 {
+        return "mainnet".to_string();
+    }
+
+    let Some(name) = name(chain_id) else {
+        return format!("chain-{}", chain_id);
+    };
+
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Network id of a chain in subgraph manifests, e.g. `matic` for Polygon
+pub fn graph_network(chain_id: ChainId) -> Option<&'static str> {
+    GRAPH_NETWORKS
+        .binary_search_by_key(&chain_id, |(id, _)| *id)
+        .ok()
+        .map(|index| GRAPH_NETWORKS[index].1)
+}
+
+pub fn is_known(chain_id: ChainId) -> bool {
+    name(chain_id).is_some()
+}
+
+/// Reads a JSON object of aliased chain id to canonical chain id, e.g. `{ "1337": 1 }`
+pub fn read_aliases(path: &str) -> Result<ChainAliases, std::io::Error> {
+    let aliases = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+
+    Ok(aliases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_chains_are_sorted_by_id() {
+        assert!(KNOWN_CHAINS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(GRAPH_NETWORKS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn test_graph_network() {
+        assert_eq!(graph_network(10), Some("optimism"));
+        assert_eq!(graph_network(56), Some("bsc"));
+        assert_eq!(graph_network(100), Some("gnosis"));
+        assert_eq!(graph_network(137), Some("matic"));
+        assert_eq!(graph_network(123456789), None);
+    }
+
+    #[test]
+    fn test_chain_id_for_short_name() {
+        assert_eq!(chain_id_for_short_name("eth"), Some(1));
+        assert_eq!(chain_id_for_short_name("base"), Some(8453));
+        assert_eq!(chain_id_for_short_name("unknown"), None);
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(name(1), Some("Ethereum"));
+        assert_eq!(name(8453), Some("Base"));
+        assert_eq!(name(123456789), None);
+    }
+
+    #[test]
+    fn test_network_name() {
+        assert_eq!(network_name(1), "mainnet");
+        assert_eq!(network_name(42161), "arbitrum-one");
+        assert_eq!(network_name(324), "zksync-era");
+        assert_eq!(network_name(123456789), "chain-123456789");
+    }
+}
@@ -0,0 +1,76 @@
+use std::{collections::BTreeMap, fs};
+
+use sha2::{Digest, Sha256};
+
+pub const CHECKSUMS_FILE_NAME: &str = "checksums.json";
+
+/// SHA-256 of every written file, keyed by its path relative to `folder`
+pub fn compute(
+    folder: &str,
+    written: &[String],
+) -> Result<BTreeMap<String, String>, std::io::Error> {
+    let mut checksums = BTreeMap::new();
+
+    for relative_path in written {
+        let contents = fs::read(format!("{}/{}", folder, relative_path))?;
+        checksums.insert(relative_path.clone(), sha256_hex(&contents));
+    }
+
+    Ok(checksums)
+}
+
+pub fn sha256_hex(contents: &[u8]) -> String {
+    Sha256::digest(contents)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        types::{ChainContracts, ProtocolDeployments},
+        write,
+    };
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_checksums_match_written_files() {
+        let folder = std::env::temp_dir()
+            .join(format!("evm-dex-index-checksums-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = fs::remove_dir_all(&folder);
+
+        let contracts = ChainContracts::from([(
+            "Permit2".to_string(),
+            "0x000000000022D473030F116dDEE9F6B43aC78BA3".to_string(),
+        )]);
+        let deployments = ProtocolDeployments::new(
+            "permit2",
+            HashMap::from([(1, contracts.clone()), (10, contracts)]),
+        );
+
+        let written = write::write(&folder, deployments, &write::JsonOptions::default()).unwrap();
+        let checksums = compute(&folder, &written).unwrap();
+
+        assert_eq!(checksums.len(), 3);
+        for (relative_path, checksum) in &checksums {
+            let contents = fs::read(format!("{}/{}", folder, relative_path)).unwrap();
+            assert_eq!(checksum, &sha256_hex(&contents));
+        }
+        assert_eq!(checksums["permit2/1.json"], checksums["permit2/10.json"]);
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,327 @@
+use std::num::NonZeroUsize;
+
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum, error::ErrorKind};
+use tracing::level_filters::LevelFilter;
+
+use evm_dex_index::{
+    balancer::DeprecatedContracts,
+    types::ChainId,
+    write::{Indent, Layout, OutputFormat},
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Index DEX contract deployments across EVM chains")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Don't show progress, and only log errors unless `--log-level` is given
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_QUIET")]
+    pub quiet: bool,
+
+    /// Maximum level of the logs, e.g. `warn` or `debug`. Defaults to `RUST_LOG`, or `info`.
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_LOG_LEVEL")]
+    pub log_level: Option<LevelFilter>,
+
+    /// Path to the balancer-deployments repo checkout, or a `.tar.gz` of its `addresses` folder
+    #[arg(
+        long,
+        global = true,
+        default_value = "source/balancer",
+        env = "EVM_DEX_INDEX_BALANCER_PATH"
+    )]
+    pub balancer_path: String,
+
+    /// Additional balancer repo checkout parsed together with `--balancer-path`,
+    /// e.g. a standalone v3 repo. Can be repeated.
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_BALANCER_EXTRA_PATH")]
+    pub balancer_extra_path: Vec<String>,
+
+    /// Git tag or commit of the balancer repo to parse, checked out into a temporary worktree
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_BALANCER_REF")]
+    pub balancer_ref: Option<String>,
+
+    /// Path to the uniswap deployments folder, or a `.tar.gz` of it
+    #[arg(
+        long,
+        global = true,
+        default_value = "source/uniswap/deployments",
+        env = "EVM_DEX_INDEX_UNISWAP_PATH"
+    )]
+    pub uniswap_path: String,
+
+    /// Folder of PancakeSwap Infinity `<chain_id>.json` files, skipped when missing
+    #[arg(
+        long,
+        global = true,
+        default_value = "source/pancakeswap/deployments",
+        env = "EVM_DEX_INDEX_PANCAKESWAP_PATH"
+    )]
+    pub pancakeswap_path: String,
+
+    /// Folder holding one deployments folder per Algebra-based DEX
+    #[arg(
+        long,
+        global = true,
+        default_value = "source/algebra",
+        env = "EVM_DEX_INDEX_ALGEBRA_PATH"
+    )]
+    pub algebra_path: String,
+
+    /// CSV of hand-maintained `protocol,chain_id,contract,address` rows, merged into the
+    /// parsed protocols (repeatable)
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_CSV_SOURCE")]
+    pub csv_source: Vec<String>,
+
+    /// Folder the deployments are written to
+    #[arg(
+        long,
+        global = true,
+        default_value = "deployments",
+        env = "EVM_DEX_INDEX_OUTPUT_DIR"
+    )]
+    pub output_dir: String,
+
+    /// Keep running, reparsing the changed sources and writing again whenever a source file
+    /// changes. Not available with a subcommand.
+    #[arg(long, env = "EVM_DEX_INDEX_WATCH")]
+    pub watch: bool,
+
+    /// How balancer `<Name>_deprecated` contracts of active deployments are handled
+    #[arg(long, global = true, value_enum, default_value_t = DeprecatedContracts::Strip, env = "EVM_DEX_INDEX_BALANCER_DEPRECATED")]
+    pub balancer_deprecated: DeprecatedContracts,
+
+    /// Fail when the newest balancer deployment of the selected chains is older than this many
+    /// days, a sign of a source mirror that hasn't been synced
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_MAX_AGE")]
+    pub max_age: Option<u64>,
+
+    /// What `--max-age` does with a stale source
+    #[arg(long, global = true, value_enum, default_value_t = StaleSourceAction::Fail, env = "EVM_DEX_INDEX_STALE_SOURCE")]
+    pub stale_source: StaleSourceAction,
+
+    /// Write balancer v2 and v3 as a single `balancer` protocol, tagging contracts with their version
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_BALANCER_MERGE")]
+    pub balancer_merge: bool,
+
+    /// Number of threads parsing runs on, defaults to one per CPU. `1` runs single-threaded.
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_WORKERS")]
+    pub workers: Option<NonZeroUsize>,
+
+    /// JSON file of chain ids folded into a canonical chain after parsing, e.g. `{ "1337": 1 }`
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_CHAIN_ALIASES")]
+    pub chain_aliases: Option<String>,
+
+    /// Only emit contracts with these names (`*` wildcard), e.g. `*Factory,*Router`.
+    /// Chains left without contracts are dropped.
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        env = "EVM_DEX_INDEX_CONTRACTS"
+    )]
+    pub contracts: Option<Vec<String>>,
+
+    /// Drop the protocol-derived prefix from contract names, e.g. `UniswapV3Factory` → `Factory`,
+    /// unless the shortened name would clash with another contract
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_STRIP_PROTOCOL_PREFIX")]
+    pub strip_protocol_prefix: bool,
+
+    /// Write a protocol under another name, as `old=new`, e.g. `uniswap-v3=univ3`. Can be repeated.
+    #[arg(long, global = true, value_parser = parse_rename, env = "EVM_DEX_INDEX_RENAME_PROTOCOL")]
+    pub rename_protocol: Vec<(String, String)>,
+
+    /// Write `delta.json` listing the chain files this run changed, for an output folder
+    /// that is a git repo of the published files
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_DELTA")]
+    pub delta: bool,
+
+    /// Write a snapshot of which contracts each protocol has on each chain to this path
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_SNAPSHOT")]
+    pub snapshot: Option<String>,
+
+    /// Fail without writing anything if the parsed coverage diverges from this snapshot
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_CHECK_SNAPSHOT")]
+    pub check_snapshot: Option<String>,
+
+    /// Also write `index.ndjson`, a `{protocol, chain_id, contract_count}` line per
+    /// protocol and chain of the index
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_OUTPUT_JSON_LINES_INDEX")]
+    pub output_json_lines_index: bool,
+
+    /// Write Prometheus textfile-format metrics of the run to this path
+    #[arg(long, global = true, env = "EVM_DEX_INDEX_METRICS_FILE")]
+    pub metrics_file: Option<String>,
+
+    /// Layout of the written deployments
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json, env = "EVM_DEX_INDEX_FORMAT")]
+    pub format: OutputFormat,
+
+    /// Write a protocol in another format than `--format`, e.g. `uniswap-v3=ts` (repeatable)
+    #[arg(long, value_parser = parse_protocol_format, env = "EVM_DEX_INDEX_FORMAT_FOR")]
+    pub format_for: Vec<(String, OutputFormat)>,
+
+    /// Placement of the `json` format's files, `flat-files` writes `<protocol>__<chain_id>.json`
+    /// into the output folder itself
+    #[arg(long, value_enum, default_value_t = Layout::Nested, env = "EVM_DEX_INDEX_LAYOUT")]
+    pub layout: Layout,
+
+    /// Folder of `<contract>.json` ABIs (bare or in an `abi` field) inlined by `--format typescript`
+    /// and `--format hardhat`
+    #[arg(long, env = "EVM_DEX_INDEX_ABIS_DIR")]
+    pub abis_dir: Option<String>,
+
+    /// Only write these protocols, e.g. `balancer-v3,uniswap-v4`
+    #[arg(long, value_delimiter = ',', env = "EVM_DEX_INDEX_PROTOCOL_ALLOWLIST")]
+    pub protocol_allowlist: Option<Vec<String>>,
+
+    /// Never write these protocols, even when allowlisted
+    #[arg(long, value_delimiter = ',', env = "EVM_DEX_INDEX_PROTOCOL_DENYLIST")]
+    pub protocol_denylist: Vec<String>,
+
+    /// Only write these chains, e.g. `1,8453`
+    #[arg(long, value_delimiter = ',', env = "EVM_DEX_INDEX_CHAINS")]
+    pub chains: Option<Vec<ChainId>>,
+
+    /// Never write these chains, even when listed in `--chains`
+    #[arg(long, value_delimiter = ',', env = "EVM_DEX_INDEX_EXCLUDE_CHAINS")]
+    pub exclude_chains: Vec<ChainId>,
+
+    /// Drop chains of a protocol missing any of its required contracts
+    #[arg(long, env = "EVM_DEX_INDEX_INCLUDE_ONLY_COMPLETE_CHAINS")]
+    pub include_only_complete_chains: bool,
+
+    /// Fail on chain ids missing from the known-chains table instead of warning
+    #[arg(long, env = "EVM_DEX_INDEX_STRICT_CHAINS")]
+    pub strict_chains: bool,
+
+    /// Fail when contracts of one protocol share an address on a chain instead of warning
+    #[arg(long, env = "EVM_DEX_INDEX_STRICT_DUPLICATE_ADDRESSES")]
+    pub strict_duplicate_addresses: bool,
+
+    /// Fail when any contract resolves to the zero address, listing all of them
+    #[arg(long, env = "EVM_DEX_INDEX_ASSERT_NO_ZERO_ADDRESSES")]
+    pub assert_no_zero_addresses: bool,
+
+    /// Escape any non-ASCII character in the JSON output
+    #[arg(long, env = "EVM_DEX_INDEX_ASCII_ONLY")]
+    pub ascii_only: bool,
+
+    /// Indentation of the JSON output: `tab` or a number of spaces
+    #[arg(long, default_value = "2", env = "EVM_DEX_INDEX_INDENT")]
+    pub indent: Indent,
+
+    /// Read every written file back and fail the run if any doesn't deserialize
+    #[arg(long, env = "EVM_DEX_INDEX_VERIFY_OUTPUT")]
+    pub verify_output: bool,
+
+    /// Name patterns (`*` wildcard) of contracts listed in `governance.json`,
+    /// defaults to proxy admins, timelocks and owner-style contracts
+    #[arg(long, value_delimiter = ',', env = "EVM_DEX_INDEX_GOVERNANCE_NAMES")]
+    pub governance_names: Option<Vec<String>>,
+
+    /// Raw base URL of the balancer-deployments repo to fetch source files from,
+    /// instead of reading the local checkout
+    #[cfg(feature = "fetch")]
+    #[arg(long, env = "EVM_DEX_INDEX_FETCH_URL_BASE")]
+    pub fetch_url_base: Option<String>,
+
+    /// Directory fetched source files are cached in
+    #[cfg(feature = "fetch")]
+    #[arg(
+        long,
+        default_value = ".cache/balancer",
+        env = "EVM_DEX_INDEX_FETCH_CACHE_DIR"
+    )]
+    pub fetch_cache_dir: String,
+
+    /// Base URL of the published registry to diff this run against, printing the
+    /// diff instead of writing anything
+    #[cfg(feature = "fetch")]
+    #[arg(long, env = "EVM_DEX_INDEX_DIFF_AGAINST_URL")]
+    pub diff_against_url: Option<String>,
+
+    /// JSON-RPC endpoint of a chain, as `chain_id=url`. Can be repeated. Uniswap contracts
+    /// no config names are then recognized by a function selector their bytecode exposes.
+    #[cfg(feature = "fetch")]
+    #[arg(long, value_parser = parse_rpc_url, env = "EVM_DEX_INDEX_VERIFY_RPC")]
+    pub verify_rpc: Vec<(ChainId, String)>,
+}
+
+impl Cli {
+    /// Parses the command line, exiting with a usage error on `--watch` with a subcommand
+    pub fn parse_args() -> Self {
+        let cli = Self::parse();
+        if let (true, Some(command)) = (cli.watch, &cli.command) {
+            Self::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    format!(
+                        "--watch can't be used with the '{}' subcommand",
+                        command.name()
+                    ),
+                )
+                .exit();
+        }
+
+        cli
+    }
+}
+
+/// What to do when the newest balancer deployment is older than `--max-age`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StaleSourceAction {
+    /// Fail the run, exit code 4
+    Fail,
+    /// Record a `stale-source` warning and carry on
+    Warn,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Parse and validate the sources, reporting every problem without writing anything
+    Validate,
+    /// Check the environment (source paths, output folder, bundled configs, git) before a run
+    Doctor,
+}
+
+impl Command {
+    fn name(&self) -> &'static str {
+        match self {
+            Command::Validate => "validate",
+            Command::Doctor => "doctor",
+        }
+    }
+}
+
+/// Parses a `chain_id=url` RPC endpoint
+#[cfg(feature = "fetch")]
+fn parse_rpc_url(value: &str) -> Result<(ChainId, String), String> {
+    match value.split_once('=') {
+        Some((chain_id, url)) if !url.is_empty() => chain_id
+            .parse()
+            .map(|chain_id| (chain_id, url.to_string()))
+            .map_err(|_| format!("invalid chain id '{}'", chain_id)),
+        _ => Err(format!("expected `chain_id=url`, got '{}'", value)),
+    }
+}
+
+/// Parses a `protocol=format` output format override
+fn parse_protocol_format(value: &str) -> Result<(String, OutputFormat), String> {
+    match value.split_once('=') {
+        Some((protocol, format)) if !protocol.is_empty() => {
+            OutputFormat::from_str(format, true).map(|format| (protocol.to_string(), format))
+        }
+        _ => Err(format!("expected `protocol=format`, got '{}'", value)),
+    }
+}
+
+/// Parses an `old=new` protocol rename
+fn parse_rename(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((old, new)) if !old.is_empty() && !new.is_empty() => {
+            Ok((old.to_string(), new.to_string()))
+        }
+        _ => Err(format!("expected `old=new`, got '{}'", value)),
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::{BTreeMap, btree_map::Entry};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    address::is_valid_address,
+    types::{ChainDeployments, ChainId, ProtocolDeployments},
+};
+
+/// A row of a hand-maintained CSV source
+#[derive(Debug, Deserialize)]
+struct Row {
+    protocol: String,
+    chain_id: String,
+    contract: String,
+    address: String,
+}
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("CSV error in '{path}': {source}")]
+    Csv { path: String, source: csv::Error },
+
+    #[error("'{path}' line {line}: invalid chain id '{value}'")]
+    InvalidChainId {
+        path: String,
+        line: usize,
+        value: String,
+    },
+
+    #[error("'{path}' line {line}: invalid address '{value}'")]
+    InvalidAddress {
+        path: String,
+        line: usize,
+        value: String,
+    },
+
+    #[error(
+        "'{path}' line {line}: contract '{contract}' of '{protocol}' on chain {chain_id} is already at another address"
+    )]
+    ConflictingAddress {
+        path: String,
+        line: usize,
+        protocol: String,
+        chain_id: ChainId,
+        contract: String,
+    },
+}
+
+/// Reads a CSV with `protocol,chain_id,contract,address` columns, for protocols maintained by
+/// hand rather than parsed from an upstream repo. Rows repeating a contract must agree on its
+/// address.
+pub fn parse(path: &str) -> Result<Vec<ProtocolDeployments>, ParseError> {
+    let csv_error = |source| ParseError::Csv {
+        path: path.to_string(),
+        source,
+    };
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .map_err(csv_error)?;
+
+    let mut protocols: BTreeMap<String, ChainDeployments> = BTreeMap::new();
+    for (index, row) in reader.deserialize::<Row>().enumerate() {
+        let row = row.map_err(csv_error)?;
+        // The header is line 1
+        let line = index + 2;
+
+        let chain_id = match row.chain_id.parse::<ChainId>() {
+            Ok(chain_id) if chain_id > 0 => chain_id,
+            _ => {
+                return Err(ParseError::InvalidChainId {
+                    path: path.to_string(),
+                    line,
+                    value: row.chain_id,
+                });
+            }
+        };
+        if !is_valid_address(&row.address) {
+            return Err(ParseError::InvalidAddress {
+                path: path.to_string(),
+                line,
+                value: row.address,
+            });
+        }
+
+        let contracts = protocols
+            .entry(row.protocol.clone())
+            .or_default()
+            .entry(chain_id)
+            .or_default();
+        match contracts.entry(row.contract) {
+            Entry::Vacant(entry) => {
+                entry.insert(row.address);
+            }
+            Entry::Occupied(entry) if entry.get().eq_ignore_ascii_case(&row.address) => {}
+            Entry::Occupied(entry) => {
+                return Err(ParseError::ConflictingAddress {
+                    path: path.to_string(),
+                    line,
+                    protocol: row.protocol,
+                    chain_id,
+                    contract: entry.key().clone(),
+                });
+            }
+        }
+    }
+
+    Ok(protocols
+        .into_iter()
+        .map(|(protocol_name, chains)| ProtocolDeployments::new(protocol_name, chains))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_groups_rows_by_protocol_and_chain() {
+        let protocols = parse("tests/fixtures/csv-source/custom.csv").unwrap();
+
+        assert_eq!(protocols.len(), 2);
+        assert_eq!(protocols[0].protocol_name, "kyber-elastic");
+        assert_eq!(protocols[0].chains.len(), 2);
+        assert_eq!(
+            protocols[1].chains[&1]["Factory"],
+            "0x0000000000000000000000000000000000000003"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_address() {
+        let err = parse("tests/fixtures/csv-source/invalid.csv").unwrap_err();
+
+        assert!(matches!(
+            err,
+            ParseError::InvalidAddress { line: 3, ref value, .. } if value == "0x1234"
+        ));
+    }
+}
@@ -0,0 +1,67 @@
+use std::path::Path;
+
+use crate::{git, write};
+
+pub const DELTA_FILE_NAME: &str = "delta.json";
+
+/// `<chain_id>.json` files of the output folder, in either layout, itself a git repo, that differ from `HEAD`
+pub fn changed_chain_files(output_dir: &str) -> Result<Vec<String>, std::io::Error> {
+    let mut changed: Vec<String> = git::changed_files(output_dir)?
+        .into_iter()
+        .filter(|path| {
+            let path = write::nested_path(path);
+            let path = Path::new(&path);
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+                && path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .is_some_and(|stem| stem.parse::<u64>().is_ok())
+        })
+        .collect();
+    changed.sort();
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, process::Command};
+
+    use super::*;
+
+    #[test]
+    fn test_changed_chain_files() {
+        let repo = std::env::temp_dir().join(format!("evm-dex-index-delta-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&repo);
+        fs::create_dir_all(repo.join("permit2")).unwrap();
+
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .arg("-C")
+                .arg(&repo)
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?} failed", args);
+        };
+
+        fs::write(repo.join("permit2/1.json"), r#"{"Permit2":"0x01"}"#).unwrap();
+        fs::write(repo.join("permit2/10.json"), r#"{"Permit2":"0x01"}"#).unwrap();
+        fs::write(repo.join("index.json"), "{}").unwrap();
+        git(&["init", "--quiet"]);
+        git(&["add", "-A"]);
+        git(&["commit", "--quiet", "-m", "published"]);
+
+        fs::write(repo.join("permit2/10.json"), r#"{"Permit2":"0x02"}"#).unwrap();
+        fs::write(repo.join("index.json"), r#"{"protocols":{}}"#).unwrap();
+
+        let changed = changed_chain_files(repo.to_str().unwrap()).unwrap();
+
+        assert_eq!(changed, vec!["permit2/10.json".to_string()]);
+
+        fs::remove_dir_all(repo).unwrap();
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{ChainContracts, ChainId, ContractAddress, ContractName};
+
+/// Address a contract moved from and to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressChange {
+    pub from: ContractAddress,
+    pub to: ContractAddress,
+}
+
+/// Contract-level changes between two versions of a chain file
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeploymentDiff {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub added: BTreeMap<ContractName, ContractAddress>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub removed: BTreeMap<ContractName, ContractAddress>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub changed: BTreeMap<ContractName, AddressChange>,
+}
+
+/// Diffs per protocol and chain, leaving out unchanged chains
+pub type RegistryDiff = BTreeMap<String, BTreeMap<ChainId, DeploymentDiff>>;
+
+impl DeploymentDiff {
+    /// Changes from `old` to `new`, comparing addresses case-insensitively
+    pub fn between(old: &ChainContracts, new: &ChainContracts) -> Self {
+        let mut diff = DeploymentDiff::default();
+
+        for (name, address) in new {
+            match old.get(name) {
+                None => {
+                    diff.added.insert(name.clone(), address.clone());
+                }
+                Some(old_address) if !old_address.eq_ignore_ascii_case(address) => {
+                    diff.changed.insert(
+                        name.clone(),
+                        AddressChange {
+                            from: old_address.clone(),
+                            to: address.clone(),
+                        },
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+
+        for (name, address) in old {
+            if !new.contains_key(name) {
+                diff.removed.insert(name.clone(), address.clone());
+            }
+        }
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contracts(entries: &[(&str, &str)]) -> ChainContracts {
+        entries
+            .iter()
+            .map(|(name, address)| (name.to_string(), address.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_between() {
+        let old = contracts(&[("Vault", "0xAA"), ("Router", "0x01"), ("Old", "0x02")]);
+        let new = contracts(&[("Vault", "0xaa"), ("Router", "0x03"), ("New", "0x04")]);
+
+        let diff = DeploymentDiff::between(&old, &new);
+
+        assert_eq!(diff.added, contracts(&[("New", "0x04")]));
+        assert_eq!(diff.removed, contracts(&[("Old", "0x02")]));
+        assert_eq!(
+            diff.changed["Router"],
+            AddressChange {
+                from: "0x01".to_string(),
+                to: "0x03".to_string(),
+            }
+        );
+        assert_eq!(diff.changed.len(), 1);
+        assert!(DeploymentDiff::between(&old, &old).is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::Path,
+    process::Command,
+};
+
+use crate::uniswap;
+
+/// Outcome of a single environment check
+#[derive(Debug)]
+pub struct Check {
+    pub name: String,
+    /// Why the check failed, `None` when it passed
+    pub problem: Option<String>,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, result: Result<(), String>) -> Self {
+        Self {
+            name: name.into(),
+            problem: result.err(),
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.problem.is_none()
+    }
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.problem {
+            None => write!(f, "[ok]   {}", self.name),
+            Some(problem) => write!(f, "[fail] {}: {}", self.name, problem),
+        }
+    }
+}
+
+/// Source folder or file at `path` exists and can be read
+pub fn source_path(source: &str, path: &str) -> Check {
+    let result = if Path::new(path).is_dir() {
+        fs::read_dir(path).map(drop)
+    } else {
+        fs::File::open(path).map(drop)
+    };
+
+    Check::new(
+        format!("{} source '{}' is readable", source, path),
+        result.map_err(|err| err.to_string()),
+    )
+}
+
+/// Files can be created in `path`, or in its closest existing ancestor when it doesn't exist yet
+pub fn output_dir(path: &str) -> Check {
+    let existing = Path::new(path)
+        .ancestors()
+        .find(|ancestor| ancestor.is_dir())
+        .unwrap_or(Path::new("."));
+    let probe = existing.join(format!(".evm-dex-index-doctor-{}", std::process::id()));
+
+    let result = fs::write(&probe, b"")
+        .and_then(|()| fs::remove_file(&probe))
+        .map_err(|err| format!("{}: {}", existing.display(), err));
+
+    Check::new(format!("output folder '{}' is writable", path), result)
+}
+
+/// Bundled uniswap protocol configs define every contract at most once
+pub fn protocol_configs() -> Check {
+    Check::new(
+        "bundled protocol configs have no duplicate contracts",
+        uniswap::validate_protocol_configs_for_duplicate_definitions()
+            .map_err(|err| err.to_string()),
+    )
+}
+
+/// `git` can be run, needed to check out refs and compute deltas
+pub fn git() -> Check {
+    let result = match Command::new("git").arg("--version").output() {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(format!("git --version exited with {}", output.status)),
+        Err(err) => Err(err.to_string()),
+    };
+
+    Check::new("git is available", result)
+}
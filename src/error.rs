@@ -0,0 +1,148 @@
+use std::process::ExitCode;
+
+use chrono::NaiveDate;
+use thiserror::Error;
+
+use evm_dex_index::{
+    algebra, balancer, csv_source, normalize, pancakeswap, registry, types::ChainId, uniswap, watch,
+};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to parse balancer deployments: {0}")]
+    BalancerParse(#[from] balancer::ParseError),
+
+    #[error("Failed to read chain aliases: {0}")]
+    ChainAliases(std::io::Error),
+
+    #[error("Failed to check out balancer ref: {0}")]
+    BalancerRef(std::io::Error),
+
+    #[error("Failed to parse uniswap deployments: {0}")]
+    UniswapParse(#[from] uniswap::ParseError),
+
+    #[error("Failed to parse algebra deployments: {0}")]
+    AlgebraParse(#[from] algebra::ParseError),
+
+    #[error("Failed to parse pancakeswap deployments: {0}")]
+    PancakeswapParse(#[from] pancakeswap::ParseError),
+
+    #[error("Failed to parse CSV source: {0}")]
+    CsvSourceParse(#[from] csv_source::ParseError),
+
+    #[error("CSV source conflicts with the parsed deployments: {0}")]
+    CsvSourceConflict(#[from] registry::MergeError),
+
+    #[error("Failed to normalize addresses: {0}")]
+    Address(#[from] normalize::NotAnAddress),
+
+    #[error("Failed to rename protocols: {0}")]
+    RenameProtocol(#[from] normalize::ProtocolNameCollision),
+
+    #[error("Failed to build the thread pool: {0}")]
+    ThreadPool(#[from] rayon::ThreadPoolBuildError),
+
+    #[error("Failed to write deployments: {0}")]
+    Write(#[from] std::io::Error),
+
+    #[error("Written deployments failed verification: {0}")]
+    Verify(std::io::Error),
+
+    #[error("Failed to compute the delta of the output repo: {0}")]
+    Delta(std::io::Error),
+
+    #[error("Duplicate addresses: {}", duplicates.join("; "))]
+    DuplicateAddresses { duplicates: Vec<String> },
+
+    #[error("Sources disagree on deployments: {}", conflicts.join("; "))]
+    SourceConflicts { conflicts: Vec<String> },
+
+    #[error("Contracts at the zero address: {}", contracts.join(", "))]
+    ZeroAddresses { contracts: Vec<String> },
+
+    #[error(
+        "Newest balancer deployment is from {newest_deployment}, more than {max_age} day(s) ago; \
+         is the source mirror synced?"
+    )]
+    StaleSource {
+        newest_deployment: NaiveDate,
+        max_age: u64,
+    },
+
+    #[error("Failed to read or write the snapshot: {0}")]
+    Snapshot(std::io::Error),
+
+    #[error("Coverage diverges from the snapshot: {}", differences.join("; "))]
+    SnapshotMismatch { differences: Vec<String> },
+
+    #[error("Unrecognized chain id(s): {chain_ids:?}")]
+    UnknownChains { chain_ids: Vec<ChainId> },
+
+    #[error("Validation found {issues} issue(s)")]
+    Validation { issues: usize },
+
+    #[error("Doctor found {failed} failing check(s)")]
+    Doctor { failed: usize },
+
+    #[error("{0}")]
+    Watch(#[from] watch::WatchError),
+
+    #[cfg(feature = "fetch")]
+    #[error("Failed to fetch source files: {0}")]
+    Fetch(#[from] evm_dex_index::fetch::FetchError),
+}
+
+impl From<registry::BuildError> for Error {
+    fn from(err: registry::BuildError) -> Self {
+        match err {
+            registry::BuildError::Balancer(balancer::ParseError::ConflictingDeployments {
+                conflicts,
+            }) => Error::SourceConflicts { conflicts },
+            registry::BuildError::Balancer(err) => Error::BalancerParse(err),
+            registry::BuildError::Uniswap(err) => Error::UniswapParse(err),
+            registry::BuildError::PancakeSwap(err) => Error::PancakeswapParse(err),
+            registry::BuildError::Algebra(err) => Error::AlgebraParse(err),
+            registry::BuildError::CsvSource(err) => Error::CsvSourceParse(err),
+            registry::BuildError::CsvSourceConflict(err) => Error::CsvSourceConflict(err),
+            registry::BuildError::BalancerRef(err) => Error::BalancerRef(err),
+            registry::BuildError::ThreadPool(err) => Error::ThreadPool(err),
+            registry::BuildError::Address(err) => Error::Address(err),
+        }
+    }
+}
+
+impl Error {
+    /// Process exit code of the failure class: parse = 2, write and verify = 3, validation = 4,
+    /// fetch = 5, anything else = 1
+    pub fn exit_code(&self) -> ExitCode {
+        let code = match self {
+            Error::UniswapParse(
+                uniswap::ParseError::MissingContracts { .. }
+                | uniswap::ParseError::DuplicateContracts { .. },
+            )
+            | Error::Validation { .. }
+            | Error::Doctor { .. }
+            | Error::UnknownChains { .. }
+            | Error::DuplicateAddresses { .. }
+            | Error::SourceConflicts { .. }
+            | Error::ZeroAddresses { .. }
+            | Error::SnapshotMismatch { .. }
+            | Error::StaleSource { .. } => 4,
+            Error::BalancerParse(_)
+            | Error::UniswapParse(_)
+            | Error::AlgebraParse(_)
+            | Error::PancakeswapParse(_)
+            | Error::CsvSourceParse(_)
+            | Error::CsvSourceConflict(_)
+            | Error::BalancerRef(_)
+            | Error::ChainAliases(_)
+            | Error::Address(_) => 2,
+            Error::Write(_) | Error::Verify(_) | Error::Delta(_) | Error::Snapshot(_) => 3,
+            Error::ThreadPool(_) | Error::RenameProtocol(_) | Error::Watch(_) => 1,
+            #[cfg(feature = "fetch")]
+            Error::Fetch(_) => 5,
+        };
+
+        ExitCode::from(code)
+    }
+}
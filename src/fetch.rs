@@ -0,0 +1,467 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use reqwest::{
+    StatusCode,
+    blocking::Client,
+    header::{CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::debug;
+
+use crate::{
+    balancer::SupportedNetworks,
+    bytecode::{self, CodeProvider},
+    diff::RegistryDiff,
+    index::{INDEX_FILE_NAME, Index},
+    registry::Registry,
+    types::{ChainContracts, ChainDeployments, ChainId, ProtocolDeployments},
+};
+
+#[derive(Debug, Error)]
+pub enum FetchError {
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Serde error: {0}")]
+    SerdeError(#[from] serde_json::Error),
+
+    #[error("HTTP error: {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("Unexpected status {status} for '{url}'")]
+    UnexpectedStatus { url: String, status: StatusCode },
+}
+
+/// Validators of a cached file, stored next to it as `<file>.cache.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_modified: Option<String>,
+}
+
+/// Downloads raw source files into a local cache directory mirroring the remote layout
+pub struct Fetcher {
+    client: Client,
+    url_base: String,
+    cache_dir: PathBuf,
+}
+
+impl Fetcher {
+    pub fn new(url_base: &str, cache_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            client: Client::new(),
+            url_base: url_base.trim_end_matches('/').to_string(),
+            cache_dir: cache_dir.into(),
+        }
+    }
+
+    /// Fetches `relative_path`, revalidating a cached copy instead of re-downloading it
+    pub fn fetch(&self, relative_path: &str) -> Result<PathBuf, FetchError> {
+        let url = format!("{}/{}", self.url_base, relative_path);
+        let path = self.cache_dir.join(relative_path);
+        let cache_path = cache_entry_path(&path);
+
+        let cached = if path.exists() {
+            read_cache_entry(&cache_path)?
+        } else {
+            None
+        };
+
+        let mut request = self.client.get(&url);
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request.send()?;
+        let status = response.status();
+
+        if status == StatusCode::NOT_MODIFIED && cached.is_some() {
+            debug!(url = %url, "Source file not modified, using cache");
+            return Ok(path);
+        }
+
+        if !status.is_success() {
+            return Err(FetchError::UnexpectedStatus { url, status });
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let entry = CacheEntry {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        };
+
+        let body = response.bytes()?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, &body)?;
+        fs::write(&cache_path, serde_json::to_vec(&entry)?)?;
+
+        debug!(url = %url, "Source file downloaded");
+
+        Ok(path)
+    }
+
+    /// Like [`Fetcher::fetch`], but `None` when the remote file doesn't exist
+    pub fn fetch_optional(&self, relative_path: &str) -> Result<Option<PathBuf>, FetchError> {
+        match self.fetch(relative_path) {
+            Err(FetchError::UnexpectedStatus {
+                status: StatusCode::NOT_FOUND,
+                ..
+            }) => Ok(None),
+            result => result.map(Some),
+        }
+    }
+}
+
+fn cache_entry_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".cache.json");
+    path.with_file_name(file_name)
+}
+
+fn read_cache_entry(cache_path: &Path) -> Result<Option<CacheEntry>, FetchError> {
+    if !cache_path.exists() {
+        return Ok(None);
+    }
+
+    let entry = serde_json::from_reader(BufReader::new(File::open(cache_path)?))?;
+
+    Ok(Some(entry))
+}
+
+/// Fetches the balancer `addresses/` folder file by file, driven by its supported networks list.
+///
+/// Returns the cache directory, which can be passed to `balancer::parse` as the repo path.
+pub fn fetch_balancer(fetcher: &Fetcher) -> Result<PathBuf, FetchError> {
+    let supported_networks_path = fetcher.fetch("addresses/.supported-networks.json")?;
+
+    let supported_networks: SupportedNetworks =
+        serde_json::from_reader(BufReader::new(File::open(supported_networks_path)?))?;
+
+    for (network, info) in &supported_networks.networks {
+        if info.is_disabled() {
+            continue;
+        }
+        fetcher.fetch(&format!("addresses/{}.json", network))?;
+    }
+
+    Ok(fetcher.cache_dir.clone())
+}
+
+/// Diffs a registry against a published one, see [`Registry::diff`].
+///
+/// Chains are taken from both the registry and the published `index.json`, if any.
+/// A chain file missing remotely counts as all contracts added.
+pub fn diff_published(fetcher: &Fetcher, registry: &Registry) -> Result<RegistryDiff, FetchError> {
+    Ok(fetch_published(fetcher, registry)?.diff(registry))
+}
+
+/// The published chain files of the protocols and chains of `registry` and of the published
+/// `index.json`
+fn fetch_published(fetcher: &Fetcher, registry: &Registry) -> Result<Registry, FetchError> {
+    let published_index: Index = match fetcher.fetch_optional(INDEX_FILE_NAME)? {
+        Some(path) => serde_json::from_reader(BufReader::new(File::open(path)?))?,
+        None => Index::default(),
+    };
+
+    let mut protocol_chains: BTreeMap<&str, BTreeSet<ChainId>> = BTreeMap::new();
+    for protocol_deployments in registry.protocols() {
+        protocol_chains
+            .entry(protocol_deployments.protocol_name.as_str())
+            .or_default()
+            .extend(protocol_deployments.chains.keys());
+    }
+    for (protocol_name, published) in &published_index.protocols {
+        protocol_chains
+            .entry(protocol_name.as_str())
+            .or_default()
+            .extend(published.chains.keys());
+    }
+
+    let mut published = Registry::new();
+    for (protocol_name, chain_ids) in protocol_chains {
+        let mut chains = ChainDeployments::new();
+        for chain_id in chain_ids {
+            if let Some(path) =
+                fetcher.fetch_optional(&format!("{}/{}.json", protocol_name, chain_id))?
+            {
+                let contracts: ChainContracts =
+                    serde_json::from_reader(BufReader::new(File::open(path)?))?;
+                chains.insert(chain_id, contracts);
+            }
+        }
+        published.insert(ProtocolDeployments::new(protocol_name, chains));
+    }
+
+    Ok(published)
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    result: Option<String>,
+    error: Option<serde_json::Value>,
+}
+
+/// Reads contract bytecode with `eth_getCode` from a JSON-RPC endpoint per chain
+pub struct RpcCodeProvider {
+    client: Client,
+    urls: HashMap<ChainId, String>,
+}
+
+impl RpcCodeProvider {
+    pub fn new(urls: HashMap<ChainId, String>) -> Self {
+        Self {
+            client: Client::new(),
+            urls,
+        }
+    }
+}
+
+impl CodeProvider for RpcCodeProvider {
+    fn code(&self, chain_id: ChainId, address: &str) -> Result<Option<Vec<u8>>, std::io::Error> {
+        let Some(url) = self.urls.get(&chain_id) else {
+            return Ok(None);
+        };
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_getCode",
+            "params": [address, "latest"],
+        });
+        let response = self
+            .client
+            .post(url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(request.to_string())
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.bytes())
+            .map_err(std::io::Error::other)?;
+        let response: RpcResponse = serde_json::from_slice(&response)?;
+
+        if let Some(error) = response.error {
+            return Err(std::io::Error::other(format!(
+                "eth_getCode of {} on chain {} failed: {}",
+                address, chain_id, error
+            )));
+        }
+        let code = response.result.unwrap_or_default();
+
+        bytecode::decode_hex(&code).map(Some).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("eth_getCode returned malformed bytecode '{}'", code),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, Write},
+        net::TcpListener,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+    };
+
+    use super::*;
+
+    const ETAG_VALUE: &str = "\"v1\"";
+
+    /// Serves `files` over HTTP, answering `304` when the request carries the known ETag
+    fn serve(files: HashMap<&'static str, &'static str>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let full_responses = Arc::new(AtomicUsize::new(0));
+        let counter = full_responses.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let path = request_line.split(' ').nth(1).unwrap_or("/").to_string();
+
+                let mut revalidating = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if line.to_lowercase().starts_with("if-none-match:") {
+                        revalidating = line.contains(ETAG_VALUE);
+                    }
+                }
+
+                let response = match files.get(path.as_str()) {
+                    Some(_) if revalidating => {
+                        "HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\n\r\n".to_string()
+                    }
+                    Some(body) => {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        format!(
+                            "HTTP/1.1 200 OK\r\nETag: {}\r\nContent-Length: {}\r\n\r\n{}",
+                            ETAG_VALUE,
+                            body.len(),
+                            body
+                        )
+                    }
+                    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string(),
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (format!("http://{}", address), full_responses)
+    }
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "evm-dex-index-fetch-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_fetch_caches_and_revalidates() {
+        let (url_base, full_responses) = serve(HashMap::from([(
+            "/addresses/mainnet.json",
+            r#"{"20210418-vault":{"version":"v2","status":"ACTIVE","contracts":[]}}"#,
+        )]));
+        let dir = cache_dir("revalidate");
+        let fetcher = Fetcher::new(&url_base, &dir);
+
+        let path = fetcher.fetch("addresses/mainnet.json").unwrap();
+        assert!(
+            fs::read_to_string(&path)
+                .unwrap()
+                .contains("20210418-vault")
+        );
+        assert!(cache_entry_path(&path).exists());
+
+        let path = fetcher.fetch("addresses/mainnet.json").unwrap();
+        assert!(path.exists());
+        assert_eq!(full_responses.load(Ordering::SeqCst), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_missing_file() {
+        let (url_base, _) = serve(HashMap::new());
+        let fetcher = Fetcher::new(&url_base, cache_dir("missing"));
+
+        let result = fetcher.fetch("addresses/mainnet.json");
+        assert!(matches!(
+            result,
+            Err(FetchError::UnexpectedStatus {
+                status: StatusCode::NOT_FOUND,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_rpc_code_provider_decodes_bytecode() {
+        let (url_base, _) = serve(HashMap::from([(
+            "/",
+            r#"{"jsonrpc":"2.0","id":1,"result":"0x63a167129514"}"#,
+        )]));
+        let provider = RpcCodeProvider::new(HashMap::from([(1, url_base)]));
+
+        let code = provider.code(1, "0x02").unwrap().unwrap();
+
+        assert!(bytecode::exposes_selector(
+            &code,
+            bytecode::parse_selector("0xa1671295").unwrap()
+        ));
+        assert_eq!(provider.code(10, "0x02").unwrap(), None);
+    }
+
+    #[test]
+    fn test_fetch_balancer() {
+        let (url_base, _) = serve(HashMap::from([
+            (
+                "/addresses/.supported-networks.json",
+                r#"{"mainnet":{"chainId":1}}"#,
+            ),
+            (
+                "/addresses/mainnet.json",
+                r#"{"20210418-vault":{"version":"v2","status":"ACTIVE","contracts":[{"name":"Vault","address":"0xBA12222222228d8Ba445958a75a0704d566BF2C8"}]}}"#,
+            ),
+        ]));
+        let dir = cache_dir("balancer");
+        let fetcher = Fetcher::new(&url_base, &dir);
+
+        let repo_path = fetch_balancer(&fetcher).unwrap();
+        let (v2, _) = crate::balancer::parse(repo_path.to_str().unwrap()).unwrap();
+
+        assert_eq!(
+            v2.chains[&1].get("Vault").map(String::as_str),
+            Some("0xBA12222222228d8Ba445958a75a0704d566BF2C8")
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_diff_published() {
+        let (url_base, _) = serve(HashMap::from([
+            (
+                "/index.json",
+                r#"{"protocols":{"permit2":{"chains":{"1":{"contract_count":1},"10":{"contract_count":1}}}}}"#,
+            ),
+            ("/permit2/1.json", r#"{"Permit2":"0x01"}"#),
+            ("/permit2/10.json", r#"{"Permit2":"0x01"}"#),
+        ]));
+        let dir = cache_dir("published");
+        let fetcher = Fetcher::new(&url_base, &dir);
+        let contracts =
+            |address: &str| ChainContracts::from([("Permit2".to_string(), address.to_string())]);
+        let registry = Registry::from_iter([ProtocolDeployments::new(
+            "permit2",
+            HashMap::from([(1, contracts("0x02")), (8453, contracts("0x01"))]),
+        )]);
+
+        let diff = diff_published(&fetcher, &registry).unwrap();
+
+        let permit2 = &diff["permit2"];
+        assert_eq!(permit2[&1].changed["Permit2"].to, "0x02");
+        assert_eq!(permit2[&10].removed, contracts("0x01"));
+        assert_eq!(permit2[&8453].added, contracts("0x01"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,99 @@
+use std::{path::Path, process::Command};
+
+use crate::types::SourceInfo;
+
+/// Checks out `git_ref` of the repo at `repo_path` as a detached worktree at `worktree_path`
+pub fn add_worktree(
+    repo_path: &str,
+    git_ref: &str,
+    worktree_path: &Path,
+) -> Result<(), std::io::Error> {
+    let worktree_path = worktree_path.to_string_lossy();
+    run(
+        repo_path,
+        &[
+            "worktree",
+            "add",
+            "--detach",
+            "--force",
+            &worktree_path,
+            git_ref,
+        ],
+    )
+    .map(drop)
+}
+
+pub fn remove_worktree(repo_path: &str, worktree_path: &Path) -> Result<(), std::io::Error> {
+    let worktree_path = worktree_path.to_string_lossy();
+    run(
+        repo_path,
+        &["worktree", "remove", "--force", &worktree_path],
+    )
+    .map(drop)
+}
+
+/// Paths, relative to the repo root, of files that are modified, added or untracked
+/// compared to `HEAD`. Deleted files are left out.
+pub fn changed_files(repo_path: &str) -> Result<Vec<String>, std::io::Error> {
+    let stdout = run(
+        repo_path,
+        &["status", "--porcelain=v1", "-z", "--untracked-files=all"],
+    )?;
+    let stdout = String::from_utf8_lossy(&stdout);
+
+    let mut entries = stdout.split('\0').filter(|entry| !entry.is_empty());
+    let mut changed = Vec::new();
+
+    while let Some(entry) = entries.next() {
+        let (status, path) = entry.split_at(3.min(entry.len()));
+        if status.starts_with('R') || status.starts_with('C') {
+            // Followed by the original path
+            entries.next();
+        }
+        if !status.contains('D') {
+            changed.push(path.to_string());
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Provenance of the source data at `path`: its repo's `origin` URL and the commit of
+/// `git_ref` (`HEAD` when unset). Outside a git repo only the path is recorded.
+pub fn source_info(path: &str, git_ref: Option<&str>) -> SourceInfo {
+    let commit = resolve_commit(path, git_ref.unwrap_or("HEAD")).ok();
+    let location = run(path, &["remote", "get-url", "origin"])
+        .map(|stdout| String::from_utf8_lossy(&stdout).trim().to_string())
+        .unwrap_or_else(|_| path.to_string());
+
+    SourceInfo { location, commit }
+}
+
+/// Full hash of the commit `git_ref` points to in the repo containing `path`
+pub fn resolve_commit(path: &str, git_ref: &str) -> Result<String, std::io::Error> {
+    let stdout = run(
+        path,
+        &["rev-parse", "--verify", &format!("{}^{{commit}}", git_ref)],
+    )?;
+
+    Ok(String::from_utf8_lossy(&stdout).trim().to_string())
+}
+
+/// Runs git in `repo_path`, returning its stdout
+fn run(repo_path: &str, args: &[&str]) -> Result<Vec<u8>, std::io::Error> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .args(args)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(output.stdout)
+}
@@ -0,0 +1,96 @@
+use serde::Serialize;
+
+use crate::{
+    pattern,
+    types::{ChainId, ContractAddress, ContractName, ProtocolDeployments},
+};
+
+pub const GOVERNANCE_FILE_NAME: &str = "governance.json";
+
+/// Name patterns of proxy admins, timelocks and owner-style contracts
+pub const DEFAULT_GOVERNANCE_NAMES: &[&str] = &[
+    "*ProxyAdmin",
+    "*TimelockController",
+    "*Timelock",
+    "*Authorizer",
+    "*Owner",
+];
+
+/// Contract controlling other contracts of a protocol on a chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GovernanceContract {
+    pub protocol: String,
+    pub chain_id: ChainId,
+    pub contract_name: ContractName,
+    pub address: ContractAddress,
+}
+
+/// Contracts matching any of the name patterns, ordered by protocol, chain and name
+pub fn find<S: AsRef<str>>(
+    protocols: &[ProtocolDeployments],
+    names: &[S],
+) -> Vec<GovernanceContract> {
+    let mut found = Vec::new();
+
+    for protocol_deployments in protocols {
+        for (chain_id, contracts) in &protocol_deployments.chains {
+            for (name, address) in contracts {
+                if !names
+                    .iter()
+                    .any(|pattern| pattern::matches(pattern.as_ref(), name))
+                {
+                    continue;
+                }
+
+                found.push(GovernanceContract {
+                    protocol: protocol_deployments.protocol_name.clone(),
+                    chain_id: *chain_id,
+                    contract_name: name.clone(),
+                    address: address.clone(),
+                });
+            }
+        }
+    }
+
+    found.sort_by(|a, b| {
+        (&a.protocol, a.chain_id, &a.contract_name).cmp(&(
+            &b.protocol,
+            b.chain_id,
+            &b.contract_name,
+        ))
+    });
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::balancer;
+
+    #[test]
+    fn test_find_timelock_controller_in_source() {
+        let (v2, v3) = balancer::parse("tests/fixtures/balancer").unwrap();
+
+        let found = find(&[v2, v3], DEFAULT_GOVERNANCE_NAMES);
+
+        assert_eq!(
+            found,
+            vec![GovernanceContract {
+                protocol: "balancer-v2".to_string(),
+                chain_id: 1,
+                contract_name: "TimelockController".to_string(),
+                address: "0xBc7A26B0E7a2b4B3e42C5D5aC2E4fEd4c2a0E3b1".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_find_uses_configured_names() {
+        let (v2, v3) = balancer::parse("tests/fixtures/balancer").unwrap();
+
+        let found = find(&[v2, v3], &["VaultAdmin"]);
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].protocol, "balancer-v3");
+    }
+}
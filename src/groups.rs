@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+
+use crate::{pattern, types::ChainContracts};
+
+/// Contracts of a protocol matching a name pattern, written together as a sub-object of the
+/// enriched output
+pub struct ContractGroup {
+    pub protocol_name: &'static str,
+    pub group_name: &'static str,
+    pub pattern: &'static str,
+}
+
+pub const CONTRACT_GROUPS: &[ContractGroup] = &[
+    ContractGroup {
+        protocol_name: "balancer-v3",
+        group_name: "routers",
+        pattern: "*Router",
+    },
+    ContractGroup {
+        protocol_name: "balancer-v3",
+        group_name: "hooks",
+        pattern: "*Hook",
+    },
+];
+
+/// Whether any group is configured for the protocol
+pub fn has_groups(protocol_name: &str) -> bool {
+    CONTRACT_GROUPS
+        .iter()
+        .any(|group| group.protocol_name == protocol_name)
+}
+
+/// Whether `name` is a group of some protocol rather than a contract
+pub fn is_group_name(name: &str) -> bool {
+    CONTRACT_GROUPS.iter().any(|group| group.group_name == name)
+}
+
+/// Members of each non-empty group of the protocol, by group name
+pub fn group(protocol_name: &str, contracts: &ChainContracts) -> BTreeMap<String, ChainContracts> {
+    let mut groups = BTreeMap::new();
+
+    for group in CONTRACT_GROUPS
+        .iter()
+        .filter(|group| group.protocol_name == protocol_name)
+    {
+        let members: ChainContracts = contracts
+            .iter()
+            .filter(|(name, _)| pattern::matches(group.pattern, name))
+            .map(|(name, address)| (name.clone(), address.clone()))
+            .collect();
+
+        if !members.is_empty() {
+            groups.insert(group.group_name.to_string(), members);
+        }
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_balancer_v3_routers_are_grouped() {
+        let (_, v3) = crate::balancer::parse("tests/fixtures/balancer").unwrap();
+
+        let groups = group("balancer-v3", &v3.chains[&1]);
+
+        let routers = &groups["routers"];
+        assert!(routers.contains_key("BatchRouter"));
+        assert!(routers.contains_key("Router"));
+        assert!(routers.keys().all(|name| name.ends_with("Router")));
+        assert!(group("balancer-v2", &v3.chains[&1]).is_empty());
+    }
+}
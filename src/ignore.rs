@@ -0,0 +1,74 @@
+use std::{fs, io::ErrorKind, path::Path};
+
+use crate::pattern;
+
+pub const IGNORE_FILE_NAME: &str = ".deployignore";
+
+/// File name globs (`*` wildcard) of a source directory's `.deployignore`,
+/// one per line, with blank lines and `#` comments skipped
+#[derive(Debug, Default)]
+pub struct IgnoreList {
+    patterns: Vec<String>,
+}
+
+impl IgnoreList {
+    /// Reads `<dir>/.deployignore`, empty when the file doesn't exist
+    pub fn read(dir: impl AsRef<Path>) -> Result<Self, std::io::Error> {
+        let contents = match fs::read_to_string(dir.as_ref().join(IGNORE_FILE_NAME)) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err),
+        };
+
+        Ok(Self::parse(&contents))
+    }
+
+    /// Patterns of `.deployignore` contents
+    pub fn parse(contents: &str) -> Self {
+        let patterns = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.trim_start_matches('/').to_string())
+            .collect();
+
+        Self { patterns }
+    }
+
+    pub fn is_ignored(&self, file_name: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| pattern::matches(pattern, file_name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_patterns() {
+        let dir =
+            std::env::temp_dir().join(format!("evm-dex-index-deployignore-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(IGNORE_FILE_NAME),
+            "# experimental\n\n/goerli.json\n*-testnet.json\n",
+        )
+        .unwrap();
+
+        let ignore_list = IgnoreList::read(&dir).unwrap();
+
+        assert!(ignore_list.is_ignored("goerli.json"));
+        assert!(ignore_list.is_ignored("base-testnet.json"));
+        assert!(!ignore_list.is_ignored("mainnet.json"));
+        assert!(
+            !IgnoreList::read(dir.join("missing"))
+                .unwrap()
+                .is_ignored("goerli.json")
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    protocol_meta,
+    types::{ChainId, ProtocolDeployments, SourceInfo},
+};
+
+pub const INDEX_FILE_NAME: &str = "index.json";
+
+pub const INDEX_NDJSON_FILE_NAME: &str = "index.ndjson";
+
+/// Summary of everything written, stored as `index.json` at the root of the output folder
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Index {
+    pub protocols: BTreeMap<String, ProtocolIndex>,
+}
+
+/// A protocol and chain of the index, as a line of `index.ndjson`
+#[derive(Debug, Serialize)]
+struct IndexLine<'a> {
+    protocol: &'a str,
+    chain_id: ChainId,
+    contract_count: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProtocolIndex {
+    /// Group of related protocols, e.g. `uniswap` for `uniswap-v3`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceInfo>,
+    pub chains: BTreeMap<ChainId, ChainIndex>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChainIndex {
+    pub contract_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completeness: Option<f64>,
+    /// Periphery release the chain is on, for protocols with versioned periphery
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub periphery_version: Option<String>,
+    /// Earliest deployment block of the protocol's contracts on the chain, where known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_start_block: Option<u64>,
+}
+
+impl Index {
+    pub fn add(&mut self, protocol_deployments: &ProtocolDeployments) {
+        let protocol = self
+            .protocols
+            .entry(protocol_deployments.protocol_name.clone())
+            .or_default();
+
+        let parsed_name = protocol_deployments
+            .renamed_from
+            .as_ref()
+            .unwrap_or(&protocol_deployments.protocol_name);
+        protocol.family = protocol_meta::family(parsed_name).map(str::to_string);
+        if protocol_deployments.source_ref.is_some() {
+            protocol.source_ref = protocol_deployments.source_ref.clone();
+        }
+        if protocol_deployments.source.is_some() {
+            protocol.source = protocol_deployments.source.clone();
+        }
+
+        for (chain_id, contracts) in &protocol_deployments.chains {
+            protocol.chains.insert(
+                *chain_id,
+                ChainIndex {
+                    contract_count: contracts.len(),
+                    completeness: protocol_deployments.completeness(*chain_id),
+                    periphery_version: protocol_deployments.periphery_version(*chain_id),
+                    min_start_block: protocol_deployments.min_start_block(*chain_id),
+                },
+            );
+        }
+    }
+
+    /// A `{protocol, chain_id, contract_count}` line per protocol and chain, sorted by both
+    pub fn to_ndjson(&self) -> String {
+        let mut ndjson = String::new();
+        for (protocol_name, protocol) in &self.protocols {
+            for (chain_id, chain) in &protocol.chains {
+                let line = IndexLine {
+                    protocol: protocol_name,
+                    chain_id: *chain_id,
+                    contract_count: chain.contract_count,
+                };
+                ndjson
+                    .push_str(&serde_json::to_string(&line).expect("Index lines always serialize"));
+                ndjson.push('\n');
+            }
+        }
+        ndjson
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::types::{ChainContracts, ChainMetadata, ContractMetadata};
+
+    fn contracts(names: &[&str]) -> ChainContracts {
+        names
+            .iter()
+            .map(|name| (name.to_string(), format!("0x{}", name)))
+            .collect()
+    }
+
+    fn uniswap_v2() -> ProtocolDeployments {
+        let mut deployments = ProtocolDeployments::new(
+            "uniswap-v2",
+            HashMap::from([
+                (1, contracts(&["UniswapV2Factory", "UniswapV2Router02"])),
+                (8453, contracts(&["UniswapV2Factory", "Multicall"])),
+            ]),
+        );
+        deployments.required_contracts = vec![
+            "UniswapV2Factory".to_string(),
+            "UniswapV2Router02".to_string(),
+        ];
+        deployments
+    }
+
+    #[test]
+    fn test_chain_missing_required_contract_scores_below_one() {
+        let mut index = Index::default();
+        index.add(&uniswap_v2());
+
+        let chains = &index.protocols["uniswap-v2"].chains;
+        assert_eq!(chains[&1].completeness, Some(1.0));
+        assert_eq!(chains[&8453].completeness, Some(0.5));
+        assert_eq!(chains[&8453].contract_count, 2);
+        assert_eq!(
+            index.protocols["uniswap-v2"].family.as_deref(),
+            Some("uniswap")
+        );
+    }
+
+    #[test]
+    fn test_min_start_block_is_earliest_contract_block() {
+        let mut deployments = uniswap_v2();
+        let created_at = |block| ContractMetadata {
+            block_created: block,
+            ..ContractMetadata::default()
+        };
+        deployments.metadata.insert(
+            1,
+            ChainMetadata::from([
+                ("UniswapV2Router02".to_string(), created_at(Some(10207858))),
+                ("UniswapV2Factory".to_string(), created_at(Some(10000835))),
+            ]),
+        );
+        deployments.metadata.insert(
+            8453,
+            ChainMetadata::from([("UniswapV2Factory".to_string(), created_at(None))]),
+        );
+
+        let mut index = Index::default();
+        index.add(&deployments);
+
+        let chains = &index.protocols["uniswap-v2"].chains;
+        assert_eq!(chains[&1].min_start_block, Some(10000835));
+        assert_eq!(chains[&8453].min_start_block, None);
+        let json = serde_json::to_value(&index).unwrap();
+        assert!(
+            json["protocols"]["uniswap-v2"]["chains"]["8453"]
+                .get("min_start_block")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_ndjson_has_a_line_per_protocol_chain() {
+        let mut index = Index::default();
+        index.add(&uniswap_v2());
+        index.add(&ProtocolDeployments::new(
+            "balancer-v2",
+            HashMap::from([(1, contracts(&["Vault"]))]),
+        ));
+
+        let ndjson = index.to_ndjson();
+        let lines: Vec<serde_json::Value> = ndjson
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            serde_json::json!({ "protocol": "balancer-v2", "chain_id": 1, "contract_count": 1 })
+        );
+        assert_eq!(lines[2]["chain_id"], 8453);
+    }
+
+    #[test]
+    fn test_protocol_without_required_contracts_has_no_completeness() {
+        let deployments =
+            ProtocolDeployments::new("balancer-v2", HashMap::from([(1, contracts(&["Vault"]))]));
+
+        let mut index = Index::default();
+        index.add(&deployments);
+
+        let json = serde_json::to_value(&index).unwrap();
+        assert_eq!(
+            json["protocols"]["balancer-v2"]["chains"]["1"],
+            serde_json::json!({ "contract_count": 1 })
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    hash::Hash,
+};
+
+use chrono::NaiveDate;
+
+/// Keeps the latest dated value per key, regardless of insertion order.
+///
+/// Values dated the same day are tie-broken by keeping the greatest value, so the
+/// outcome never depends on iteration order of the source.
+#[derive(Debug)]
+pub struct LatestWinsMap<K, V> {
+    /// Latest date and value, and how many values were inserted for the key
+    entries: HashMap<K, (NaiveDate, V, usize)>,
+}
+
+impl<K: Eq + Hash, V: Ord> LatestWinsMap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, key: K, date: NaiveDate, value: V) {
+        match self.entries.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let (existing_date, existing_value, candidates) = entry.get_mut();
+                *candidates += 1;
+                if (date, &value) > (*existing_date, &*existing_value) {
+                    *existing_date = date;
+                    *existing_value = value;
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert((date, value, 1));
+            }
+        }
+    }
+
+    pub fn into_latest(self) -> impl Iterator<Item = (K, V)> {
+        self.into_latest_with_candidates()
+            .map(|(key, value, _)| (key, value))
+    }
+
+    /// Latest value per key along with the number of candidates it was chosen from
+    pub fn into_latest_with_candidates(self) -> impl Iterator<Item = (K, V, usize)> {
+        self.entries
+            .into_iter()
+            .map(|(key, (_, value, candidates))| (key, value, candidates))
+    }
+}
+
+impl<K: Eq + Hash, V: Ord> Default for LatestWinsMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y%m%d").unwrap()
+    }
+
+    fn latest<V: Ord>(map: LatestWinsMap<&'static str, V>) -> HashMap<&'static str, V> {
+        map.into_latest().collect()
+    }
+
+    #[test]
+    fn test_latest_wins() {
+        let mut map = LatestWinsMap::new();
+        map.insert("Vault", date("20240101"), "0xOLD");
+        map.insert("Vault", date("20250101"), "0xNEW");
+
+        assert_eq!(latest(map)["Vault"], "0xNEW");
+    }
+
+    #[test]
+    fn test_out_of_order_insertion() {
+        let mut map = LatestWinsMap::new();
+        map.insert("Vault", date("20250101"), "0xNEW");
+        map.insert("Vault", date("20240101"), "0xOLD");
+        map.insert("Router", date("20230101"), "0xROUTER");
+
+        let latest = latest(map);
+        assert_eq!(latest["Vault"], "0xNEW");
+        assert_eq!(latest["Router"], "0xROUTER");
+    }
+
+    #[test]
+    fn test_same_date_tie_break_is_order_independent() {
+        let mut forward = LatestWinsMap::new();
+        forward.insert("Vault", date("20250101"), "0xA");
+        forward.insert("Vault", date("20250101"), "0xB");
+
+        let mut backward = LatestWinsMap::new();
+        backward.insert("Vault", date("20250101"), "0xB");
+        backward.insert("Vault", date("20250101"), "0xA");
+
+        assert_eq!(latest(forward)["Vault"], "0xB");
+        assert_eq!(latest(backward)["Vault"], "0xB");
+    }
+
+    #[test]
+    fn test_into_latest() {
+        let mut map = LatestWinsMap::new();
+        map.insert("Vault", date("20240101"), 1);
+        map.insert("Vault", date("20250101"), 2);
+        map.insert("Router", date("20240101"), 3);
+
+        let mut latest: Vec<_> = map.into_latest().collect();
+        latest.sort();
+        assert_eq!(latest, vec![("Router", 3), ("Vault", 2)]);
+    }
+
+    #[test]
+    fn test_counts_candidates_per_key() {
+        let mut map = LatestWinsMap::new();
+        map.insert("Vault", date("20210418"), "0x01");
+        map.insert("Vault", date("20230101"), "0x02");
+        map.insert("Router", date("20230101"), "0x03");
+
+        let mut latest: Vec<_> = map.into_latest_with_candidates().collect();
+        latest.sort();
+
+        assert_eq!(latest, vec![("Router", "0x03", 1), ("Vault", "0x02", 2)]);
+    }
+}
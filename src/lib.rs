@@ -0,0 +1,41 @@
+//! Index of DEX contract deployments across EVM chains
+
+pub mod address;
+pub mod algebra;
+pub mod balancer;
+pub mod bytecode;
+pub mod candidates;
+pub mod chains;
+pub mod checksums;
+pub mod csv_source;
+pub mod delta;
+pub mod diff;
+pub mod doctor;
+#[cfg(feature = "fetch")]
+pub mod fetch;
+pub mod git;
+pub mod governance;
+pub mod groups;
+pub mod ignore;
+pub mod index;
+pub mod latest;
+pub mod matrix;
+pub mod meta;
+pub mod metrics;
+pub mod normalize;
+pub mod pancakeswap;
+pub mod pattern;
+pub mod progress;
+pub mod protocol_meta;
+pub mod registry;
+pub mod shared;
+pub mod snapshot;
+pub mod source_files;
+pub mod transform;
+pub mod types;
+pub mod uniswap;
+pub mod validate;
+pub mod warnings;
+#[cfg(feature = "watch")]
+pub mod watch;
+pub mod write;
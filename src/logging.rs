@@ -0,0 +1,80 @@
+use std::{env, str::FromStr};
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{filter::Targets, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+
+/// Installs the global log subscriber, filtered by `filter`
+pub fn init(log_level: Option<LevelFilter>, quiet: bool) {
+    tracing_subscriber::registry()
+        .with(fmt::layer())
+        .with(filter(log_level, quiet))
+        .init();
+}
+
+/// `--log-level` if given, errors only with `--quiet`, otherwise `RUST_LOG` (info by default)
+pub fn filter(log_level: Option<LevelFilter>, quiet: bool) -> Targets {
+    match (log_level, quiet) {
+        (Some(level), _) => Targets::new().with_default(level),
+        (None, true) => Targets::new().with_default(LevelFilter::ERROR),
+        (None, false) => env_filter(),
+    }
+}
+
+fn env_filter() -> Targets {
+    let default = Targets::new().with_default(LevelFilter::INFO);
+
+    match env::var("RUST_LOG") {
+        Ok(var) => Targets::from_str(&var).unwrap_or_else(|err| {
+            eprintln!("Ignoring `RUST_LOG={:?}`: {}", var, err);
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer};
+
+    use super::*;
+
+    /// Records the level of every event it sees
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<Level>>>);
+
+    impl<S: Subscriber> Layer<S> for Capture {
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            self.0.lock().unwrap().push(*event.metadata().level());
+        }
+    }
+
+    fn captured_levels(log_level: Option<LevelFilter>, quiet: bool) -> Vec<Level> {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(capture.clone().with_filter(filter(log_level, quiet)));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!("debug");
+            tracing::info!("info");
+            tracing::error!("error");
+        });
+
+        capture.0.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn test_quiet_suppresses_info_logs() {
+        assert_eq!(captured_levels(None, true), vec![Level::ERROR]);
+    }
+
+    #[test]
+    fn test_log_level_takes_precedence_over_quiet() {
+        assert_eq!(
+            captured_levels(Some(LevelFilter::DEBUG), true),
+            vec![Level::DEBUG, Level::INFO, Level::ERROR]
+        );
+    }
+}
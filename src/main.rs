@@ -1,26 +1,508 @@
-mod balancer;
-mod types;
-mod uniswap;
-mod write;
+mod cli;
+mod error;
+mod logging;
+#[cfg(feature = "progress")]
+mod progress_bar;
 
-const TARGET_FOLDER: &str = "deployments";
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
 
-const BALANCER_REPO_PATH: &str = "source/balancer";
-const UNISWAP_DEPLOYMENTS_PATH: &str = "source/uniswap/deployments";
+use cli::{Cli, Command, StaleSourceAction};
+use error::Error;
+#[cfg(feature = "fetch")]
+use evm_dex_index::fetch;
+use evm_dex_index::{
+    address, balancer, candidates, chains, checksums, delta, doctor, governance,
+    index::{self, Index},
+    matrix::Matrix,
+    metrics, normalize,
+    progress::ProgressEvent,
+    registry::{Registry, RegistryBuilder},
+    shared, snapshot,
+    types::ProtocolDeployments,
+    validate, warnings,
+    warnings::{Warning, WarningKind},
+    watch::{self, Watcher},
+    write,
+};
+use tracing::{info, warn};
 
-fn main() {
-    tracing_subscriber::fmt::init();
+/// Cache of the published registry files fetched by `--diff-against-url`
+#[cfg(feature = "fetch")]
+const PUBLISHED_CACHE_DIR: &str = ".cache/published";
 
-    let (v2_deployments, v3_deployments) =
-        balancer::parse(BALANCER_REPO_PATH).expect("Failed to parse balancer deployments");
+fn main() -> ExitCode {
+    let cli = Cli::parse_args();
+    logging::init(cli.log_level, cli.quiet);
 
-    write::write(TARGET_FOLDER, v2_deployments).expect("Failed to write v2 deployments");
-    write::write(TARGET_FOLDER, v3_deployments).expect("Failed to write v3 deployments");
+    let result = match cli.command {
+        Some(Command::Validate) => run_validate(&cli),
+        Some(Command::Doctor) => run_doctor(&cli),
+        None if cli.watch => run_watch(&cli),
+        None => run(&cli),
+    };
 
-    let uniswap_deployments =
-        uniswap::parse(UNISWAP_DEPLOYMENTS_PATH).expect("Failed to parse uniswap deployments");
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            err.exit_code()
+        }
+    }
+}
+
+fn run_validate(cli: &Cli) -> Result<(), Error> {
+    let chain_aliases = read_chain_aliases(cli)?;
+    let issues = validate::validate(&cli.balancer_path, &cli.uniswap_path, &chain_aliases);
+
+    for issue in &issues {
+        eprintln!("{}", issue);
+    }
+
+    if !issues.is_empty() {
+        return Err(Error::Validation {
+            issues: issues.len(),
+        });
+    }
+
+    Ok(())
+}
+
+fn read_chain_aliases(cli: &Cli) -> Result<chains::ChainAliases, Error> {
+    match &cli.chain_aliases {
+        Some(path) => chains::read_aliases(path).map_err(Error::ChainAliases),
+        None => Ok(chains::ChainAliases::new()),
+    }
+}
+
+fn run_doctor(cli: &Cli) -> Result<(), Error> {
+    let mut checks = Vec::new();
+
+    if !uses_fetch(cli) {
+        checks.push(doctor::source_path("balancer", &cli.balancer_path));
+    }
+    for path in &cli.balancer_extra_path {
+        checks.push(doctor::source_path("balancer", path));
+    }
+    checks.push(doctor::source_path("uniswap", &cli.uniswap_path));
+    checks.push(doctor::output_dir(&cli.output_dir));
+    checks.push(doctor::protocol_configs());
+    if cli.balancer_ref.is_some() || cli.delta {
+        checks.push(doctor::git());
+    }
+
+    for check in &checks {
+        println!("{}", check);
+    }
+
+    let failed = checks.iter().filter(|check| !check.passed()).count();
+    if failed > 0 {
+        return Err(Error::Doctor { failed });
+    }
+
+    Ok(())
+}
+
+/// Runs once, then reparses the changed sources and writes again whenever source files
+/// change, until interrupted. A failing run is reported without ending the watch, the next
+/// change then rebuilding the whole registry.
+fn run_watch(cli: &Cli) -> Result<(), Error> {
+    let balancer_path = balancer_repo_path(cli)?;
+    let builder = registry_builder(cli, &balancer_path)?;
+    let sources = [
+        &balancer_path,
+        &cli.uniswap_path,
+        &cli.pancakeswap_path,
+        &cli.algebra_path,
+    ]
+    .into_iter()
+    .chain(&cli.balancer_extra_path)
+    .chain(&cli.csv_source);
+    let watcher = Watcher::new(sources, watch::DEBOUNCE)?;
+    let mut on_progress = progress_reporter(cli);
+
+    let mut registry = None;
+    let mut changed = Vec::new();
+    loop {
+        let mut warnings = Vec::new();
+        let result = update(
+            &builder,
+            &mut registry,
+            &changed,
+            &mut warnings,
+            &mut *on_progress,
+        )
+        .and_then(|registry| {
+            emit(
+                cli,
+                registry.protocols().cloned().collect(),
+                warnings,
+                &mut *on_progress,
+            )
+        });
+        if let Err(err) = result {
+            eprintln!("error: {}", err);
+        }
+
+        changed = watcher.next_change()?;
+        info!(files = changed.len(), "Source files changed, reparsing");
+    }
+}
+
+/// Reparses the `changed` source paths into `registry`, or builds it when there is none.
+/// A failure leaves no registry, so the next change builds it again.
+fn update<'a>(
+    builder: &RegistryBuilder,
+    registry: &'a mut Option<Registry>,
+    changed: &[PathBuf],
+    warnings: &mut Vec<Warning>,
+    on_progress: &mut dyn FnMut(ProgressEvent),
+) -> Result<&'a Registry, Error> {
+    let updated = match registry.take() {
+        Some(mut current) => {
+            let changed: Vec<_> = changed.iter().map(|path| path.to_string_lossy()).collect();
+            let changed: Vec<&str> = changed.iter().map(AsRef::as_ref).collect();
+            builder.reparse_changed(&mut current, &changed, warnings)?;
+            current
+        }
+        None => builder.run_with_progress(warnings, on_progress)?,
+    };
+
+    Ok(registry.insert(updated))
+}
+
+fn run(cli: &Cli) -> Result<(), Error> {
+    let mut warnings = Vec::new();
+    let mut on_progress = progress_reporter(cli);
+    let builder = registry_builder(cli, &balancer_repo_path(cli)?)?;
+    let protocols = builder
+        .run_with_progress(&mut warnings, &mut *on_progress)?
+        .into_protocols();
+
+    emit(cli, protocols, warnings, &mut *on_progress)
+}
+
+/// Checks the parsed protocols against the flags and writes them with the root files
+fn emit(
+    cli: &Cli,
+    mut protocols: Vec<ProtocolDeployments>,
+    mut warnings: Vec<Warning>,
+    on_progress: &mut dyn FnMut(ProgressEvent),
+) -> Result<(), Error> {
+    let mut index = Index::default();
+    let mut matrix = Matrix::default();
+
+    if let Some(max_age) = cli.max_age {
+        warnings.extend(check_freshness(&protocols, max_age, cli.stale_source)?);
+    }
+    normalize::rename_protocols(&mut protocols, &cli.rename_protocol)?;
+
+    for protocol_deployments in &protocols {
+        index.add(protocol_deployments);
+        matrix.add(protocol_deployments);
+        warnings.extend(warnings::address_anomalies(protocol_deployments));
+        warnings.extend(warnings::unknown_chains(protocol_deployments));
+        warnings.extend(warnings::duplicate_addresses(protocol_deployments));
+    }
+    // Parsers collect some warnings in hash map order
+    warnings.sort();
+
+    if cli.strict_chains {
+        let mut chain_ids: Vec<_> = warnings
+            .iter()
+            .filter(|warning| warning.kind == WarningKind::UnknownChain)
+            .filter_map(|warning| warning.chain_id)
+            .collect();
+        chain_ids.sort();
+        chain_ids.dedup();
+
+        if !chain_ids.is_empty() {
+            return Err(Error::UnknownChains { chain_ids });
+        }
+    }
+
+    let coverage = snapshot::from_matrix(&matrix);
+    if let Some(path) = &cli.check_snapshot {
+        let expected = snapshot::read(path).map_err(Error::Snapshot)?;
+        let differences = snapshot::differences(&expected, &coverage);
+        if !differences.is_empty() {
+            return Err(Error::SnapshotMismatch { differences });
+        }
+    }
+    if let Some(path) = &cli.snapshot {
+        snapshot::write(path, &coverage).map_err(Error::Snapshot)?;
+    }
+
+    if cli.strict_duplicate_addresses {
+        let duplicates: Vec<_> = warnings
+            .iter()
+            .filter(|warning| warning.kind == WarningKind::DuplicateAddress)
+            .map(|warning| {
+                format!(
+                    "{} on chain {}: {}",
+                    warning.protocol.as_deref().unwrap_or_default(),
+                    warning.chain_id.unwrap_or_default(),
+                    warning.detail
+                )
+            })
+            .collect();
+
+        if !duplicates.is_empty() {
+            return Err(Error::DuplicateAddresses { duplicates });
+        }
+    }
+
+    if cli.assert_no_zero_addresses {
+        let contracts = address::zero_address_contracts(&protocols);
+        if !contracts.is_empty() {
+            return Err(Error::ZeroAddresses { contracts });
+        }
+    }
+
+    #[cfg(feature = "fetch")]
+    if let Some(url_base) = &cli.diff_against_url {
+        return print_published_diff(url_base, &Registry::from_iter(protocols));
+    }
+
+    let json_options = write::JsonOptions {
+        ascii_only: cli.ascii_only,
+        indent: cli.indent,
+    };
+
+    let candidates_report = candidates::report(&protocols);
+
+    let shared_addresses = shared::report(&protocols);
+
+    let governance_contracts = match &cli.governance_names {
+        Some(names) => governance::find(&protocols, names),
+        None => governance::find(&protocols, governance::DEFAULT_GOVERNANCE_NAMES),
+    };
+
+    let mut written = write::write_all_by_protocol(
+        &cli.output_dir,
+        cli.format,
+        &cli.format_for,
+        cli.layout,
+        cli.abis_dir.as_deref(),
+        &json_options,
+        protocols,
+        on_progress,
+    )?;
+
+    written.push(write::write_root_file(
+        &cli.output_dir,
+        index::INDEX_FILE_NAME,
+        &index,
+        &json_options,
+    )?);
+    if cli.output_json_lines_index {
+        std::fs::write(
+            Path::new(&cli.output_dir).join(index::INDEX_NDJSON_FILE_NAME),
+            index.to_ndjson(),
+        )?;
+        written.push(index::INDEX_NDJSON_FILE_NAME.to_string());
+    }
+    written.push(write::write_root_file(
+        &cli.output_dir,
+        "matrix.json",
+        &matrix,
+        &json_options,
+    )?);
+    written.push(write::write_root_file(
+        &cli.output_dir,
+        candidates::CANDIDATES_FILE_NAME,
+        &candidates_report,
+        &json_options,
+    )?);
+    written.push(write::write_root_file(
+        &cli.output_dir,
+        governance::GOVERNANCE_FILE_NAME,
+        &governance_contracts,
+        &json_options,
+    )?);
+    written.push(write::write_root_file(
+        &cli.output_dir,
+        shared::SHARED_ADDRESSES_FILE_NAME,
+        &shared_addresses,
+        &json_options,
+    )?);
+    written.push(write::write_root_file(
+        &cli.output_dir,
+        warnings::WARNINGS_FILE_NAME,
+        &warnings,
+        &json_options,
+    )?);
+
+    if cli.verify_output {
+        write::verify(&cli.output_dir, &written).map_err(Error::Verify)?;
+    }
+
+    if cli.delta {
+        let changed = delta::changed_chain_files(&cli.output_dir).map_err(Error::Delta)?;
+        written.push(write::write_root_file(
+            &cli.output_dir,
+            delta::DELTA_FILE_NAME,
+            &changed,
+            &json_options,
+        )?);
+    }
+
+    let checksums = checksums::compute(&cli.output_dir, &written)?;
+    write::write_root_file(
+        &cli.output_dir,
+        checksums::CHECKSUMS_FILE_NAME,
+        &checksums,
+        &json_options,
+    )?;
 
-    for deployment in uniswap_deployments {
-        write::write(TARGET_FOLDER, deployment).expect("Failed to write uniswap deployment");
+    if let Some(metrics_file) = &cli.metrics_file {
+        std::fs::write(metrics_file, metrics::render(&index, &warnings))?;
+    }
+
+    Ok(())
+}
+
+/// Fails, or warns, when the newest deployment of the selected chains is older than `max_age`
+fn check_freshness(
+    protocols: &[ProtocolDeployments],
+    max_age: u64,
+    action: StaleSourceAction,
+) -> Result<Option<Warning>, Error> {
+    let newest_deployment = protocols
+        .iter()
+        .filter_map(ProtocolDeployments::newest_deployment)
+        .max();
+    let today = chrono::Utc::now().date_naive();
+
+    let Some(newest_deployment) =
+        newest_deployment.filter(|date| balancer::is_stale(*date, today, max_age))
+    else {
+        return Ok(None);
+    };
+
+    let err = Error::StaleSource {
+        newest_deployment,
+        max_age,
+    };
+    match action {
+        StaleSourceAction::Fail => Err(err),
+        StaleSourceAction::Warn => {
+            warn!("{}", err);
+            Ok(Some(Warning {
+                kind: WarningKind::StaleSource,
+                protocol: None,
+                chain_id: None,
+                detail: err.to_string(),
+            }))
+        }
+    }
+}
+
+/// Builder of the run's registry: the sources, filters and transforms the flags select
+fn registry_builder(cli: &Cli, balancer_path: &str) -> Result<RegistryBuilder, Error> {
+    let mut builder = Registry::builder()
+        .balancer_path(balancer_path)
+        .balancer_extra_paths(&cli.balancer_extra_path)
+        .balancer_merge(cli.balancer_merge)
+        .deprecated_contracts(cli.balancer_deprecated)
+        .uniswap_path(&cli.uniswap_path)
+        .algebra_path(&cli.algebra_path)
+        .csv_sources(&cli.csv_source)
+        .chain_aliases(read_chain_aliases(cli)?)
+        .exclude_chains(cli.exclude_chains.iter().copied())
+        .exclude_protocols(&cli.protocol_denylist)
+        .include_only_complete_chains(cli.include_only_complete_chains);
+    if let Some(workers) = cli.workers {
+        builder = builder.workers(workers);
+    }
+    if let Some(git_ref) = &cli.balancer_ref {
+        builder = builder.balancer_ref(git_ref);
+    }
+    if Path::new(&cli.pancakeswap_path).exists() {
+        builder = builder.pancakeswap_path(&cli.pancakeswap_path);
+    }
+    if let Some(chains) = &cli.chains {
+        builder = builder.chains(chains.iter().copied());
+    }
+    if let Some(allowlist) = &cli.protocol_allowlist {
+        builder = builder.protocols(allowlist);
     }
+    #[cfg(feature = "fetch")]
+    if !cli.verify_rpc.is_empty() {
+        builder = builder.code_provider(fetch::RpcCodeProvider::new(
+            cli.verify_rpc.iter().cloned().collect(),
+        ));
+    }
+    if cli.strip_protocol_prefix {
+        builder = builder.transform(normalize::strip_protocol_prefix);
+    }
+    if let Some(contracts) = cli.contracts.clone() {
+        builder = builder.transform(move |protocol_deployments: &mut ProtocolDeployments| {
+            write::retain_contracts(protocol_deployments, &contracts)
+        });
+    }
+
+    Ok(builder)
+}
+
+/// Progress bar on an interactive stderr unless `--quiet`, no-op otherwise
+#[cfg(feature = "progress")]
+fn progress_reporter(cli: &Cli) -> Box<dyn FnMut(ProgressEvent)> {
+    use std::io::IsTerminal;
+
+    if cli.quiet || !std::io::stderr().is_terminal() {
+        return Box::new(|_| {});
+    }
+
+    let reporter = progress_bar::Reporter::new();
+    Box::new(move |event| reporter.report(event))
+}
+
+#[cfg(not(feature = "progress"))]
+fn progress_reporter(_cli: &Cli) -> Box<dyn FnMut(ProgressEvent)> {
+    Box::new(|_| {})
+}
+
+/// Prints the diff of the registry against the one published at `url_base`
+#[cfg(feature = "fetch")]
+fn print_published_diff(url_base: &str, registry: &Registry) -> Result<(), Error> {
+    let fetcher = fetch::Fetcher::new(url_base, PUBLISHED_CACHE_DIR);
+    let diff = fetch::diff_published(&fetcher, registry)?;
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&diff).expect("Diff always serializes")
+    );
+
+    Ok(())
+}
+
+/// Whether balancer sources are downloaded instead of read from `--balancer-path`
+#[cfg(feature = "fetch")]
+fn uses_fetch(cli: &Cli) -> bool {
+    cli.fetch_url_base.is_some()
+}
+
+#[cfg(not(feature = "fetch"))]
+fn uses_fetch(_cli: &Cli) -> bool {
+    false
+}
+
+#[cfg(feature = "fetch")]
+fn balancer_repo_path(cli: &Cli) -> Result<String, Error> {
+    let Some(url_base) = &cli.fetch_url_base else {
+        return Ok(cli.balancer_path.clone());
+    };
+
+    let fetcher = fetch::Fetcher::new(url_base, &cli.fetch_cache_dir);
+    let path = fetch::fetch_balancer(&fetcher)?;
+
+    Ok(path.to_string_lossy().into_owned())
+}
+
+#[cfg(not(feature = "fetch"))]
+fn balancer_repo_path(cli: &Cli) -> Result<String, Error> {
+    Ok(cli.balancer_path.clone())
 }
@@ -0,0 +1,118 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::types::{ChainId, ContractName, ProtocolDeployments};
+
+/// Protocol × chain coverage, stored as `matrix.json` at the root of the output folder
+#[derive(Debug, Default, Serialize)]
+pub struct Matrix {
+    pub protocols: BTreeMap<String, ProtocolMatrix>,
+}
+
+/// `present` of every chain is aligned with `contracts`
+#[derive(Debug, Serialize)]
+pub struct ProtocolMatrix {
+    pub contracts: Vec<ContractName>,
+    pub chains: BTreeMap<ChainId, ChainCoverage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChainCoverage {
+    pub present: Vec<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completeness: Option<f64>,
+}
+
+impl Matrix {
+    /// Columns are the required contracts, or every contract seen when the protocol has none
+    pub fn add(&mut self, protocol_deployments: &ProtocolDeployments) {
+        let contracts: Vec<ContractName> = if protocol_deployments.required_contracts.is_empty() {
+            protocol_deployments
+                .chains
+                .values()
+                .flat_map(|contracts| contracts.keys().cloned())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect()
+        } else {
+            protocol_deployments.required_contracts.clone()
+        };
+
+        let chains = protocol_deployments
+            .chains
+            .iter()
+            .map(|(chain_id, chain_contracts)| {
+                let coverage = ChainCoverage {
+                    present: contracts
+                        .iter()
+                        .map(|name| chain_contracts.contains_key(name))
+                        .collect(),
+                    completeness: protocol_deployments.completeness(*chain_id),
+                };
+                (*chain_id, coverage)
+            })
+            .collect();
+
+        self.protocols.insert(
+            protocol_deployments.protocol_name.clone(),
+            ProtocolMatrix { contracts, chains },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::types::ChainContracts;
+
+    fn contracts(names: &[&str]) -> ChainContracts {
+        names
+            .iter()
+            .map(|name| (name.to_string(), format!("0x{}", name)))
+            .collect()
+    }
+
+    #[test]
+    fn test_matrix_two_protocols_three_chains() {
+        let mut uniswap_v2 = ProtocolDeployments::new(
+            "uniswap-v2",
+            HashMap::from([
+                (1, contracts(&["UniswapV2Factory", "UniswapV2Router02"])),
+                (10, contracts(&["UniswapV2Factory"])),
+            ]),
+        );
+        uniswap_v2.required_contracts = vec![
+            "UniswapV2Factory".to_string(),
+            "UniswapV2Router02".to_string(),
+        ];
+
+        let balancer_v2 = ProtocolDeployments::new(
+            "balancer-v2",
+            HashMap::from([
+                (1, contracts(&["Vault", "BalancerQueries"])),
+                (8453, contracts(&["Vault"])),
+            ]),
+        );
+
+        let mut matrix = Matrix::default();
+        matrix.add(&uniswap_v2);
+        matrix.add(&balancer_v2);
+
+        let uniswap = &matrix.protocols["uniswap-v2"];
+        assert_eq!(uniswap.contracts, ["UniswapV2Factory", "UniswapV2Router02"]);
+        assert_eq!(uniswap.chains.keys().collect::<Vec<_>>(), [&1, &10]);
+        assert_eq!(uniswap.chains[&1].present, [true, true]);
+        assert_eq!(uniswap.chains[&10].present, [true, false]);
+        assert_eq!(uniswap.chains[&10].completeness, Some(0.5));
+
+        let balancer = &matrix.protocols["balancer-v2"];
+        assert_eq!(balancer.contracts, ["BalancerQueries", "Vault"]);
+        assert_eq!(balancer.chains.keys().collect::<Vec<_>>(), [&1, &8453]);
+        assert_eq!(balancer.chains[&1].present, [true, true]);
+        assert_eq!(balancer.chains[&8453].present, [false, true]);
+        assert_eq!(balancer.chains[&8453].completeness, None);
+    }
+}
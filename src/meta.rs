@@ -0,0 +1,155 @@
+use std::{collections::BTreeMap, fs::File, io::BufReader, path::Path};
+
+use crate::types::{ChainId, ContractMetadata, ContractName, ContractStatus, ProtocolDeployments};
+
+type ProtocolMetadata = BTreeMap<ContractName, ContractMetadata>;
+
+/// Deprecated contracts of a protocol, per chain
+type ProtocolDeprecations = BTreeMap<ChainId, Vec<ContractName>>;
+
+fn meta_file_name(protocol_name: &str) -> String {
+    format!("{}.meta.json", protocol_name)
+}
+
+fn deprecated_file_name(protocol_name: &str) -> String {
+    format!("{}.deprecated.json", protocol_name)
+}
+
+/// Merges `<folder>/<protocol>.meta.json` into the deployments, if the file exists
+pub fn apply(
+    folder: &str,
+    protocol_deployments: &mut ProtocolDeployments,
+) -> Result<(), std::io::Error> {
+    let path = format!(
+        "{}/{}",
+        folder,
+        meta_file_name(&protocol_deployments.protocol_name)
+    );
+
+    if !Path::new(&path).exists() {
+        return Ok(());
+    }
+
+    let protocol_metadata: ProtocolMetadata =
+        serde_json::from_reader(BufReader::new(File::open(&path)?))?;
+
+    for (chain_id, contracts) in &protocol_deployments.chains {
+        for name in contracts.keys() {
+            let Some(file_metadata) = protocol_metadata.get(name) else {
+                continue;
+            };
+
+            let metadata = protocol_deployments
+                .metadata
+                .entry(*chain_id)
+                .or_default()
+                .entry(name.clone())
+                .or_default();
+
+            if file_metadata.description.is_some() {
+                metadata.description = file_metadata.description.clone();
+            }
+            if file_metadata.docs.is_some() {
+                metadata.docs = file_metadata.docs.clone();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Marks every contract active, except those listed per chain in
+/// `<folder>/<protocol>.deprecated.json`, for sources publishing no status
+pub fn apply_statuses(
+    folder: &str,
+    protocol_deployments: &mut ProtocolDeployments,
+) -> Result<(), std::io::Error> {
+    let path = format!(
+        "{}/{}",
+        folder,
+        deprecated_file_name(&protocol_deployments.protocol_name)
+    );
+
+    let deprecations: ProtocolDeprecations = if Path::new(&path).exists() {
+        serde_json::from_reader(BufReader::new(File::open(&path)?))?
+    } else {
+        ProtocolDeprecations::new()
+    };
+
+    for (chain_id, contracts) in &protocol_deployments.chains {
+        let deprecated = deprecations.get(chain_id);
+        let chain_metadata = protocol_deployments.metadata.entry(*chain_id).or_default();
+
+        for name in contracts.keys() {
+            let status = if deprecated.is_some_and(|deprecated| deprecated.contains(name)) {
+                ContractStatus::Deprecated
+            } else {
+                ContractStatus::Active
+            };
+            chain_metadata.entry(name.clone()).or_default().status = Some(status);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::types::ChainContracts;
+
+    fn uniswap_v3() -> ProtocolDeployments {
+        let mut contracts = ChainContracts::new();
+        contracts.insert("UniswapV3Factory".to_string(), "0x1F98".to_string());
+        contracts.insert("TickLens".to_string(), "0xbfd8".to_string());
+
+        ProtocolDeployments::new("uniswap-v3", HashMap::from([(1, contracts)]))
+    }
+
+    #[test]
+    fn test_apply_merges_meta_file() {
+        let mut deployments = uniswap_v3();
+
+        apply("tests/fixtures/meta", &mut deployments).unwrap();
+
+        let enriched = deployments.enriched_chain(1).unwrap();
+        let factory = enriched.get("UniswapV3Factory").unwrap();
+        assert_eq!(factory.address, "0x1F98");
+        assert!(factory.metadata.description.is_some());
+        assert_eq!(
+            factory.metadata.docs.as_deref(),
+            Some("https://docs.uniswap.org/contracts/v3/reference/core/UniswapV3Factory")
+        );
+
+        let json = serde_json::to_value(&enriched).unwrap();
+        assert_eq!(json["TickLens"], serde_json::json!({ "address": "0xbfd8" }));
+    }
+
+    #[test]
+    fn test_apply_without_meta_file() {
+        let mut deployments = uniswap_v3();
+
+        apply("tests/fixtures/meta/missing", &mut deployments).unwrap();
+
+        assert!(deployments.metadata.is_empty());
+    }
+
+    #[test]
+    fn test_apply_statuses_marks_listed_contracts_deprecated() {
+        let mut deployments = uniswap_v3();
+
+        apply_statuses("tests/fixtures/meta", &mut deployments).unwrap();
+
+        let enriched = deployments.enriched_chain(1).unwrap();
+        assert_eq!(
+            enriched["UniswapV3Factory"].metadata.status,
+            Some(ContractStatus::Active)
+        );
+        assert_eq!(
+            enriched["TickLens"].metadata.status,
+            Some(ContractStatus::Deprecated)
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt::Write;
+
+use crate::{index::Index, warnings::Warning};
+
+/// Run metrics in Prometheus textfile format, built from the run's index and warnings
+pub fn render(index: &Index, warnings: &[Warning]) -> String {
+    let mut out = String::new();
+
+    write_header(
+        &mut out,
+        "contracts_total",
+        "Contracts written per protocol",
+    );
+    for (protocol_name, protocol) in &index.protocols {
+        let contracts: usize = protocol
+            .chains
+            .values()
+            .map(|chain| chain.contract_count)
+            .sum();
+        writeln!(
+            out,
+            "evm_dex_index_contracts_total{{protocol=\"{}\"}} {}",
+            protocol_name, contracts
+        )
+        .unwrap();
+    }
+
+    write_header(&mut out, "chains_total", "Chains written per protocol");
+    for (protocol_name, protocol) in &index.protocols {
+        writeln!(
+            out,
+            "evm_dex_index_chains_total{{protocol=\"{}\"}} {}",
+            protocol_name,
+            protocol.chains.len()
+        )
+        .unwrap();
+    }
+
+    write_header(&mut out, "warnings_total", "Warnings raised by the run");
+    writeln!(out, "evm_dex_index_warnings_total {}", warnings.len()).unwrap();
+
+    out
+}
+
+fn write_header(out: &mut String, name: &str, help: &str) {
+    writeln!(out, "# HELP evm_dex_index_{} {}", name, help).unwrap();
+    writeln!(out, "# TYPE evm_dex_index_{} gauge", name).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_fixture_metrics() {
+        let mut warnings = Vec::new();
+        let protocols =
+            crate::uniswap::parse("tests/fixtures/uniswap/deployments", &mut warnings).unwrap();
+        let mut index = Index::default();
+        for protocol_deployments in &protocols {
+            index.add(protocol_deployments);
+        }
+
+        let metrics = render(&index, &warnings);
+
+        let lines: Vec<&str> = metrics.lines().collect();
+        assert!(lines.contains(&"evm_dex_index_contracts_total{protocol=\"permit2\"} 2"));
+        assert!(lines.contains(&"evm_dex_index_chains_total{protocol=\"uniswap-v3\"} 2"));
+        assert!(
+            lines.contains(&format!("evm_dex_index_warnings_total {}", warnings.len()).as_str())
+        );
+        assert!(lines.contains(&"# TYPE evm_dex_index_contracts_total gauge"));
+    }
+}
@@ -0,0 +1,556 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use thiserror::Error;
+
+use crate::{
+    address::{is_bytes32, is_valid_address},
+    chains::{self, ChainAliases},
+    types::{ChainId, ContractName, ProtocolDeployments},
+    warnings::{Warning, WarningKind},
+};
+
+/// Splits an EIP-3770 `<short name>:<address>` with a recognized short name and a valid address
+/// into the chain id the prefix implies and the bare address
+pub fn split_chain_prefix(value: &str) -> Option<(ChainId, &str)> {
+    let (short_name, address) = value.split_once(':')?;
+    let chain_id = chains::chain_id_for_short_name(short_name)?;
+
+    is_valid_address(address).then_some((chain_id, address))
+}
+
+/// Hex digits without a `0x` prefix
+fn is_bare_hex(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Adds the `0x` prefix to a bare 40-character hex string
+pub fn prefix_bare_hex(value: &str) -> Option<String> {
+    (value.len() == 40 && is_bare_hex(value)).then(|| format!("0x{}", value))
+}
+
+/// A 32-byte value whose top 12 bytes aren't zero, or bare hex that isn't 40 characters long,
+/// so it can't be an address
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error(
+    "Contract '{contract}' of protocol '{protocol}' on chain {chain_id} has value '{value}', which is not an address"
+)]
+pub struct NotAnAddress {
+    pub protocol: String,
+    pub chain_id: ChainId,
+    pub contract: ContractName,
+    pub value: String,
+}
+
+/// The address inside a 32-byte value whose top 12 bytes are zero, e.g. an address stored
+/// as `bytes32`
+pub fn unpad_bytes32(value: &str) -> Option<String> {
+    if !is_bytes32(value) {
+        return None;
+    }
+
+    let (padding, address) = value[2..].split_at(24);
+    padding
+        .chars()
+        .all(|c| c == '0')
+        .then(|| format!("0x{}", address))
+}
+
+/// Rewrites source addresses into plain `0x` addresses, warning about anything suspicious.
+/// Fails on a 32-byte value that isn't a zero-padded address, or on bare hex of the wrong length.
+pub fn addresses(
+    protocol_deployments: &mut ProtocolDeployments,
+) -> Result<Vec<Warning>, NotAnAddress> {
+    let mut warnings = Vec::new();
+
+    let mut chain_ids: Vec<_> = protocol_deployments.chains.keys().copied().collect();
+    chain_ids.sort();
+
+    for chain_id in chain_ids {
+        let contracts = protocol_deployments
+            .chains
+            .get_mut(&chain_id)
+            .expect("Chain id taken from the map");
+
+        for (name, address) in contracts.iter_mut() {
+            if let Some(prefixed) = prefix_bare_hex(address) {
+                *address = prefixed;
+                continue;
+            }
+
+            if let Some(unpadded) = unpad_bytes32(address) {
+                *address = unpadded;
+                continue;
+            }
+            if is_bytes32(address) || is_bare_hex(address) {
+                return Err(NotAnAddress {
+                    protocol: protocol_deployments.protocol_name.clone(),
+                    chain_id,
+                    contract: name.clone(),
+                    value: address.clone(),
+                });
+            }
+
+            let Some((prefix_chain_id, bare_address)) = split_chain_prefix(address) else {
+                continue;
+            };
+
+            if prefix_chain_id != chain_id {
+                warnings.push(Warning {
+                    kind: WarningKind::ChainMismatch,
+                    protocol: Some(protocol_deployments.protocol_name.clone()),
+                    chain_id: Some(chain_id),
+                    detail: format!(
+                        "address '{}' of contract '{}' is prefixed for chain {}",
+                        address, name, prefix_chain_id
+                    ),
+                });
+            }
+
+            *address = bare_address.to_string();
+        }
+    }
+
+    Ok(warnings)
+}
+
+/// `UniswapV3` for `uniswap-v3`: every dash-separated part of the protocol name, capitalized
+fn protocol_prefix(protocol_name: &str) -> String {
+    protocol_name
+        .split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Drops the protocol-derived prefix from contract names, e.g. `UniswapV3Factory` → `Factory`
+/// in `uniswap-v3`. A name is kept whole if its stripped form would clash with another
+/// contract of the protocol on any chain.
+pub fn strip_protocol_prefix(protocol_deployments: &mut ProtocolDeployments) {
+    let prefix = protocol_prefix(&protocol_deployments.protocol_name);
+
+    let strip = |name: &str| -> Option<String> {
+        let rest = name.get(prefix.len()..)?;
+        let is_prefixed = name[..prefix.len()].eq_ignore_ascii_case(&prefix)
+            && rest.starts_with(|c: char| c.is_ascii_uppercase());
+
+        is_prefixed.then(|| rest.to_string())
+    };
+
+    let names: BTreeSet<&ContractName> = protocol_deployments
+        .chains
+        .values()
+        .flat_map(|contracts| contracts.keys())
+        .collect();
+
+    let mut stripped_by: BTreeMap<String, Vec<&ContractName>> = BTreeMap::new();
+    for name in &names {
+        if let Some(stripped) = strip(name) {
+            stripped_by.entry(stripped).or_default().push(name);
+        }
+    }
+
+    let renames: BTreeMap<ContractName, ContractName> = stripped_by
+        .into_iter()
+        .filter(|(stripped, from)| from.len() == 1 && !names.contains(stripped))
+        .map(|(stripped, from)| (from[0].clone(), stripped))
+        .collect();
+
+    let rename = |name: ContractName| renames.get(&name).cloned().unwrap_or(name);
+
+    for contracts in protocol_deployments.chains.values_mut() {
+        *contracts = std::mem::take(contracts)
+            .into_iter()
+            .map(|(name, address)| (rename(name), address))
+            .collect();
+    }
+    for metadata in protocol_deployments.metadata.values_mut() {
+        *metadata = std::mem::take(metadata)
+            .into_iter()
+            .map(|(name, contract_metadata)| (rename(name), contract_metadata))
+            .collect();
+    }
+    for candidates in protocol_deployments.candidates.values_mut() {
+        *candidates = std::mem::take(candidates)
+            .into_iter()
+            .map(|(name, count)| (rename(name), count))
+            .collect();
+    }
+    for signatures in protocol_deployments.signatures.values_mut() {
+        *signatures = std::mem::take(signatures)
+            .into_iter()
+            .map(|(name, signature)| (rename(name), signature))
+            .collect();
+    }
+    for name in protocol_deployments.required_contracts.iter_mut().chain(
+        protocol_deployments
+            .chain_required_contracts
+            .values_mut()
+            .flatten(),
+    ) {
+        *name = rename(std::mem::take(name));
+    }
+}
+
+/// Folds the contracts of aliased chains into their canonical chain, aliases applied in chain
+/// id order. On a name both chains have at different addresses, the later deployment wins by
+/// signature, the chain folded into keeping its address when either has none, with a warning.
+pub fn fold_chain_aliases(
+    protocol_deployments: &mut ProtocolDeployments,
+    aliases: &ChainAliases,
+) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    for (from, to) in aliases {
+        if from == to {
+            continue;
+        }
+
+        let contracts = protocol_deployments.chains.remove(from).unwrap_or_default();
+        let mut metadata = protocol_deployments
+            .metadata
+            .remove(from)
+            .unwrap_or_default();
+        let mut candidates = protocol_deployments
+            .candidates
+            .remove(from)
+            .unwrap_or_default();
+        let mut signatures = protocol_deployments
+            .signatures
+            .remove(from)
+            .unwrap_or_default();
+
+        for (name, address) in contracts {
+            let existing = protocol_deployments
+                .chains
+                .get(to)
+                .and_then(|contracts| contracts.get(&name));
+            if let Some(existing) = existing {
+                if *existing == address {
+                    continue;
+                }
+
+                let existing_signature = protocol_deployments
+                    .signatures
+                    .get(to)
+                    .and_then(|signatures| signatures.get(&name));
+                let alias_is_later = matches!(
+                    (signatures.get(&name), existing_signature),
+                    (Some(signature), Some(existing_signature)) if signature > existing_signature
+                );
+                let kept = if alias_is_later { &address } else { existing };
+                warnings.push(Warning {
+                    kind: WarningKind::AliasCollision,
+                    protocol: Some(protocol_deployments.protocol_name.clone()),
+                    chain_id: Some(*to),
+                    detail: format!(
+                        "contract '{}' is at '{}' and at '{}' on aliased chain {}, keeping '{}'",
+                        name, existing, address, from, kept
+                    ),
+                });
+                if !alias_is_later {
+                    continue;
+                }
+            }
+
+            move_entry(
+                &mut protocol_deployments.metadata,
+                metadata.remove(&name),
+                *to,
+                &name,
+            );
+            move_entry(
+                &mut protocol_deployments.candidates,
+                candidates.remove(&name),
+                *to,
+                &name,
+            );
+            move_entry(
+                &mut protocol_deployments.signatures,
+                signatures.remove(&name),
+                *to,
+                &name,
+            );
+            protocol_deployments
+                .chains
+                .entry(*to)
+                .or_default()
+                .insert(name, address);
+        }
+    }
+
+    warnings
+}
+
+/// Sets a contract's entry of a per-chain side map to the one it's folded with, dropping the
+/// entry it replaces
+fn move_entry<V>(
+    side: &mut HashMap<ChainId, BTreeMap<ContractName, V>>,
+    value: Option<V>,
+    chain_id: ChainId,
+    name: &str,
+) {
+    match value {
+        Some(value) => {
+            side.entry(chain_id)
+                .or_default()
+                .insert(name.to_string(), value);
+        }
+        None => {
+            if let Some(entries) = side.get_mut(&chain_id) {
+                entries.remove(name);
+            }
+        }
+    }
+}
+
+/// Two protocols would be written under the same name after renaming
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("More than one protocol would be named '{0}'")]
+pub struct ProtocolNameCollision(pub String);
+
+/// Renames protocols per the `(old, new)` pairs, failing without renaming anything when two
+/// protocols would end up with the same name
+pub fn rename_protocols(
+    protocols: &mut [ProtocolDeployments],
+    renames: &[(String, String)],
+) -> Result<(), ProtocolNameCollision> {
+    let renamed = |name: &str| {
+        renames
+            .iter()
+            .find(|(old, _)| old == name)
+            .map_or(name, |(_, new)| new.as_str())
+            .to_string()
+    };
+
+    let mut names = BTreeSet::new();
+    for protocol_deployments in protocols.iter() {
+        let name = renamed(&protocol_deployments.protocol_name);
+        if !names.insert(name.clone()) {
+            return Err(ProtocolNameCollision(name));
+        }
+    }
+
+    for protocol_deployments in protocols {
+        let name = renamed(&protocol_deployments.protocol_name);
+        if name != protocol_deployments.protocol_name {
+            let parsed_name = std::mem::replace(&mut protocol_deployments.protocol_name, name);
+            protocol_deployments.renamed_from.get_or_insert(parsed_name);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::types::{ChainContracts, ChainSignatures};
+
+    const PERMIT2: &str = "0x000000000022D473030F116dDEE9F6B43aC78BA3";
+
+    fn deployments(chain_id: ChainId, address: &str) -> ProtocolDeployments {
+        let contracts = ChainContracts::from([("Permit2".to_string(), address.to_string())]);
+        ProtocolDeployments::new("permit2", HashMap::from([(chain_id, contracts)]))
+    }
+
+    #[test]
+    fn test_prefixed_address_matching_chain() {
+        let mut deployments = deployments(8453, &format!("base:{}", PERMIT2));
+
+        let warnings = addresses(&mut deployments).unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(deployments.chains[&8453]["Permit2"], PERMIT2);
+    }
+
+    #[test]
+    fn test_prefixed_address_mismatching_chain() {
+        let mut deployments = deployments(1, &format!("base:{}", PERMIT2));
+
+        let warnings = addresses(&mut deployments).unwrap();
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::ChainMismatch);
+        assert_eq!(deployments.chains[&1]["Permit2"], PERMIT2);
+    }
+
+    #[test]
+    fn test_unrecognized_prefix_is_kept() {
+        assert_eq!(split_chain_prefix(&format!("nope:{}", PERMIT2)), None);
+        assert_eq!(split_chain_prefix("eth:0x1234"), None);
+        assert_eq!(split_chain_prefix(PERMIT2), None);
+    }
+
+    #[test]
+    fn test_bare_hex_address_gets_prefix() {
+        let mut deployments = deployments(1, PERMIT2.trim_start_matches("0x"));
+
+        let warnings = addresses(&mut deployments).unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(deployments.chains[&1]["Permit2"], PERMIT2);
+    }
+
+    #[test]
+    fn test_wrong_length_bare_hex_fails() {
+        let value = "000000000022D473030F116dDEE9F6B43aC78BA";
+        let mut deployments = deployments(1, value);
+
+        let err = addresses(&mut deployments).unwrap_err();
+
+        assert_eq!(prefix_bare_hex(value), None);
+        assert_eq!(err.contract, "Permit2");
+        assert_eq!(err.value, value);
+    }
+
+    #[test]
+    fn test_padded_address_is_unpadded() {
+        let mut deployments = deployments(
+            1,
+            "0x000000000000000000000000000000000022D473030F116dDEE9F6B43aC78BA3",
+        );
+
+        let warnings = addresses(&mut deployments).unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(deployments.chains[&1]["Permit2"], PERMIT2);
+    }
+
+    #[test]
+    fn test_padded_value_with_nonzero_top_bytes_fails() {
+        let value = "0x0000000000000000000000010000000000000000000000000000000000000001";
+        let mut deployments = deployments(1, value);
+
+        let err = addresses(&mut deployments).unwrap_err();
+
+        assert_eq!(err.contract, "Permit2");
+        assert_eq!(err.value, value);
+    }
+
+    #[test]
+    fn test_strip_protocol_prefix() {
+        let contracts = |names: &[&str]| -> ChainContracts {
+            names
+                .iter()
+                .map(|name| (name.to_string(), PERMIT2.to_string()))
+                .collect()
+        };
+        let mut deployments = ProtocolDeployments::new(
+            "uniswap-v3",
+            HashMap::from([
+                (
+                    1,
+                    contracts(&["UniswapV3Factory", "UniswapV3Staker", "QuoterV2"]),
+                ),
+                (8453, contracts(&["UniswapV3Factory", "Staker"])),
+            ]),
+        );
+        deployments.required_contracts = vec!["UniswapV3Factory".to_string()];
+
+        strip_protocol_prefix(&mut deployments);
+
+        assert!(deployments.chains[&1].contains_key("Factory"));
+        assert!(deployments.chains[&8453].contains_key("Factory"));
+        assert!(deployments.chains[&1].contains_key("UniswapV3Staker"));
+        assert!(deployments.chains[&1].contains_key("QuoterV2"));
+        assert_eq!(deployments.required_contracts, vec!["Factory".to_string()]);
+    }
+
+    #[test]
+    fn test_fold_chain_aliases() {
+        let contracts = |entries: &[(&str, &str)]| -> ChainContracts {
+            entries
+                .iter()
+                .map(|(name, address)| (name.to_string(), address.to_string()))
+                .collect()
+        };
+        let mut deployments = ProtocolDeployments::new(
+            "uniswap-v3",
+            HashMap::from([
+                (1, contracts(&[("Factory", "0x01"), ("Router", "0x02")])),
+                (1337, contracts(&[("Router", "0x03"), ("Quoter", "0x04")])),
+            ]),
+        );
+
+        deployments.signatures = HashMap::from([
+            (
+                1,
+                ChainSignatures::from([("Factory".to_string(), "20210101-v1".to_string())]),
+            ),
+            (
+                1337,
+                ChainSignatures::from([("Quoter".to_string(), "20220101-v1".to_string())]),
+            ),
+        ]);
+
+        let warnings = fold_chain_aliases(&mut deployments, &ChainAliases::from([(1337, 1)]));
+
+        assert_eq!(deployments.chains.len(), 1);
+        assert_eq!(
+            deployments.chains[&1],
+            contracts(&[("Factory", "0x01"), ("Quoter", "0x04"), ("Router", "0x02")])
+        );
+        assert_eq!(deployments.signatures[&1]["Quoter"], "20220101-v1");
+        assert!(!deployments.signatures.contains_key(&1337));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::AliasCollision);
+    }
+
+    #[test]
+    fn test_fold_chain_aliases_keeps_later_deployment_on_collision() {
+        let router =
+            |address: &str| ChainContracts::from([("Router".to_string(), address.to_string())]);
+        let signature = |signature: &str| {
+            ChainSignatures::from([("Router".to_string(), signature.to_string())])
+        };
+        let mut deployments = ProtocolDeployments::new(
+            "balancer-v2",
+            HashMap::from([
+                (1, router("0x01")),
+                (1337, router("0x02")),
+                (1338, router("0x03")),
+            ]),
+        );
+        deployments.signatures = HashMap::from([
+            (1, signature("20220101-router")),
+            (1337, signature("20230101-router")),
+            (1338, signature("20210101-router")),
+        ]);
+
+        let warnings = fold_chain_aliases(
+            &mut deployments,
+            &ChainAliases::from([(1337, 1), (1338, 1)]),
+        );
+
+        assert_eq!(deployments.chains[&1]["Router"], "0x02");
+        assert_eq!(deployments.signatures[&1]["Router"], "20230101-router");
+        assert_eq!(warnings.len(), 2);
+    }
+
+    #[test]
+    fn test_rename_protocols_rejects_collision() {
+        let protocol = |name| ProtocolDeployments::new(name, HashMap::new());
+        let mut protocols = [protocol("uniswap-v3"), protocol("uniswap-v4")];
+
+        let renames = [("uniswap-v3".to_string(), "univ3".to_string())];
+        rename_protocols(&mut protocols, &renames).unwrap();
+        assert_eq!(protocols[0].protocol_name, "univ3");
+        assert_eq!(protocols[0].renamed_from.as_deref(), Some("uniswap-v3"));
+        assert_eq!(protocols[1].protocol_name, "uniswap-v4");
+        assert_eq!(protocols[1].renamed_from, None);
+
+        let renames = [("univ3".to_string(), "uniswap-v4".to_string())];
+        assert_eq!(
+            rename_protocols(&mut protocols, &renames),
+            Err(ProtocolNameCollision("uniswap-v4".to_string()))
+        );
+        assert_eq!(protocols[0].protocol_name, "univ3");
+    }
+}
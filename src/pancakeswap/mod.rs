@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+use tracing::debug;
+
+use crate::{
+    meta, pattern,
+    source_files::{self, ReadError},
+    types::{ChainContracts, ChainDeployments, ContractAddress, ProtocolDeployments},
+};
+
+pub const INFINITY_PROTOCOL_NAME: &str = "pancakeswap-infinity";
+
+/// Pool managers and the vault every Infinity chain is expected to have
+const INFINITY_CONTRACTS: &[&str] = &["Vault", "CLPoolManager", "BinPoolManager"];
+
+/// Periphery of the Infinity architecture that only exists on some chains
+const INFINITY_OPTIONAL_CONTRACTS: &[&str] = &[
+    "CLPositionManager",
+    "BinPositionManager",
+    "CLQuoter",
+    "BinQuoter",
+    "CLPositionDescriptor",
+    "MixedQuoter",
+    "InfinityRouter",
+];
+
+/// Name patterns (`*` wildcard) of Infinity hook contracts, all optional
+const INFINITY_HOOK_PATTERNS: &[&str] = &["*Hook"];
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Serde error in '{path}': {source}")]
+    SerdeError {
+        path: String,
+        source: serde_json::Error,
+    },
+}
+
+impl From<ReadError> for ParseError {
+    fn from(err: ReadError) -> Self {
+        match err {
+            ReadError::IoError(err) => Self::IoError(err),
+            ReadError::SerdeError { path, source } => Self::SerdeError { path, source },
+        }
+    }
+}
+
+fn is_infinity_contract(name: &str) -> bool {
+    INFINITY_CONTRACTS.contains(&name)
+        || INFINITY_OPTIONAL_CONTRACTS.contains(&name)
+        || INFINITY_HOOK_PATTERNS
+            .iter()
+            .any(|pattern| pattern::matches(pattern, name))
+}
+
+/// Parses `<path>/<chain_id>.json` files, each a map of contract name to address, into
+/// the Infinity contract set. Other contracts, such as Pancake's v3, are left out.
+pub fn parse(path: &str) -> Result<ProtocolDeployments, ParseError> {
+    let files = source_files::read_json_files(path, |file_path| {
+        source_files::chain_id_of(file_path).is_some()
+    })?;
+
+    let mut chains = ChainDeployments::new();
+    for (file_path, source) in files {
+        let chain_id = source_files::chain_id_of(&file_path).expect("Accepted by chain id");
+        let contracts = parse_chain(source);
+        if !contracts.is_empty() {
+            chains.insert(chain_id, contracts);
+        }
+    }
+
+    let mut deployments = ProtocolDeployments::new(INFINITY_PROTOCOL_NAME, chains);
+    deployments.required_contracts = INFINITY_CONTRACTS.iter().map(|s| s.to_string()).collect();
+    meta::apply(path, &mut deployments)?;
+
+    Ok(deployments)
+}
+
+fn parse_chain(source: HashMap<String, ContractAddress>) -> ChainContracts {
+    let mut contracts = ChainContracts::new();
+
+    for (name, address) in source {
+        if is_infinity_contract(&name) {
+            contracts.insert(name, address);
+        } else {
+            debug!(
+                contract = name,
+                "Skipping contract outside the Infinity contract set"
+            );
+        }
+    }
+
+    contracts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_infinity_on_bsc() {
+        let infinity = parse("tests/fixtures/pancakeswap/deployments").unwrap();
+
+        assert_eq!(infinity.protocol_name, INFINITY_PROTOCOL_NAME);
+        let bsc = &infinity.chains[&56];
+        assert_eq!(bsc["Vault"], "0x238a358808379702088667322f80aC48bAd5e6c4");
+        assert_eq!(
+            bsc["CLPoolManager"],
+            "0xa0FfB9c1CE1Fe56963B0321B32E7A0302114058b"
+        );
+        assert_eq!(
+            bsc["BinPoolManager"],
+            "0xC697d2898e0D09264376196696c51D7aBbbAA4a9"
+        );
+        assert!(bsc.contains_key("CLDynamicFeeHook"));
+        assert!(!bsc.contains_key("PancakeV3Factory"));
+        assert_eq!(infinity.completeness(56), Some(1.0));
+    }
+}
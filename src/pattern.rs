@@ -0,0 +1,39 @@
+/// Whether `name` matches `pattern`, where `*` stands for any run of characters
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("*Router", "SwapRouter"));
+        assert!(matches("*Router", "Router"));
+        assert!(matches("Uniswap*Factory", "UniswapV3Factory"));
+        assert!(matches("Permit2", "Permit2"));
+        assert!(!matches("Permit2", "Permit22"));
+        assert!(!matches("*Router", "RouterV2"));
+        assert!(!matches("a*b*c", "acb"));
+    }
+}
@@ -0,0 +1,8 @@
+/// Step of a run, reported to progress consumers such as a progress bar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// A source finished parsing
+    SourceParsed { source: &'static str },
+    /// A file was written, path relative to the output folder
+    FileWritten { path: String },
+}
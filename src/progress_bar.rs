@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use evm_dex_index::progress::ProgressEvent;
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Spinner on stderr counting parsed sources and written files
+pub struct Reporter {
+    bar: ProgressBar,
+}
+
+impl Reporter {
+    pub fn new() -> Self {
+        let bar = ProgressBar::new_spinner();
+        bar.set_style(
+            ProgressStyle::with_template("{spinner} [{elapsed}] {pos} steps: {msg}")
+                .expect("Valid progress template"),
+        );
+        bar.enable_steady_tick(Duration::from_millis(100));
+
+        Self { bar }
+    }
+
+    pub fn report(&self, event: ProgressEvent) {
+        let message = match event {
+            ProgressEvent::SourceParsed { source } => format!("parsed {}", source),
+            ProgressEvent::FileWritten { path } => format!("wrote {}", path),
+        };
+
+        self.bar.inc(1);
+        self.bar.set_message(message);
+    }
+}
+
+impl Drop for Reporter {
+    fn drop(&mut self) {
+        self.bar.finish_and_clear();
+    }
+}
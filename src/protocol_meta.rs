@@ -0,0 +1,40 @@
+use crate::{algebra, balancer, pancakeswap, uniswap};
+
+/// Family of the protocol, rendered as one collapsible group by frontends. Derived from the
+/// parser producing the protocol, so every `balancer-<version>` is `balancer`. `None` for
+/// protocols of no parser, e.g. CSV sources.
+pub fn family(protocol_name: &str) -> Option<&'static str> {
+    let is_balancer = protocol_name == balancer::MERGED_PROTOCOL_NAME
+        || protocol_name.starts_with(&format!("{}-", balancer::MERGED_PROTOCOL_NAME));
+
+    if uniswap::protocol_names().any(|name| name == protocol_name) {
+        Some("uniswap")
+    } else if is_balancer {
+        Some("balancer")
+    } else if protocol_name == pancakeswap::INFINITY_PROTOCOL_NAME {
+        Some("pancakeswap")
+    } else if algebra::ALGEBRA_DEXES
+        .iter()
+        .any(|dex| dex.protocol_name == protocol_name)
+    {
+        Some("algebra")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_family() {
+        assert_eq!(family("uniswap-v3"), Some("uniswap"));
+        assert_eq!(family("permit2"), Some("uniswap"));
+        assert_eq!(family("balancer-v2"), Some("balancer"));
+        assert_eq!(family("balancer-v4"), Some("balancer"));
+        assert_eq!(family("camelot-v3"), Some("algebra"));
+        assert_eq!(family("balancerish"), None);
+        assert_eq!(family("univ3"), None);
+    }
+}
@@ -0,0 +1,1408 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    num::NonZeroUsize,
+    path::Path,
+    sync::Arc,
+};
+
+use thiserror::Error;
+
+use crate::{
+    algebra,
+    balancer::{self, DeprecatedContracts},
+    bytecode::CodeProvider,
+    chains::ChainAliases,
+    csv_source,
+    diff::{DeploymentDiff, RegistryDiff},
+    git,
+    ignore::IgnoreList,
+    normalize::{self, NotAnAddress},
+    pancakeswap,
+    progress::ProgressEvent,
+    source_files,
+    transform::{AddressCase, ChainFilter, CompleteChains, ProtocolFilter, Transform},
+    types::{ChainContracts, ChainId, ContractAddress, ContractName, ProtocolDeployments},
+    uniswap,
+    warnings::Warning,
+};
+
+/// What to do when both registries hold a contract under different addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    /// Fail the merge, leaving `self` untouched
+    #[default]
+    Error,
+    KeepSelf,
+    KeepOther,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MergeError {
+    #[error(
+        "Conflicting addresses for contract '{contract_name}' of protocol '{protocol_name}' on chain {chain_id}: '{existing}' vs '{incoming}'"
+    )]
+    Conflict {
+        protocol_name: String,
+        chain_id: ChainId,
+        contract_name: ContractName,
+        existing: ContractAddress,
+        incoming: ContractAddress,
+    },
+}
+
+/// Parsed deployments of several protocols, keyed by protocol name
+#[derive(Debug, Default)]
+pub struct Registry {
+    protocols: BTreeMap<String, ProtocolDeployments>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn builder() -> RegistryBuilder {
+        RegistryBuilder::new()
+    }
+
+    pub fn get(&self, protocol_name: &str) -> Option<&ProtocolDeployments> {
+        self.protocols.get(protocol_name)
+    }
+
+    /// Protocols ordered by name
+    pub fn protocols(&self) -> impl Iterator<Item = &ProtocolDeployments> {
+        self.protocols.values()
+    }
+
+    /// Every contract as `(protocol, chain id, contract name, address)`, ordered by
+    /// protocol, chain id and contract name
+    pub fn iter_all(&self) -> impl Iterator<Item = (&str, ChainId, &str, &str)> {
+        self.protocols.values().flat_map(|protocol_deployments| {
+            let mut chain_ids: Vec<_> = protocol_deployments.chains.keys().copied().collect();
+            chain_ids.sort();
+
+            chain_ids.into_iter().flat_map(move |chain_id| {
+                protocol_deployments.chains[&chain_id]
+                    .iter()
+                    .map(move |(name, address)| {
+                        (
+                            protocol_deployments.protocol_name.as_str(),
+                            chain_id,
+                            name.as_str(),
+                            address.as_str(),
+                        )
+                    })
+            })
+        })
+    }
+
+    pub fn into_protocols(self) -> Vec<ProtocolDeployments> {
+        self.protocols.into_values().collect()
+    }
+
+    /// Adds a protocol, replacing any protocol of the same name
+    pub fn insert(&mut self, protocol_deployments: ProtocolDeployments) {
+        self.protocols.insert(
+            protocol_deployments.protocol_name.clone(),
+            protocol_deployments,
+        );
+    }
+
+    /// Combines the protocols and chains of `other` into `self`.
+    /// Addresses are compared case-insensitively. A contract taken from `other` brings along
+    /// its metadata, candidates and signature, a replaced address loses those of the old one.
+    pub fn merge(&mut self, other: Registry, policy: ConflictPolicy) -> Result<(), MergeError> {
+        if policy == ConflictPolicy::Error
+            && let Some(conflict) = self.conflicts(&other).into_iter().next()
+        {
+            return Err(conflict);
+        }
+
+        for (protocol_name, incoming) in other.protocols {
+            let Some(existing) = self.protocols.get_mut(&protocol_name) else {
+                self.protocols.insert(protocol_name, incoming);
+                continue;
+            };
+
+            if existing.required_contracts.is_empty() {
+                existing.required_contracts = incoming.required_contracts;
+                existing.chain_required_contracts = incoming.chain_required_contracts;
+            }
+
+            for (chain_id, contracts) in incoming.chains {
+                let mut incoming_metadata = incoming.metadata.get(&chain_id).cloned();
+                let mut incoming_candidates = incoming.candidates.get(&chain_id).cloned();
+                let mut incoming_signatures = incoming.signatures.get(&chain_id).cloned();
+                let existing_contracts = existing.chains.entry(chain_id).or_default();
+
+                for (name, address) in contracts {
+                    let replaced = match existing_contracts.get(&name) {
+                        Some(_) if policy != ConflictPolicy::KeepOther => continue,
+                        Some(existing_address) => !existing_address.eq_ignore_ascii_case(&address),
+                        None => false,
+                    };
+
+                    let metadata = &mut existing.metadata;
+                    take_entry(metadata, &mut incoming_metadata, chain_id, &name, replaced);
+                    let candidates = &mut existing.candidates;
+                    take_entry(
+                        candidates,
+                        &mut incoming_candidates,
+                        chain_id,
+                        &name,
+                        replaced,
+                    );
+                    let signatures = &mut existing.signatures;
+                    take_entry(
+                        signatures,
+                        &mut incoming_signatures,
+                        chain_id,
+                        &name,
+                        replaced,
+                    );
+
+                    existing_contracts.insert(name, address);
+                }
+
+                if let Some(&date) = incoming.newest_deployments.get(&chain_id) {
+                    let newest = existing.newest_deployments.entry(chain_id).or_insert(date);
+                    *newest = (*newest).max(date);
+                }
+            }
+
+            if existing.source.is_none() {
+                existing.source = incoming.source;
+            }
+            if existing.source_ref.is_none() {
+                existing.source_ref = incoming.source_ref;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Contracts added, removed and moved going from `self` to `other`, per protocol and
+    /// chain. A protocol or chain only one side has counts as all added or removed.
+    pub fn diff(&self, other: &Registry) -> RegistryDiff {
+        let empty = ChainContracts::new();
+        let protocol_names: BTreeSet<&String> = self
+            .protocols
+            .keys()
+            .chain(other.protocols.keys())
+            .collect();
+
+        let mut diff = RegistryDiff::new();
+        for protocol_name in protocol_names {
+            let old = self.protocols.get(protocol_name);
+            let new = other.protocols.get(protocol_name);
+            let chain_ids: BTreeSet<ChainId> = [old, new]
+                .into_iter()
+                .flatten()
+                .flat_map(|protocol_deployments| protocol_deployments.chains.keys().copied())
+                .collect();
+
+            for chain_id in chain_ids {
+                let old_contracts = old
+                    .and_then(|protocol_deployments| protocol_deployments.chains.get(&chain_id))
+                    .unwrap_or(&empty);
+                let new_contracts = new
+                    .and_then(|protocol_deployments| protocol_deployments.chains.get(&chain_id))
+                    .unwrap_or(&empty);
+
+                let chain_diff = DeploymentDiff::between(old_contracts, new_contracts);
+                if !chain_diff.is_empty() {
+                    diff.entry(protocol_name.clone())
+                        .or_default()
+                        .insert(chain_id, chain_diff);
+                }
+            }
+        }
+
+        diff
+    }
+
+    /// Contracts both registries hold under different addresses, e.g. two source mirrors
+    /// disagreeing. Addresses differing only in case don't conflict.
+    pub fn conflicts(&self, other: &Registry) -> Vec<MergeError> {
+        let mut conflicts = Vec::new();
+
+        for (protocol_name, incoming) in &other.protocols {
+            let Some(existing) = self.protocols.get(protocol_name) else {
+                continue;
+            };
+
+            let mut chain_ids: Vec<_> = incoming.chains.keys().collect();
+            chain_ids.sort();
+
+            for chain_id in chain_ids {
+                let Some(existing_contracts) = existing.chains.get(chain_id) else {
+                    continue;
+                };
+
+                let mut contracts: Vec<_> = incoming.chains[chain_id].iter().collect();
+                contracts.sort();
+
+                for (name, address) in contracts {
+                    match existing_contracts.get(name) {
+                        Some(existing_address)
+                            if !existing_address.eq_ignore_ascii_case(address) =>
+                        {
+                            conflicts.push(MergeError::Conflict {
+                                protocol_name: protocol_name.clone(),
+                                chain_id: *chain_id,
+                                contract_name: name.clone(),
+                                existing: existing_address.clone(),
+                                incoming: address.clone(),
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        conflicts
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BuildError {
+    #[error("Balancer: {0}")]
+    Balancer(#[from] balancer::ParseError),
+
+    #[error("Uniswap: {0}")]
+    Uniswap(#[from] uniswap::ParseError),
+
+    #[error("PancakeSwap: {0}")]
+    PancakeSwap(#[from] pancakeswap::ParseError),
+
+    #[error("Algebra: {0}")]
+    Algebra(#[from] algebra::ParseError),
+
+    #[error("CSV source: {0}")]
+    CsvSource(#[from] csv_source::ParseError),
+
+    #[error("CSV source conflicts with the parsed deployments: {0}")]
+    CsvSourceConflict(#[from] MergeError),
+
+    #[error("Failed to check out balancer ref: {0}")]
+    BalancerRef(std::io::Error),
+
+    #[error("Failed to build the thread pool: {0}")]
+    ThreadPool(#[from] rayon::ThreadPoolBuildError),
+
+    #[error(transparent)]
+    Address(#[from] NotAnAddress),
+}
+
+/// Configures a parse run over the sources into a [`Registry`].
+/// Only the sources given a path are parsed.
+#[derive(Clone, Default)]
+pub struct RegistryBuilder {
+    balancer_path: Option<String>,
+    balancer_extra_paths: Vec<String>,
+    balancer_ref: Option<String>,
+    balancer_merge: bool,
+    uniswap_path: Option<String>,
+    pancakeswap_path: Option<String>,
+    algebra_path: Option<String>,
+    csv_sources: Vec<String>,
+    chain_aliases: ChainAliases,
+    chain_filter: ChainFilter,
+    protocol_filter: ProtocolFilter,
+    include_only_complete_chains: bool,
+    address_case: AddressCase,
+    balancer_options: balancer::ParseOptions,
+    workers: Option<NonZeroUsize>,
+    code_provider: Option<Arc<dyn CodeProvider + Send>>,
+    transforms: Vec<Arc<dyn Transform>>,
+}
+
+impl RegistryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Balancer repo checkout or `.tar.gz` of its `addresses/` folder
+    pub fn balancer_path(mut self, path: impl Into<String>) -> Self {
+        self.balancer_path = Some(path.into());
+        self
+    }
+
+    /// Further balancer repos, merged into the main one before the latest-wins selection
+    pub fn balancer_extra_paths<S: Into<String>>(
+        mut self,
+        paths: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.balancer_extra_paths = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Parse the balancer repo as of this git ref, checked out in a temporary worktree
+    pub fn balancer_ref(mut self, git_ref: impl Into<String>) -> Self {
+        self.balancer_ref = Some(git_ref.into());
+        self
+    }
+
+    /// Merge balancer v2 and v3 into a single `balancer` protocol
+    pub fn balancer_merge(mut self, merge: bool) -> Self {
+        self.balancer_merge = merge;
+        self
+    }
+
+    pub fn uniswap_path(mut self, path: impl Into<String>) -> Self {
+        self.uniswap_path = Some(path.into());
+        self
+    }
+
+    pub fn pancakeswap_path(mut self, path: impl Into<String>) -> Self {
+        self.pancakeswap_path = Some(path.into());
+        self
+    }
+
+    /// Folder holding a sub-folder per Algebra DEX, see [`algebra::ALGEBRA_DEXES`]
+    pub fn algebra_path(mut self, path: impl Into<String>) -> Self {
+        self.algebra_path = Some(path.into());
+        self
+    }
+
+    /// CSV files of further deployments, failing the run when they contradict the sources
+    pub fn csv_sources<S: Into<String>>(mut self, paths: impl IntoIterator<Item = S>) -> Self {
+        self.csv_sources = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Folds aliased chains into their canonical chain before the chain filters apply
+    pub fn chain_aliases(mut self, chain_aliases: ChainAliases) -> Self {
+        self.chain_aliases = chain_aliases;
+        self
+    }
+
+    /// Only keep these chains, every chain is kept when never called
+    pub fn chains(mut self, chain_ids: impl IntoIterator<Item = ChainId>) -> Self {
+        self.chain_filter.chains = Some(chain_ids.into_iter().collect());
+        self
+    }
+
+    /// Drop these chains, even when selected by [`Self::chains`]
+    pub fn exclude_chains(mut self, chain_ids: impl IntoIterator<Item = ChainId>) -> Self {
+        self.chain_filter.exclude_chains = chain_ids.into_iter().collect();
+        self
+    }
+
+    /// Only keep these protocols, every protocol is kept when never called
+    pub fn protocols<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.protocol_filter.protocols = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Drop these protocols, even when selected by [`Self::protocols`]
+    pub fn exclude_protocols<S: Into<String>>(
+        mut self,
+        names: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.protocol_filter.exclude_protocols = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Drop the chains missing a required contract instead of failing on them
+    pub fn include_only_complete_chains(mut self, include_only_complete_chains: bool) -> Self {
+        self.include_only_complete_chains = include_only_complete_chains;
+        self
+    }
+
+    pub fn address_case(mut self, address_case: AddressCase) -> Self {
+        self.address_case = address_case;
+        self
+    }
+
+    /// What to do with balancer's `<Name>_deprecated` contracts
+    pub fn deprecated_contracts(mut self, deprecated_contracts: DeprecatedContracts) -> Self {
+        self.balancer_options.deprecated_contracts = deprecated_contracts;
+        self
+    }
+
+    /// Also take contracts from balancer's `SCRIPT` deployments
+    pub fn include_script_deployments(mut self, include: bool) -> Self {
+        self.balancer_options.include_script_deployments = include;
+        self
+    }
+
+    /// Threads parsing the balancer repos run on, rayon's default when never called
+    pub fn workers(mut self, workers: NonZeroUsize) -> Self {
+        self.workers = Some(workers);
+        self
+    }
+
+    /// Bytecode source to recognize uniswap contracts by the selectors they expose
+    pub fn code_provider(mut self, code_provider: impl CodeProvider + Send + 'static) -> Self {
+        self.code_provider = Some(Arc::new(code_provider));
+        self
+    }
+
+    /// Adds a transform, applied after the built-in ones in the order of registration
+    pub fn transform(mut self, transform: impl Transform + 'static) -> Self {
+        self.transforms.push(Arc::new(transform));
+        self
+    }
+
+    pub fn run(&self) -> Result<Registry, BuildError> {
+        self.run_with_warnings(&mut Vec::new())
+    }
+
+    /// Parses the configured sources, normalizes their addresses and applies the filters
+    pub fn run_with_warnings(&self, warnings: &mut Vec<Warning>) -> Result<Registry, BuildError> {
+        self.run_with_progress(warnings, &mut |_| {})
+    }
+
+    /// Like [`Self::run_with_warnings`], reporting each parsed source to `on_progress`
+    pub fn run_with_progress(
+        &self,
+        warnings: &mut Vec<Warning>,
+        on_progress: &mut dyn FnMut(ProgressEvent),
+    ) -> Result<Registry, BuildError> {
+        let mut protocols = Vec::new();
+        if let Some(path) = &self.balancer_path {
+            protocols.extend(self.parse_balancer(path, &self.balancer_options, warnings)?);
+            on_progress(ProgressEvent::SourceParsed { source: "balancer" });
+        }
+        if let Some(path) = &self.uniswap_path {
+            protocols.extend(self.parse_uniswap(path, warnings)?);
+            on_progress(ProgressEvent::SourceParsed { source: "uniswap" });
+        }
+        if let Some(path) = &self.pancakeswap_path {
+            let mut protocol_deployments = pancakeswap::parse(path)?;
+            protocol_deployments.source = Some(git::source_info(path, None));
+            protocols.push(protocol_deployments);
+            on_progress(ProgressEvent::SourceParsed {
+                source: "pancakeswap",
+            });
+        }
+        if let Some(path) = &self.algebra_path {
+            protocols.extend(algebra::parse_all(path)?);
+            on_progress(ProgressEvent::SourceParsed { source: "algebra" });
+        }
+
+        let mut registry = Registry::from_iter(protocols);
+        for path in &self.csv_sources {
+            registry.merge(
+                Registry::from_iter(csv_source::parse(path)?),
+                ConflictPolicy::Error,
+            )?;
+        }
+        if !self.csv_sources.is_empty() {
+            on_progress(ProgressEvent::SourceParsed { source: "csv" });
+        }
+
+        Ok(Registry::from_iter(
+            self.finish(registry.into_protocols(), warnings)?,
+        ))
+    }
+
+    /// Parses the balancer repo with the extra repos, as of the configured ref if any,
+    /// recording the source on every version's protocol
+    fn parse_balancer(
+        &self,
+        path: &str,
+        options: &balancer::ParseOptions,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<Vec<ProtocolDeployments>, BuildError> {
+        let extra_paths: Vec<&str> = self
+            .balancer_extra_paths
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let mut thread_pool = rayon::ThreadPoolBuilder::new();
+        if let Some(workers) = self.workers {
+            thread_pool = thread_pool.num_threads(workers.get());
+        }
+        let thread_pool = thread_pool.build()?;
+        let mut parse_versions = |paths: &[&str]| {
+            thread_pool.install(|| balancer::parse_versions(paths, options, warnings))
+        };
+
+        let mut protocols = match &self.balancer_ref {
+            Some(git_ref) => {
+                let worktree_path = std::env::temp_dir()
+                    .join(format!("evm-dex-index-balancer-{}", std::process::id()));
+                git::add_worktree(path, git_ref, &worktree_path)
+                    .map_err(BuildError::BalancerRef)?;
+                let worktree = worktree_path.to_string_lossy();
+                let paths = [&[worktree.as_ref()], extra_paths.as_slice()].concat();
+                let parsed = parse_versions(&paths);
+                git::remove_worktree(path, &worktree_path).map_err(BuildError::BalancerRef)?;
+
+                let mut protocols = parsed?;
+                for protocol_deployments in &mut protocols {
+                    protocol_deployments.source_ref = Some(git_ref.clone());
+                }
+                protocols
+            }
+            None => {
+                let paths = [&[path], extra_paths.as_slice()].concat();
+                parse_versions(&paths)?
+            }
+        };
+
+        let source = git::source_info(path, self.balancer_ref.as_deref());
+        for protocol_deployments in &mut protocols {
+            protocol_deployments.source = Some(source.clone());
+        }
+
+        if !self.balancer_merge {
+            return Ok(protocols);
+        }
+        let mut versions = protocols.into_iter();
+        let v2_deployments = versions.next().expect("v2 is always parsed");
+        let v3_deployments = versions.next().expect("v3 is always parsed");
+        // Versions newer than v3 stay their own protocols
+        Ok(
+            std::iter::once(balancer::merge_versions(v2_deployments, v3_deployments))
+                .chain(versions)
+                .collect(),
+        )
+    }
+
+    fn parse_uniswap(
+        &self,
+        path: &str,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<Vec<ProtocolDeployments>, BuildError> {
+        let mut protocols =
+            uniswap::parse_with_code(path, &self.uniswap_options(), warnings, self.code())?;
+
+        let source = git::source_info(path, None);
+        for protocol_deployments in &mut protocols {
+            protocol_deployments.source = Some(source.clone());
+        }
+
+        Ok(protocols)
+    }
+
+    /// Updates a registry this builder ran into after the given source files changed,
+    /// reparsing only what they affect: the chain of a balancer network file or of a uniswap
+    /// per-chain file, along with the chains aliased to the same chain. Any other change
+    /// reparses its whole source, and a changed CSV source reruns the build. The affected
+    /// chains are dropped from the registry before the reparsed ones are added, so chains
+    /// whose file was deleted, ignored or emptied go away.
+    pub fn reparse_changed(
+        &self,
+        registry: &mut Registry,
+        changed_paths: &[&str],
+        warnings: &mut Vec<Warning>,
+    ) -> Result<(), BuildError> {
+        let under = |source: &str, changed: &str| {
+            Path::new(changed)
+                .strip_prefix(source)
+                .ok()
+                .map(Path::to_path_buf)
+        };
+        let balancer_paths: Vec<&str> = self
+            .balancer_path
+            .iter()
+            .chain(&self.balancer_extra_paths)
+            .map(String::as_str)
+            .collect();
+
+        let mut balancer_networks = BTreeSet::new();
+        let mut balancer_all = false;
+        let mut uniswap_chains = BTreeSet::new();
+        let mut uniswap_all = false;
+        let mut pancakeswap = false;
+        let mut algebra = false;
+        for changed in changed_paths {
+            if self.csv_sources.iter().any(|path| path == changed) {
+                *registry = self.run_with_warnings(warnings)?;
+                return Ok(());
+            }
+
+            if let Some(relative) = balancer_paths.iter().find_map(|path| under(path, changed)) {
+                match balancer_network(&relative) {
+                    Some(network) => {
+                        balancer_networks.insert(network);
+                    }
+                    None => balancer_all = true,
+                }
+            } else if let Some(relative) = self
+                .uniswap_path
+                .as_deref()
+                .and_then(|path| under(path, changed))
+            {
+                let chain_id = (relative.components().count() == 1
+                    && source_files::is_source_file(&relative))
+                .then(|| source_files::chain_id_of(&relative))
+                .flatten();
+                match chain_id {
+                    Some(chain_id) => {
+                        uniswap_chains.insert(chain_id);
+                    }
+                    None => uniswap_all = true,
+                }
+            } else if self
+                .pancakeswap_path
+                .as_deref()
+                .and_then(|path| under(path, changed))
+                .is_some()
+            {
+                pancakeswap = true;
+            } else if self
+                .algebra_path
+                .as_deref()
+                .and_then(|path| under(path, changed))
+                .is_some()
+            {
+                algebra = true;
+            }
+        }
+
+        if let Some(path) = &self.balancer_path
+            && (balancer_all || !balancer_networks.is_empty())
+        {
+            self.reparse_balancer(
+                path,
+                &balancer_paths,
+                balancer_all,
+                balancer_networks,
+                registry,
+                warnings,
+            )?;
+        }
+
+        if let Some(path) = &self.uniswap_path
+            && (uniswap_all || !uniswap_chains.is_empty())
+        {
+            self.reparse_uniswap(path, uniswap_all, uniswap_chains, registry, warnings)?;
+        }
+
+        if let Some(path) = &self.pancakeswap_path
+            && pancakeswap
+        {
+            let mut protocol_deployments = pancakeswap::parse(path)?;
+            protocol_deployments.source = Some(git::source_info(path, None));
+            let names = [pancakeswap::INFINITY_PROTOCOL_NAME];
+            self.replace(registry, &names, None, vec![protocol_deployments], warnings)?;
+        }
+
+        if let Some(path) = &self.algebra_path
+            && algebra
+        {
+            let protocols = algebra::parse_all(path)?;
+            let names: Vec<_> = algebra::ALGEBRA_DEXES
+                .iter()
+                .map(|dex| dex.protocol_name)
+                .collect();
+            self.replace(registry, &names, None, protocols, warnings)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reparses the changed balancer networks, or every network when `all`
+    fn reparse_balancer(
+        &self,
+        path: &str,
+        balancer_paths: &[&str],
+        all: bool,
+        networks: BTreeSet<String>,
+        registry: &mut Registry,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<(), BuildError> {
+        let network_chain_ids = balancer::network_chain_ids(balancer_paths)?;
+        // A network missing from `.supported-networks.json` can't be traced to its chain
+        let all = all
+            || networks
+                .iter()
+                .any(|network| !network_chain_ids.contains_key(network));
+
+        let affected = (!all).then(|| {
+            self.alias_group(
+                networks
+                    .iter()
+                    .map(|network| network_chain_ids[network])
+                    .collect(),
+            )
+        });
+        let options = balancer::ParseOptions {
+            networks: affected.as_ref().map(|affected| {
+                network_chain_ids
+                    .iter()
+                    .filter(|(_, chain_id)| affected.contains(chain_id))
+                    .map(|(network, _)| network.clone())
+                    .collect()
+            }),
+            ..self.balancer_options.clone()
+        };
+
+        let protocols = self.parse_balancer(path, &options, warnings)?;
+        let names: Vec<String> = protocols
+            .iter()
+            .map(|protocol_deployments| protocol_deployments.protocol_name.clone())
+            .collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let affected = affected.map(|affected| self.canonical_chains(&affected));
+        self.replace(registry, &names, affected.as_ref(), protocols, warnings)
+    }
+
+    /// Reparses the per-chain files of the changed uniswap chains, or the whole source
+    /// when `all`. Files deleted or listed in `.deployignore` leave their chain empty.
+    fn reparse_uniswap(
+        &self,
+        path: &str,
+        all: bool,
+        chain_ids: BTreeSet<ChainId>,
+        registry: &mut Registry,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<(), BuildError> {
+        let names: Vec<_> = uniswap::protocol_names().collect();
+        if all {
+            let protocols = self.parse_uniswap(path, warnings)?;
+            return self.replace(registry, &names, None, protocols, warnings);
+        }
+
+        let affected = self.alias_group(chain_ids);
+        let ignore_list = IgnoreList::read(path).map_err(uniswap::ParseError::from)?;
+        let source = git::source_info(path, None);
+        let mut protocols = Vec::new();
+        for chain_id in &affected {
+            let file_name = format!("{}.json", chain_id);
+            let file = Path::new(path).join(&file_name);
+            if !file.exists() || ignore_list.is_ignored(&file_name) {
+                continue;
+            }
+
+            for mut protocol_deployments in self.parse_uniswap_file(&file, warnings)? {
+                protocol_deployments.source = Some(source.clone());
+                protocols.push(protocol_deployments);
+            }
+        }
+
+        let affected = self.canonical_chains(&affected);
+        self.replace(registry, &names, Some(&affected), protocols, warnings)
+    }
+
+    /// The chains, along with every chain aliased to the same chain as one of them
+    fn alias_group(&self, chain_ids: BTreeSet<ChainId>) -> BTreeSet<ChainId> {
+        let canonical = self.canonical_chains(&chain_ids);
+        let aliased = self
+            .chain_aliases
+            .iter()
+            .filter(|(_, to)| canonical.contains(to))
+            .map(|(from, _)| *from);
+
+        chain_ids
+            .into_iter()
+            .chain(canonical.iter().copied())
+            .chain(aliased)
+            .collect()
+    }
+
+    /// The chains the given source chains end up as once aliases are folded
+    fn canonical_chains(&self, chain_ids: &BTreeSet<ChainId>) -> BTreeSet<ChainId> {
+        chain_ids
+            .iter()
+            .map(|chain_id| {
+                self.chain_aliases
+                    .get(chain_id)
+                    .copied()
+                    .unwrap_or(*chain_id)
+            })
+            .collect()
+    }
+
+    /// Swaps what a source reparsed into the registry: the `chain_ids` of `protocol_names`,
+    /// or the whole protocols when `None`. Reparsed chains come with all their per-chain
+    /// details.
+    fn replace(
+        &self,
+        registry: &mut Registry,
+        protocol_names: &[&str],
+        chain_ids: Option<&BTreeSet<ChainId>>,
+        protocols: Vec<ProtocolDeployments>,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<(), BuildError> {
+        for protocol_name in protocol_names {
+            match chain_ids {
+                None => {
+                    registry.protocols.remove(*protocol_name);
+                }
+                Some(chain_ids) => {
+                    if let Some(existing) = registry.protocols.get_mut(*protocol_name) {
+                        existing.retain_chains(|chain_id| !chain_ids.contains(&chain_id));
+                    }
+                }
+            }
+        }
+
+        for reparsed in self.finish(protocols, warnings)? {
+            match registry.protocols.get_mut(&reparsed.protocol_name) {
+                Some(existing) => existing.extend_chains(reparsed),
+                None => registry.insert(reparsed),
+            }
+        }
+        registry
+            .protocols
+            .retain(|_, protocol_deployments| !protocol_deployments.chains.is_empty());
+
+        Ok(())
+    }
+
+    fn parse_uniswap_file(
+        &self,
+        file: &Path,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<Vec<ProtocolDeployments>, BuildError> {
+        Ok(uniswap::parse_file(
+            &file.to_string_lossy(),
+            &self.uniswap_options(),
+            warnings,
+            self.code(),
+        )?)
+    }
+
+    fn uniswap_options(&self) -> uniswap::ParseOptions {
+        uniswap::ParseOptions {
+            drop_incomplete_chains: self.include_only_complete_chains,
+        }
+    }
+
+    fn code(&self) -> Option<&dyn CodeProvider> {
+        self.code_provider
+            .as_deref()
+            .map(|provider| provider as &dyn CodeProvider)
+    }
+
+    /// Applies the filters, address normalization and transforms to parsed protocols
+    fn finish(
+        &self,
+        mut protocols: Vec<ProtocolDeployments>,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<Vec<ProtocolDeployments>, BuildError> {
+        protocols.retain(|protocol_deployments| {
+            self.protocol_filter
+                .is_selected(&protocol_deployments.protocol_name)
+        });
+        for protocol_deployments in &mut protocols {
+            warnings.extend(normalize::fold_chain_aliases(
+                protocol_deployments,
+                &self.chain_aliases,
+            ));
+            self.chain_filter.apply(protocol_deployments);
+            if self.include_only_complete_chains {
+                CompleteChains.apply(protocol_deployments);
+            }
+            warnings.extend(normalize::addresses(protocol_deployments)?);
+            self.address_case.apply(protocol_deployments);
+            for transform in &self.transforms {
+                transform.apply(protocol_deployments);
+            }
+        }
+        protocols.retain(|protocol_deployments| !protocol_deployments.chains.is_empty());
+
+        Ok(protocols)
+    }
+}
+
+/// Network of a balancer file, relative to the repo: `addresses/<network>.json` or a
+/// fragment `addresses/<network>/<file>.json`
+/// Sets the `name` entry of a chain's per-contract map to the one coming with the contract.
+/// Without one, a replaced contract loses its entry, which described the old address.
+fn take_entry<T>(
+    entries: &mut HashMap<ChainId, BTreeMap<ContractName, T>>,
+    incoming: &mut Option<BTreeMap<ContractName, T>>,
+    chain_id: ChainId,
+    name: &str,
+    replaced: bool,
+) {
+    match incoming.as_mut().and_then(|incoming| incoming.remove(name)) {
+        Some(entry) => {
+            entries
+                .entry(chain_id)
+                .or_default()
+                .insert(name.to_string(), entry);
+        }
+        None if replaced => {
+            if let Some(chain_entries) = entries.get_mut(&chain_id) {
+                chain_entries.remove(name);
+                if chain_entries.is_empty() {
+                    entries.remove(&chain_id);
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn balancer_network(relative: &Path) -> Option<String> {
+    let mut components = relative.components().map(|component| component.as_os_str());
+    if components.next()? != "addresses" {
+        return None;
+    }
+
+    let first = Path::new(components.next()?);
+    let network = match components.next() {
+        Some(_) => first.to_str()?,
+        None if first
+            .extension()
+            .is_some_and(|extension| extension == "json") =>
+        {
+            first.file_stem()?.to_str()?
+        }
+        None => return None,
+    };
+    // Hidden files like `.supported-networks.json` concern every network
+    if components.next().is_some() || network.starts_with('.') {
+        return None;
+    }
+
+    Some(network.to_string())
+}
+
+impl FromIterator<ProtocolDeployments> for Registry {
+    fn from_iter<I: IntoIterator<Item = ProtocolDeployments>>(iter: I) -> Self {
+        let mut registry = Registry::new();
+        for protocol_deployments in iter {
+            registry.insert(protocol_deployments);
+        }
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::{
+        diff::AddressChange,
+        transform::AddressCase,
+        types::{ContractMetadata, SourceInfo},
+    };
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn registry(protocol_name: &str, chain_id: ChainId, contracts: &[(&str, &str)]) -> Registry {
+        let contracts: ChainContracts = contracts
+            .iter()
+            .map(|(name, address)| (name.to_string(), address.to_string()))
+            .collect();
+
+        Registry::from_iter([ProtocolDeployments::new(
+            protocol_name,
+            HashMap::from([(chain_id, contracts)]),
+        )])
+    }
+
+    fn overlapping() -> (Registry, Registry) {
+        (
+            registry("permit2", 1, &[("Permit2", "0x01")]),
+            registry("permit2", 1, &[("Permit2", "0x02")]),
+        )
+    }
+
+    #[test]
+    fn test_conflicts_ignore_checksum_case() {
+        let checksummed = registry(
+            "balancer-v2",
+            1,
+            &[("Vault", "0xBA12222222228d8Ba445958a75a0704d566BF2C8")],
+        );
+        let lowercase = registry(
+            "balancer-v2",
+            1,
+            &[("Vault", "0xba12222222228d8ba445958a75a0704d566bf2c8")],
+        );
+        let moved = registry(
+            "balancer-v2",
+            1,
+            &[("Vault", "0xBA12222222228d8Ba445958a75a0704d566BF2C9")],
+        );
+
+        assert!(checksummed.conflicts(&lowercase).is_empty());
+        assert_eq!(
+            checksummed.conflicts(&moved),
+            vec![MergeError::Conflict {
+                protocol_name: "balancer-v2".to_string(),
+                chain_id: 1,
+                contract_name: "Vault".to_string(),
+                existing: "0xBA12222222228d8Ba445958a75a0704d566BF2C8".to_string(),
+                incoming: "0xBA12222222228d8Ba445958a75a0704d566BF2C9".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_merge_non_overlapping() {
+        let mut merged = registry("permit2", 1, &[("Permit2", "0x01")]);
+        merged
+            .merge(
+                registry("permit2", 10, &[("Permit2", "0x01")]),
+                ConflictPolicy::Error,
+            )
+            .unwrap();
+        merged
+            .merge(
+                registry("uniswap-v2", 1, &[("UniswapV2Factory", "0x03")]),
+                ConflictPolicy::Error,
+            )
+            .unwrap();
+
+        let permit2 = merged.get("permit2").unwrap();
+        assert_eq!(permit2.chains.len(), 2);
+        assert_eq!(merged.protocols().count(), 2);
+    }
+
+    #[test]
+    fn test_merge_same_address_is_not_a_conflict() {
+        let mut merged = registry("permit2", 1, &[("Permit2", "0xAB")]);
+
+        let result = merged.merge(
+            registry("permit2", 1, &[("Permit2", "0xab")]),
+            ConflictPolicy::Error,
+        );
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn test_merge_conflict_errors() {
+        let (mut merged, other) = overlapping();
+
+        let result = merged.merge(other, ConflictPolicy::Error);
+
+        assert!(matches!(
+            result,
+            Err(MergeError::Conflict { chain_id: 1, ref existing, ref incoming, .. })
+                if existing == "0x01" && incoming == "0x02"
+        ));
+        assert_eq!(merged.get("permit2").unwrap().chains[&1]["Permit2"], "0x01");
+    }
+
+    #[test]
+    fn test_merge_conflict_keeps_self() {
+        let (mut merged, other) = overlapping();
+
+        merged.merge(other, ConflictPolicy::KeepSelf).unwrap();
+
+        assert_eq!(merged.get("permit2").unwrap().chains[&1]["Permit2"], "0x01");
+    }
+
+    #[test]
+    fn test_merge_conflict_keeps_other() {
+        let (mut merged, other) = overlapping();
+
+        merged.merge(other, ConflictPolicy::KeepOther).unwrap();
+
+        assert_eq!(merged.get("permit2").unwrap().chains[&1]["Permit2"], "0x02");
+    }
+
+    #[test]
+    fn test_merge_keeping_other_replaces_what_describes_the_old_address() {
+        let mut merged = registry(
+            "balancer-v2",
+            1,
+            &[("Vault", "0x01"), ("BalancerQueries", "0x03")],
+        );
+        let existing = merged.protocols.get_mut("balancer-v2").unwrap();
+        let old_metadata = ContractMetadata {
+            version: Some("v2".to_string()),
+            block_created: Some(100),
+            ..Default::default()
+        };
+        existing.metadata.insert(
+            1,
+            BTreeMap::from([
+                ("Vault".to_string(), old_metadata.clone()),
+                ("BalancerQueries".to_string(), old_metadata.clone()),
+            ]),
+        );
+        existing.signatures.insert(
+            1,
+            BTreeMap::from([("Vault".to_string(), "20210418-vault".to_string())]),
+        );
+        existing.newest_deployments.insert(1, date(2021, 4, 18));
+
+        let mut other = registry(
+            "balancer-v2",
+            1,
+            &[("Vault", "0x02"), ("BalancerQueries", "0x04")],
+        );
+        let incoming = other.protocols.get_mut("balancer-v2").unwrap();
+        let new_metadata = ContractMetadata {
+            block_created: Some(200),
+            ..Default::default()
+        };
+        incoming.metadata.insert(
+            1,
+            BTreeMap::from([("BalancerQueries".to_string(), new_metadata.clone())]),
+        );
+        incoming
+            .candidates
+            .insert(1, BTreeMap::from([("Vault".to_string(), 2)]));
+        incoming.newest_deployments.insert(1, date(2024, 1, 1));
+        incoming.source = Some(SourceInfo {
+            location: "mirror".to_string(),
+            commit: None,
+        });
+
+        merged.merge(other, ConflictPolicy::KeepOther).unwrap();
+
+        let balancer = merged.get("balancer-v2").unwrap();
+        assert_eq!(balancer.chains[&1]["Vault"], "0x02");
+        // The old Vault's metadata and signature don't describe 0x02
+        assert_eq!(
+            balancer.metadata[&1],
+            BTreeMap::from([("BalancerQueries".to_string(), new_metadata)])
+        );
+        assert!(!balancer.signatures.contains_key(&1));
+        assert_eq!(balancer.candidates[&1]["Vault"], 2);
+        assert_eq!(balancer.newest_deployments[&1], date(2024, 1, 1));
+        assert_eq!(balancer.source.as_ref().unwrap().location, "mirror");
+    }
+
+    #[test]
+    fn test_iter_all_is_flat_and_sorted() {
+        let registry: Registry =
+            crate::uniswap::parse("tests/fixtures/uniswap/deployments", &mut Vec::new())
+                .unwrap()
+                .into_iter()
+                .collect();
+        let tuples: Vec<_> = registry.iter_all().collect();
+
+        // Every assigned contract of the 2 fixture chains
+        assert_eq!(tuples.len(), 40);
+        assert!(tuples.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(tuples.contains(&(
+            "permit2",
+            8453,
+            "Permit2",
+            "0x000000000022D473030F116dDEE9F6B43aC78BA3"
+        )));
+    }
+
+    #[test]
+    fn test_builder_filters_and_lowercases() {
+        let registry = Registry::builder()
+            .balancer_path("tests/fixtures/balancer")
+            .uniswap_path("tests/fixtures/uniswap/deployments")
+            .chains([1])
+            .exclude_protocols(["balancer-v3"])
+            .address_case(AddressCase::Lower)
+            .run()
+            .unwrap();
+
+        assert!(registry.get("balancer-v2").is_some());
+        assert!(registry.get("balancer-v3").is_none());
+        assert_eq!(
+            registry.get("balancer-v2").unwrap().chains[&1]["Vault"],
+            "0xba12222222228d8ba445958a75a0704d566bf2c8"
+        );
+        for (_, chain_id, _, address) in registry.iter_all() {
+            assert_eq!(chain_id, 1);
+            assert_eq!(address, address.to_ascii_lowercase());
+        }
+    }
+
+    #[test]
+    fn test_builder_applies_custom_transform() {
+        let registry = Registry::builder()
+            .uniswap_path("tests/fixtures/uniswap/deployments")
+            .protocols(["uniswap-v3"])
+            .transform(|protocol_deployments: &mut ProtocolDeployments| {
+                for contracts in protocol_deployments.chains.values_mut() {
+                    if let Some(address) = contracts.remove("UniswapV3Factory") {
+                        contracts.insert("Factory".to_string(), address);
+                    }
+                }
+            })
+            .run()
+            .unwrap();
+
+        let chains = &registry.get("uniswap-v3").unwrap().chains;
+        assert!(chains[&1].contains_key("Factory"));
+        assert!(!chains[&1].contains_key("UniswapV3Factory"));
+    }
+
+    #[test]
+    fn test_builder_merges_csv_sources_and_folds_chain_aliases() {
+        let registry = Registry::builder()
+            .csv_sources(["tests/fixtures/csv-source/custom.csv"])
+            .chain_aliases(ChainAliases::from([(8453, 1)]))
+            .protocols(["kyber-elastic"])
+            .run()
+            .unwrap();
+
+        let chains = &registry.get("kyber-elastic").unwrap().chains;
+        assert_eq!(chains.len(), 1);
+        assert_eq!(
+            chains[&1]["Factory"],
+            "0x0000000000000000000000000000000000000001"
+        );
+        assert!(registry.get("maverick-v2").is_none());
+    }
+
+    #[test]
+    fn test_reparse_changed_updates_only_that_chain() {
+        let repo =
+            std::env::temp_dir().join(format!("evm-dex-index-reparse-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&repo);
+        std::fs::create_dir_all(repo.join("addresses")).unwrap();
+        for file_name in [".supported-networks.json", "base.json", "mainnet.json"] {
+            std::fs::copy(
+                format!("tests/fixtures/balancer/addresses/{}", file_name),
+                repo.join("addresses").join(file_name),
+            )
+            .unwrap();
+        }
+        let builder = Registry::builder().balancer_path(repo.to_string_lossy());
+        let mut registry = builder.run().unwrap();
+
+        // Both networks move their vault, only mainnet is reported as changed
+        let moved = "0x00000000000000000000000000000000000000aa";
+        let mainnet = repo.join("addresses/mainnet.json");
+        for path in [&mainnet, &repo.join("addresses/base.json")] {
+            let contents = std::fs::read_to_string(path)
+                .unwrap()
+                .replace("0xBA12222222228d8Ba445958a75a0704d566BF2C8", moved);
+            std::fs::write(path, contents).unwrap();
+        }
+        builder
+            .reparse_changed(
+                &mut registry,
+                &[&mainnet.to_string_lossy()],
+                &mut Vec::new(),
+            )
+            .unwrap();
+
+        let v2 = registry.get("balancer-v2").unwrap();
+        assert_eq!(v2.chains[&1]["Vault"], moved);
+        assert_eq!(
+            v2.chains[&8453]["Vault"],
+            "0xBA12222222228d8Ba445958a75a0704d566BF2C8"
+        );
+        assert!(v2.signatures[&1].contains_key("Vault"));
+        assert!(v2.candidates[&1].contains_key("Vault"));
+
+        std::fs::remove_dir_all(repo).unwrap();
+    }
+
+    /// Copy of the uniswap fixture deployments to change files of
+    fn uniswap_copy(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "evm-dex-index-reparse-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for file_name in ["1.json", "8453.json"] {
+            std::fs::copy(
+                format!("tests/fixtures/uniswap/deployments/{}", file_name),
+                dir.join(file_name),
+            )
+            .unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_reparse_changed_drops_chain_of_deleted_file() {
+        let dir = uniswap_copy("deleted");
+        let builder = Registry::builder().uniswap_path(dir.to_string_lossy());
+        let mut registry = builder.run().unwrap();
+        assert!(
+            registry
+                .get("uniswap-v3")
+                .unwrap()
+                .chains
+                .contains_key(&8453)
+        );
+
+        let base = dir.join("8453.json");
+        std::fs::remove_file(&base).unwrap();
+        builder
+            .reparse_changed(&mut registry, &[&base.to_string_lossy()], &mut Vec::new())
+            .unwrap();
+
+        let chains = &registry.get("uniswap-v3").unwrap().chains;
+        assert!(!chains.contains_key(&8453));
+        assert!(chains.contains_key(&1));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_reparse_changed_drops_chain_emptied_by_change() {
+        let dir = uniswap_copy("emptied");
+        let builder = Registry::builder().uniswap_path(dir.to_string_lossy());
+        let mut registry = builder.run().unwrap();
+
+        let base = dir.join("8453.json");
+        std::fs::write(&base, r#"{ "chainId": "8453", "latest": {} }"#).unwrap();
+        builder
+            .reparse_changed(&mut registry, &[&base.to_string_lossy()], &mut Vec::new())
+            .unwrap();
+
+        let chains = &registry.get("uniswap-v3").unwrap().chains;
+        assert!(!chains.contains_key(&8453));
+        assert!(chains.contains_key(&1));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_diff_reports_added_contracts_and_chains() {
+        let old = registry("permit2", 1, &[("Permit2", "0x01")]);
+        let mut new = registry("permit2", 1, &[("Permit2", "0x01"), ("Helper", "0x02")]);
+        new.merge(
+            registry("permit2", 10, &[("Permit2", "0x01")]),
+            ConflictPolicy::Error,
+        )
+        .unwrap();
+
+        let diff = old.diff(&new);
+
+        assert_eq!(diff["permit2"][&1].added["Helper"], "0x02");
+        assert_eq!(diff["permit2"][&10].added["Permit2"], "0x01");
+        assert!(diff["permit2"][&1].removed.is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_removed_protocols() {
+        let mut old = registry("permit2", 1, &[("Permit2", "0x01")]);
+        old.insert(ProtocolDeployments::new(
+            "uniswap-v2",
+            HashMap::from([(
+                1,
+                ChainContracts::from([("Factory".to_string(), "0x03".to_string())]),
+            )]),
+        ));
+        let new = registry("permit2", 1, &[("Permit2", "0x01")]);
+
+        let diff = old.diff(&new);
+
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff["uniswap-v2"][&1].removed["Factory"], "0x03");
+    }
+
+    #[test]
+    fn test_diff_reports_moved_contracts_ignoring_case() {
+        let old = registry("permit2", 1, &[("Permit2", "0xAA"), ("Helper", "0x02")]);
+        let new = registry("permit2", 1, &[("Permit2", "0xaa"), ("Helper", "0x03")]);
+
+        let diff = old.diff(&new);
+
+        assert_eq!(
+            diff["permit2"][&1].changed,
+            BTreeMap::from([(
+                "Helper".to_string(),
+                AddressChange {
+                    from: "0x02".to_string(),
+                    to: "0x03".to_string(),
+                }
+            )])
+        );
+        assert!(old.diff(&old).is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::types::{ChainId, ContractAddress, ContractName, ProtocolDeployments};
+
+pub const SHARED_ADDRESSES_FILE_NAME: &str = "shared-addresses.json";
+
+/// Top-level keys some source variants nest a file's deployments under, read by every parser
+/// when the root of the file holds no deployments
+pub(crate) const WRAPPER_KEYS: [&str; 2] = ["deployment", "addresses"];
+
+/// Place a contract address appears at in the registry
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Occurrence {
+    pub protocol: String,
+    pub chain_id: ChainId,
+    pub contract_name: ContractName,
+}
+
+/// Every occurrence of each address, keyed by the lowercased address so checksummed and
+/// lowercase spellings count as one, ordered by protocol, chain and name
+pub fn reverse_index(
+    protocols: &[ProtocolDeployments],
+) -> BTreeMap<ContractAddress, Vec<Occurrence>> {
+    let mut index: BTreeMap<ContractAddress, Vec<Occurrence>> = BTreeMap::new();
+
+    for protocol_deployments in protocols {
+        for (chain_id, contracts) in &protocol_deployments.chains {
+            for (name, address) in contracts {
+                index
+                    .entry(address.to_lowercase())
+                    .or_default()
+                    .push(Occurrence {
+                        protocol: protocol_deployments.protocol_name.clone(),
+                        chain_id: *chain_id,
+                        contract_name: name.clone(),
+                    });
+            }
+        }
+    }
+
+    for occurrences in index.values_mut() {
+        occurrences.sort();
+    }
+
+    index
+}
+
+/// Addresses occurring more than once across the registry, with all their occurrences
+pub fn report(protocols: &[ProtocolDeployments]) -> BTreeMap<ContractAddress, Vec<Occurrence>> {
+    reverse_index(protocols)
+        .into_iter()
+        .filter(|(_, occurrences)| occurrences.len() > 1)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::types::ChainContracts;
+
+    #[test]
+    fn test_address_shared_across_chains_is_reported() {
+        let permit2 =
+            |address: &str| ChainContracts::from([("Permit2".to_string(), address.to_string())]);
+        let protocols = [
+            ProtocolDeployments::new(
+                "permit2",
+                HashMap::from([
+                    (1, permit2("0x000000000022D473030F116dDEE9F6B43aC78BA3")),
+                    (10, permit2("0x000000000022d473030f116ddee9f6b43ac78ba3")),
+                ]),
+            ),
+            ProtocolDeployments::new(
+                "uniswap-v2",
+                HashMap::from([(
+                    1,
+                    ChainContracts::from([("UniswapV2Factory".to_string(), "0x01".to_string())]),
+                )]),
+            ),
+        ];
+
+        let shared = report(&protocols);
+
+        assert_eq!(shared.len(), 1);
+        let occurrences = &shared["0x000000000022d473030f116ddee9f6b43ac78ba3"];
+        assert_eq!(
+            occurrences
+                .iter()
+                .map(|occurrence| occurrence.chain_id)
+                .collect::<Vec<_>>(),
+            [1, 10]
+        );
+        assert!(
+            occurrences
+                .iter()
+                .all(|occurrence| occurrence.protocol == "permit2")
+        );
+    }
+}
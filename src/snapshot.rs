@@ -0,0 +1,137 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+};
+
+use crate::{
+    matrix::Matrix,
+    types::{ChainId, ContractName},
+};
+
+/// Contracts present per protocol and chain, to compare later runs against
+pub type Snapshot = BTreeMap<String, BTreeMap<ChainId, BTreeSet<ContractName>>>;
+
+/// Present contracts of every protocol and chain of the matrix
+pub fn from_matrix(matrix: &Matrix) -> Snapshot {
+    matrix
+        .protocols
+        .iter()
+        .map(|(protocol_name, protocol_matrix)| {
+            let chains = protocol_matrix
+                .chains
+                .iter()
+                .map(|(chain_id, coverage)| {
+                    let present = protocol_matrix
+                        .contracts
+                        .iter()
+                        .zip(&coverage.present)
+                        .filter(|(_, present)| **present)
+                        .map(|(name, _)| name.clone())
+                        .collect();
+                    (*chain_id, present)
+                })
+                .collect();
+            (protocol_name.clone(), chains)
+        })
+        .collect()
+}
+
+pub fn write(path: &str, snapshot: &Snapshot) -> Result<(), std::io::Error> {
+    fs::write(path, serde_json::to_string_pretty(snapshot)?)
+}
+
+pub fn read(path: &str) -> Result<Snapshot, std::io::Error> {
+    Ok(serde_json::from_slice(&fs::read(path)?)?)
+}
+
+/// Every way `actual` diverges from `expected`, empty when they match
+pub fn differences(expected: &Snapshot, actual: &Snapshot) -> Vec<String> {
+    let mut differences = Vec::new();
+    let empty_chains = BTreeMap::new();
+    let empty_contracts = BTreeSet::new();
+
+    let protocol_names: BTreeSet<&String> = expected.keys().chain(actual.keys()).collect();
+    for protocol_name in protocol_names {
+        let (expected_chains, actual_chains) =
+            match (expected.get(protocol_name), actual.get(protocol_name)) {
+                (Some(_), None) => {
+                    differences.push(format!("protocol '{}' is missing", protocol_name));
+                    continue;
+                }
+                (None, Some(_)) => {
+                    differences.push(format!("protocol '{}' is new", protocol_name));
+                    continue;
+                }
+                (expected_chains, actual_chains) => (
+                    expected_chains.unwrap_or(&empty_chains),
+                    actual_chains.unwrap_or(&empty_chains),
+                ),
+            };
+
+        let chain_ids: BTreeSet<&ChainId> =
+            expected_chains.keys().chain(actual_chains.keys()).collect();
+        for chain_id in chain_ids {
+            let expected_contracts = expected_chains.get(chain_id).unwrap_or(&empty_contracts);
+            let actual_contracts = actual_chains.get(chain_id).unwrap_or(&empty_contracts);
+
+            let missing: Vec<_> = expected_contracts.difference(actual_contracts).collect();
+            let new: Vec<_> = actual_contracts.difference(expected_contracts).collect();
+            if !missing.is_empty() {
+                differences.push(format!(
+                    "{} on chain {}: missing {:?}",
+                    protocol_name, chain_id, missing
+                ));
+            }
+            if !new.is_empty() {
+                differences.push(format!(
+                    "{} on chain {}: new {:?}",
+                    protocol_name, chain_id, new
+                ));
+            }
+        }
+    }
+
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(entries: &[(&str, ChainId, &[&str])]) -> Snapshot {
+        let mut snapshot = Snapshot::new();
+        for (protocol_name, chain_id, contracts) in entries {
+            snapshot
+                .entry(protocol_name.to_string())
+                .or_default()
+                .insert(
+                    *chain_id,
+                    contracts.iter().map(|name| name.to_string()).collect(),
+                );
+        }
+        snapshot
+    }
+
+    #[test]
+    fn test_differences() {
+        let expected = snapshot(&[
+            ("uniswap-v2", 1, &["Factory", "Router"]),
+            ("uniswap-v2", 10, &["Factory"]),
+            ("permit2", 1, &["Permit2"]),
+        ]);
+        let actual = snapshot(&[
+            ("uniswap-v2", 1, &["Factory", "Quoter"]),
+            ("uniswap-v2", 10, &["Factory"]),
+        ]);
+
+        assert!(differences(&expected, &expected).is_empty());
+        assert_eq!(
+            differences(&expected, &actual),
+            [
+                "protocol 'permit2' is missing",
+                r#"uniswap-v2 on chain 1: missing ["Router"]"#,
+                r#"uniswap-v2 on chain 1: new ["Quoter"]"#,
+            ]
+        );
+    }
+}
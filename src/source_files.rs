@@ -0,0 +1,239 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+use serde::de::DeserializeOwned;
+use tar::Archive;
+use thiserror::Error;
+use tracing::debug;
+
+use crate::{
+    ignore::{IGNORE_FILE_NAME, IgnoreList},
+    types::ChainId,
+};
+
+#[derive(Debug, Error)]
+pub enum ReadError {
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Serde error in '{path}': {source}")]
+    SerdeError {
+        path: String,
+        source: serde_json::Error,
+    },
+}
+
+/// Whether the source at `path` is a gzipped tar archive, by extension
+pub fn is_tarball(path: &str) -> bool {
+    path.ends_with(".tar.gz") || path.ends_with(".tgz")
+}
+
+/// Chain id a `<chain_id>.json` file is named after
+pub fn chain_id_of(path: &Path) -> Option<ChainId> {
+    path.file_stem()?.to_str()?.parse().ok()
+}
+
+/// Reads every `*.json` file of a source folder, or every `*.json` member of a `.tar.gz` of
+/// one, that `accept` takes, ordered by path. Other files, companion `*.meta.json` files and
+/// files the folder's `.deployignore` lists are skipped.
+pub fn read_json_files<T: DeserializeOwned>(
+    path: &str,
+    accept: impl Fn(&Path) -> bool,
+) -> Result<Vec<(PathBuf, T)>, ReadError> {
+    let mut files = if is_tarball(path) {
+        read_tarball(path, &accept)?
+    } else {
+        read_dir(path, &accept)?
+    };
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(files)
+}
+
+fn read_dir<T: DeserializeOwned>(
+    path: &str,
+    accept: &impl Fn(&Path) -> bool,
+) -> Result<Vec<(PathBuf, T)>, ReadError> {
+    let ignore_list = IgnoreList::read(path)?;
+    let mut files = Vec::new();
+
+    for entry in std::fs::read_dir(path)? {
+        let file_path = entry?.path();
+        if !is_source_file(&file_path) || !accept(&file_path) {
+            continue;
+        }
+        if is_ignored(&ignore_list, &file_path) {
+            continue;
+        }
+
+        let contents =
+            serde_json::from_reader(BufReader::new(File::open(&file_path)?)).map_err(|source| {
+                ReadError::SerdeError {
+                    path: file_path.to_string_lossy().into_owned(),
+                    source,
+                }
+            })?;
+        files.push((file_path, contents));
+    }
+
+    Ok(files)
+}
+
+fn read_tarball<T: DeserializeOwned>(
+    path_to_tarball: &str,
+    accept: &impl Fn(&Path) -> bool,
+) -> Result<Vec<(PathBuf, T)>, ReadError> {
+    let file = File::open(path_to_tarball)?;
+    let mut archive = Archive::new(GzDecoder::new(BufReader::new(file)));
+    // A folder's `.deployignore` may come after its files in the archive, so members are
+    // only deserialized once every ignore list is known
+    let mut members = Vec::new();
+    let mut ignore_lists = HashMap::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let file_path = entry.path()?.into_owned();
+
+        if file_path
+            .file_name()
+            .is_some_and(|name| name == IGNORE_FILE_NAME)
+        {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)?;
+            let dir = file_path.parent().unwrap_or(Path::new("")).to_path_buf();
+            ignore_lists.insert(dir, IgnoreList::parse(&contents));
+        } else if is_source_file(&file_path) && accept(&file_path) {
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+            members.push((file_path, contents));
+        }
+    }
+
+    let mut files = Vec::new();
+    for (file_path, contents) in members {
+        let dir = file_path.parent().unwrap_or(Path::new(""));
+        if let Some(ignore_list) = ignore_lists.get(dir)
+            && is_ignored(ignore_list, &file_path)
+        {
+            continue;
+        }
+
+        let contents =
+            serde_json::from_slice(&contents).map_err(|source| ReadError::SerdeError {
+                path: format!("{}/{}", path_to_tarball, file_path.display()),
+                source,
+            })?;
+        files.push((file_path, contents));
+    }
+
+    Ok(files)
+}
+
+/// A `*.json` file that isn't a companion file
+pub(crate) fn is_source_file(path: &Path) -> bool {
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension == "json");
+
+    is_json && !is_companion_file(path)
+}
+
+/// A `<protocol>.meta.json` or `<protocol>.deprecated.json` lying next to the deployments
+fn is_companion_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(".meta.json") || name.ends_with(".deprecated.json"))
+}
+
+fn is_ignored(ignore_list: &IgnoreList, path: &Path) -> bool {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let ignored = ignore_list.is_ignored(&file_name);
+    if ignored {
+        debug!(path = %path.display(), "Skipping ignored deployments file");
+    }
+
+    ignored
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, fs};
+
+    use super::*;
+
+    #[test]
+    fn test_read_json_files_skips_companion_and_ignored_files() {
+        let dir =
+            std::env::temp_dir().join(format!("evm-dex-index-source-files-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for file_name in [
+            "137.json",
+            "8453.json",
+            "1.json",
+            "uniswap-v3.meta.json",
+            "uniswap-v3.deprecated.json",
+        ] {
+            fs::write(dir.join(file_name), r#"{"Factory": "0x01"}"#).unwrap();
+        }
+        fs::write(dir.join("README.md"), "not json").unwrap();
+        fs::write(dir.join(".deployignore"), "8453.json\n").unwrap();
+
+        let files: Vec<(PathBuf, BTreeMap<String, String>)> =
+            read_json_files(&dir.to_string_lossy(), |_| true).unwrap();
+
+        let chain_ids: Vec<_> = files
+            .iter()
+            .map(|(path, _)| chain_id_of(path).unwrap())
+            .collect();
+        assert_eq!(chain_ids, vec![1, 137]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_json_files_applies_deployignore_in_tarball() {
+        let path = std::env::temp_dir().join(format!(
+            "evm-dex-index-source-files-{}.tar.gz",
+            std::process::id()
+        ));
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        ));
+        // The ignore list comes last, after the file it lists
+        for (member, contents) in [
+            ("deployments/1.json", r#"{"Factory": "0x01"}"#),
+            ("deployments/8453.json", r#"{"Factory": "0x02"}"#),
+            ("deployments/.deployignore", "8453.json\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, member, contents.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let files: Vec<(PathBuf, BTreeMap<String, String>)> =
+            read_json_files(&path.to_string_lossy(), |_| true).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, Path::new("deployments/1.json"));
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,151 @@
+use tracing::warn;
+
+use crate::types::{ChainId, ProtocolDeployments};
+
+/// Post-processing step applied to every parsed protocol before it is written,
+/// e.g. custom filtering, renaming or enrichment
+pub trait Transform: Send + Sync {
+    fn apply(&self, protocol_deployments: &mut ProtocolDeployments);
+}
+
+impl<F> Transform for F
+where
+    F: Fn(&mut ProtocolDeployments) + Send + Sync,
+{
+    fn apply(&self, protocol_deployments: &mut ProtocolDeployments) {
+        self(protocol_deployments)
+    }
+}
+
+/// Keeps the selected chains (every chain when `chains` is `None`) that aren't excluded
+#[derive(Debug, Clone, Default)]
+pub struct ChainFilter {
+    pub chains: Option<Vec<ChainId>>,
+    pub exclude_chains: Vec<ChainId>,
+}
+
+impl ChainFilter {
+    pub fn is_selected(&self, chain_id: ChainId) -> bool {
+        let allowed = self
+            .chains
+            .as_ref()
+            .is_none_or(|chains| chains.contains(&chain_id));
+
+        allowed && !self.exclude_chains.contains(&chain_id)
+    }
+}
+
+impl Transform for ChainFilter {
+    fn apply(&self, protocol_deployments: &mut ProtocolDeployments) {
+        protocol_deployments.retain_chains(|chain_id| self.is_selected(chain_id));
+    }
+}
+
+/// Keeps the selected protocols (every protocol when `protocols` is `None`) that aren't excluded
+#[derive(Debug, Clone, Default)]
+pub struct ProtocolFilter {
+    pub protocols: Option<Vec<String>>,
+    pub exclude_protocols: Vec<String>,
+}
+
+impl ProtocolFilter {
+    pub fn is_selected(&self, protocol_name: &str) -> bool {
+        let allowed = self
+            .protocols
+            .as_ref()
+            .is_none_or(|protocols| protocols.iter().any(|name| name == protocol_name));
+
+        allowed
+            && !self
+                .exclude_protocols
+                .iter()
+                .any(|name| name == protocol_name)
+    }
+}
+
+/// Drops, logging them, the chains missing a required contract of the protocol
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompleteChains;
+
+impl Transform for CompleteChains {
+    fn apply(&self, protocol_deployments: &mut ProtocolDeployments) {
+        let incomplete: Vec<ChainId> = protocol_deployments
+            .chains
+            .keys()
+            .copied()
+            .filter(|chain_id| {
+                protocol_deployments
+                    .completeness(*chain_id)
+                    .is_some_and(|completeness| completeness < 1.0)
+            })
+            .collect();
+
+        for chain_id in &incomplete {
+            warn!(
+                protocol = protocol_deployments.protocol_name,
+                chain_id, "Dropping chain missing required contracts"
+            );
+        }
+        protocol_deployments.retain_chains(|chain_id| !incomplete.contains(&chain_id));
+    }
+}
+
+/// How addresses are spelled in the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AddressCase {
+    /// As published by the source
+    #[default]
+    Source,
+    Lower,
+}
+
+impl Transform for AddressCase {
+    fn apply(&self, protocol_deployments: &mut ProtocolDeployments) {
+        if *self == AddressCase::Source {
+            return;
+        }
+
+        for contracts in protocol_deployments.chains.values_mut() {
+            for address in contracts.values_mut() {
+                address.make_ascii_lowercase();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::*;
+
+    #[test]
+    fn test_chain_filter_exclusion_wins() {
+        let mut protocol_deployments = ProtocolDeployments::new(
+            "uniswap-v3",
+            HashMap::from([(1, BTreeMap::new()), (10, BTreeMap::new())]),
+        );
+        let filter = ChainFilter {
+            chains: Some(vec![1, 10]),
+            exclude_chains: vec![10],
+        };
+
+        filter.apply(&mut protocol_deployments);
+
+        assert_eq!(protocol_deployments.chains.len(), 1);
+        assert!(protocol_deployments.chains.contains_key(&1));
+    }
+
+    #[test]
+    fn test_protocol_filter_exclusion_wins() {
+        let filter = ProtocolFilter {
+            protocols: Some(vec!["uniswap-v3".to_string(), "balancer-v2".to_string()]),
+            exclude_protocols: vec!["balancer-v2".to_string()],
+        };
+
+        assert!(filter.is_selected("uniswap-v3"));
+        assert!(!filter.is_selected("balancer-v2"));
+        assert!(!filter.is_selected("balancer-v3"));
+        assert!(ProtocolFilter::default().is_selected("balancer-v3"));
+    }
+}
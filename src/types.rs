@@ -1,11 +1,179 @@
 use std::collections::{BTreeMap, HashMap};
 
-use serde::Serialize;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ProtocolDeployments {
     pub protocol_name: String,
     pub chains: ChainDeployments,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<ChainId, ChainMetadata>,
+    /// Contracts every chain is expected to have, empty when the protocol has no such list
+    #[serde(skip)]
+    pub required_contracts: Vec<ContractName>,
+    /// Required contracts of chains with a minimal deployment, instead of `required_contracts`
+    #[serde(skip)]
+    pub chain_required_contracts: HashMap<ChainId, Vec<ContractName>>,
+    /// Number of candidate deployments each contract was chosen from, per chain.
+    /// Empty for sources without latest-wins selection.
+    #[serde(skip)]
+    pub candidates: HashMap<ChainId, ChainCandidates>,
+    /// Signature of the deployment each contract was taken from, per chain.
+    /// Empty for sources without named deployments.
+    #[serde(skip)]
+    pub signatures: HashMap<ChainId, ChainSignatures>,
+    /// Date of the newest deployment of each chain in the source, for sources with dated
+    /// deployments
+    #[serde(skip)]
+    pub newest_deployments: HashMap<ChainId, NaiveDate>,
+    /// Name the protocol was parsed under, when renamed since
+    #[serde(skip)]
+    pub renamed_from: Option<String>,
+    /// Git ref of the source repo the deployments were parsed from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_ref: Option<String>,
+    /// Repo and commit the deployments were parsed from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceInfo>,
+}
+
+impl ProtocolDeployments {
+    pub fn new(protocol_name: impl Into<String>, chains: ChainDeployments) -> Self {
+        Self {
+            protocol_name: protocol_name.into(),
+            chains,
+            metadata: HashMap::new(),
+            required_contracts: Vec::new(),
+            chain_required_contracts: HashMap::new(),
+            candidates: HashMap::new(),
+            signatures: HashMap::new(),
+            newest_deployments: HashMap::new(),
+            renamed_from: None,
+            source_ref: None,
+            source: None,
+        }
+    }
+
+    /// Drops the chains `keep` rejects, along with their per-chain details
+    pub fn retain_chains(&mut self, keep: impl Fn(ChainId) -> bool) {
+        self.chains.retain(|chain_id, _| keep(*chain_id));
+        self.metadata.retain(|chain_id, _| keep(*chain_id));
+        self.candidates.retain(|chain_id, _| keep(*chain_id));
+        self.signatures.retain(|chain_id, _| keep(*chain_id));
+        self.newest_deployments
+            .retain(|chain_id, _| keep(*chain_id));
+    }
+
+    /// Adds the chains of `other` along with their per-chain details, replacing the chains
+    /// both have
+    pub fn extend_chains(&mut self, other: ProtocolDeployments) {
+        self.chains.extend(other.chains);
+        self.metadata.extend(other.metadata);
+        self.chain_required_contracts
+            .extend(other.chain_required_contracts);
+        self.candidates.extend(other.candidates);
+        self.signatures.extend(other.signatures);
+        self.newest_deployments.extend(other.newest_deployments);
+    }
+
+    /// Date of the newest deployment across the chains, for sources with dated deployments
+    pub fn newest_deployment(&self) -> Option<NaiveDate> {
+        self.newest_deployments.values().max().copied()
+    }
+
+    /// Contracts the chain is expected to have, its override if any
+    pub fn required_contracts_of(&self, chain_id: ChainId) -> &[ContractName] {
+        self.chain_required_contracts
+            .get(&chain_id)
+            .unwrap_or(&self.required_contracts)
+    }
+
+    /// Required contracts absent from the chain, in the order they're required
+    pub fn missing_contracts(&self, chain_id: ChainId) -> Vec<&ContractName> {
+        let contracts = self.chains.get(&chain_id);
+        self.required_contracts_of(chain_id)
+            .iter()
+            .filter(|name| !contracts.is_some_and(|contracts| contracts.contains_key(*name)))
+            .collect()
+    }
+
+    /// Share of required contracts present on the chain, `None` without a required list
+    pub fn completeness(&self, chain_id: ChainId) -> Option<f64> {
+        let required_contracts = self.required_contracts_of(chain_id);
+        if required_contracts.is_empty() {
+            return None;
+        }
+
+        let contracts = self.chains.get(&chain_id)?;
+        let present = required_contracts
+            .iter()
+            .filter(|name| contracts.contains_key(*name))
+            .count();
+
+        Some(present as f64 / required_contracts.len() as f64)
+    }
+
+    /// Oldest periphery release among the chain's contracts, which bounds what the chain
+    /// is compatible with. `None` when no contract records one.
+    pub fn periphery_version(&self, chain_id: ChainId) -> Option<String> {
+        self.metadata
+            .get(&chain_id)?
+            .values()
+            .filter_map(|metadata| metadata.periphery_version.as_ref())
+            .min_by_key(|version| release_numbers(version))
+            .cloned()
+    }
+
+    /// Earliest block any of the chain's contracts was deployed at, where indexers syncing the
+    /// protocol's events can start. `None` when no contract records its block.
+    pub fn min_start_block(&self, chain_id: ChainId) -> Option<u64> {
+        self.metadata
+            .get(&chain_id)?
+            .values()
+            .filter_map(|metadata| metadata.block_created)
+            .min()
+    }
+
+    /// Contracts of a chain merged with their metadata, `None` if the chain is unknown
+    pub fn enriched_chain(&self, chain_id: ChainId) -> Option<EnrichedChainContracts> {
+        let contracts = self.chains.get(&chain_id)?;
+        let metadata = self.metadata.get(&chain_id);
+
+        let enriched = contracts
+            .iter()
+            .map(|(name, address)| {
+                let contract = EnrichedContract {
+                    address: address.clone(),
+                    metadata: metadata
+                        .and_then(|metadata| metadata.get(name))
+                        .cloned()
+                        .unwrap_or_default(),
+                };
+                (name.clone(), contract)
+            })
+            .collect();
+
+        Some(enriched)
+    }
+}
+
+/// Numbers of a release like `v1.10.2`, compared numerically rather than as text
+fn release_numbers(version: &str) -> Vec<u64> {
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|number| number.parse().ok())
+        .collect()
+}
+
+/// Where a protocol's source data came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceInfo {
+    /// URL of the source repo's `origin` remote, or the local path without one
+    pub location: String,
+    /// Commit checked out at parse time, `None` outside a git repo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
 }
 
 pub type ChainId = u64;
@@ -16,4 +184,63 @@ pub type ChainContracts = BTreeMap<ContractName, ContractAddress>;
 
 pub type ContractName = String;
 
+pub type ChainCandidates = BTreeMap<ContractName, usize>;
+
+pub type ChainSignatures = BTreeMap<ContractName, String>;
+
 pub type ContractAddress = String;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContractStatus {
+    Active,
+    Deprecated,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ContractStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub create2: Option<Create2>,
+    /// Protocol version the contract belongs to, for protocols combining several versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Vault version a balancer relayer pairs with, taken from its deployment, e.g. `v2`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relayer_version: Option<String>,
+    /// Release of the periphery repo the contract was deployed from, e.g. `v1.0.1`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub periphery_version: Option<String>,
+    /// Solidity compiler that produced the bytecode, e.g. `v0.8.24+commit.e11b9ed9`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiler_version: Option<String>,
+    /// Block the contract was deployed at, when the source records it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_created: Option<u64>,
+    /// Address of every labeled version, for contracts tracked across versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub versions: Option<BTreeMap<String, ContractAddress>>,
+}
+
+/// CREATE2 factory and salt the contract address can be recomputed from
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Create2 {
+    pub deployer: ContractAddress,
+    pub salt: String,
+}
+
+pub type ChainMetadata = BTreeMap<ContractName, ContractMetadata>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnrichedContract {
+    pub address: ContractAddress,
+    #[serde(flatten)]
+    pub metadata: ContractMetadata,
+}
+
+pub type EnrichedChainContracts = BTreeMap<ContractName, EnrichedContract>;
@@ -1,61 +1,285 @@
 use std::{
-    collections::{HashMap, HashSet},
-    fs::File,
-    io::BufReader,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    path::Path,
 };
 
-use serde::Deserialize;
+use serde::{
+    Deserialize, Deserializer,
+    de::{self, IgnoredAny, MapAccess, Visitor},
+};
 use thiserror::Error;
-use tracing::debug;
+use tracing::{debug, info, warn};
 
-use crate::types::{ChainContracts, ChainDeployments, ProtocolDeployments};
+use crate::{
+    bytecode::{self, CodeProvider},
+    meta, pattern, shared,
+    source_files::{self, ReadError},
+    types::{
+        ChainContracts, ChainDeployments, ChainId, ChainMetadata, ContractAddress,
+        ContractMetadata, ProtocolDeployments,
+    },
+    warnings::{Warning, WarningKind},
+};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 struct UniswapDeployment {
-    #[serde(rename = "chainId")]
     chain_id: String,
     latest: HashMap<String, ContractDeployment>,
 }
 
+/// Read from the root of the file, or from under a wrapper key when the root has no
+/// `chainId` and `latest`
+impl<'de> Deserialize<'de> for UniswapDeployment {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DeploymentVisitor;
+
+        impl<'de> Visitor<'de> for DeploymentVisitor {
+            type Value = UniswapDeployment;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an object with `chainId` and `latest`")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut chain_id = None;
+                let mut latest = None;
+                let mut nested = None;
+
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "chainId" => chain_id = Some(map.next_value()?),
+                        "latest" => latest = Some(map.next_value()?),
+                        key if shared::WRAPPER_KEYS.contains(&key) && nested.is_none() => {
+                            nested = Some(map.next_value::<UniswapDeployment>()?)
+                        }
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+
+                match (chain_id, latest, nested) {
+                    (Some(chain_id), Some(latest), _) => Ok(UniswapDeployment { chain_id, latest }),
+                    (None, None, Some(nested)) => Ok(nested),
+                    (None, _, _) => Err(de::Error::missing_field("chainId")),
+                    (_, None, _) => Err(de::Error::missing_field("latest")),
+                }
+            }
+        }
+
+        deserializer.deserialize_map(DeploymentVisitor)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct ContractDeployment {
-    address: String,
+    /// `None` for placeholders of planned deployments
+    #[serde(default, alias = "deployedTo", alias = "contractAddress")]
+    address: Option<String>,
+    #[serde(default, rename = "compilerVersion", alias = "solcVersion")]
+    compiler_version: Option<String>,
+    #[serde(
+        default,
+        rename = "blockCreated",
+        alias = "blockNumber",
+        alias = "startBlock"
+    )]
+    block_created: Option<u64>,
+    /// Release of the repo the contract was deployed from, e.g. `v1.0.1` of v4-periphery
+    #[serde(default)]
+    version: Option<String>,
 }
 
 type ProtocolName = &'static str;
 
 type ProtocolsDeployments = HashMap<ProtocolName, ChainDeployments>;
 
+type ProtocolsMetadata = HashMap<ProtocolName, HashMap<ChainId, ChainMetadata>>;
+
+/// Position in the protocol's `labeled` list, label and address of a versioned contract
+type LabeledAddress = (usize, &'static str, ContractAddress);
+
+/// Contracts and metadata of a single chain, per protocol
+struct AssignedChain {
+    contracts: HashMap<ProtocolName, ChainContracts>,
+    metadata: HashMap<ProtocolName, ChainMetadata>,
+}
+
 #[derive(Debug, Error)]
 pub enum ParseError {
     #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
 
-    #[error("Serde error: {0}")]
-    SerdeError(#[from] serde_json::Error),
+    #[error("Serde error in '{path}': {source}")]
+    SerdeError {
+        path: String,
+        source: serde_json::Error,
+    },
 
-    #[error("Missing contracts for protocol '{protocol_name}': {contracts:?}")]
+    #[error(
+        "Missing contracts for protocol '{protocol_name}'{}: {contracts:?}",
+        chain_id.map(|chain_id| format!(" on chain {}", chain_id)).unwrap_or_default()
+    )]
     MissingContracts {
         protocol_name: String,
+        /// The chain missing them, for a chain with its own required contracts
+        chain_id: Option<ChainId>,
         contracts: Vec<String>,
     },
 
-    #[error("Contract '{contract_name}' is defined in multiple protocols: {protocols:?}")]
+    #[error("Contracts defined in multiple protocols: {duplicates:?}")]
     DuplicateContracts {
-        contract_name: String,
-        protocols: Vec<String>,
+        /// Protocols defining each duplicated contract
+        duplicates: BTreeMap<String, Vec<String>>,
     },
 }
 
+impl From<ReadError> for ParseError {
+    fn from(err: ReadError) -> Self {
+        match err {
+            ReadError::IoError(err) => Self::IoError(err),
+            ReadError::SerdeError { path, source } => Self::SerdeError { path, source },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Leave out the chains missing a required contract instead of failing the parse
+    pub drop_incomplete_chains: bool,
+}
+
 struct ProtocolConfig {
     protocol_name: &'static str,
+    /// Contracts expected on every chain the protocol is deployed to
     contracts: &'static [&'static str],
+    /// Required contracts of chains with a minimal deployment, instead of `contracts`
+    chain_contracts: &'static [(ChainId, &'static [&'static str])],
+    /// Contracts assigned to the protocol that only exist on some chains
+    optional_contracts: &'static [&'static str],
+    /// Name patterns (`*` wildcard) of further contracts assigned to the protocol
+    patterns: &'static [&'static str],
+    /// Names or patterns vetoing assignment, even when an include rule matches
+    exclude: &'static [&'static str],
+    /// Fail the parse when a chain misses a contract it requires, warn otherwise
+    enforce_completeness: bool,
+    /// Exact source name → canonical name, applied only within this protocol.
+    /// The contract lists reference canonical names. When a chain has several source names
+    /// resolving to one canonical name, the canonical name itself is kept, otherwise the
+    /// first one listed here.
+    rename: &'static [(&'static str, &'static str)],
+    /// Versioned source name → (canonical name, label), oldest first. The canonical
+    /// contract keeps every label's address and points at the newest one present.
+    labeled: &'static [(&'static str, (&'static str, &'static str))],
+    /// Canonical name → function selector its bytecode exposes. With bytecode available,
+    /// an otherwise unassigned contract exposing it is recognized as the missing contract.
+    selectors: &'static [(&'static str, &'static str)],
+    /// Contracts released with the protocol's periphery, whose source `version` is recorded
+    /// as their `periphery_version`
+    periphery: &'static [&'static str],
+    /// Rank among configs claiming the same contract, the highest one gets it. Configs may
+    /// only share contracts when each of them has a distinct priority.
+    priority: Option<u8>,
+}
+
+impl ProtocolConfig {
+    /// Configs in the order they claim contracts: by descending priority, configs without
+    /// one last, in declaration order otherwise
+    fn by_priority(configs: &[ProtocolConfig]) -> Vec<&ProtocolConfig> {
+        let mut ordered: Vec<_> = configs.iter().collect();
+        ordered.sort_by_key(|config| std::cmp::Reverse(config.priority));
+        ordered
+    }
+
+    fn all_contracts(&self) -> impl Iterator<Item = &'static str> {
+        self.contracts
+            .iter()
+            .chain(self.optional_contracts)
+            .copied()
+    }
+
+    /// Contracts the chain is expected to have, its override if any
+    fn required_contracts_of(&self, chain_id: ChainId) -> &'static [&'static str] {
+        self.chain_contracts
+            .iter()
+            .find(|(id, _)| *id == chain_id)
+            .map_or(self.contracts, |(_, contracts)| contracts)
+    }
+
+    /// Sets the protocol-wide and per-chain required contracts of `deployments`
+    fn apply_required_contracts(&self, deployments: &mut ProtocolDeployments) {
+        let names = |contracts: &[&str]| contracts.iter().map(|s| s.to_string()).collect();
+        deployments.required_contracts = names(self.contracts);
+        deployments.chain_required_contracts = self
+            .chain_contracts
+            .iter()
+            .map(|(chain_id, contracts)| (*chain_id, names(contracts)))
+            .collect();
+    }
+
+    /// Position, canonical name and label of a versioned source contract
+    fn label(&self, source_name: &str) -> Option<(usize, &'static str, &'static str)> {
+        self.labeled
+            .iter()
+            .position(|(from, _)| *from == source_name)
+            .map(|index| {
+                let (_, (canonical_name, label)) = self.labeled[index];
+                (index, canonical_name, label)
+            })
+    }
+
+    /// Precedence of a source name among those resolving to the same canonical name: the
+    /// canonical name itself first, then renamed names in the order of `rename`
+    fn rename_rank(&self, source_name: &str) -> usize {
+        self.rename
+            .iter()
+            .position(|(from, _)| *from == source_name)
+            .map_or(0, |index| index + 1)
+    }
+
+    /// Canonical name of a source contract, if it belongs to this protocol
+    fn resolve(&self, source_name: &str) -> Option<String> {
+        let canonical_name = self
+            .rename
+            .iter()
+            .find(|(from, _)| *from == source_name)
+            .map_or(source_name, |(_, to)| to);
+
+        let excluded = self.exclude.iter().any(|pattern| {
+            pattern::matches(pattern, source_name) || pattern::matches(pattern, canonical_name)
+        });
+        if excluded {
+            return None;
+        }
+
+        let included = self
+            .all_contracts()
+            .any(|contract| contract == canonical_name)
+            || self
+                .patterns
+                .iter()
+                .any(|pattern| pattern::matches(pattern, canonical_name));
+
+        included.then(|| canonical_name.to_string())
+    }
 }
 
 const PROTOCOL_CONFIGS: &[ProtocolConfig] = &[
     ProtocolConfig {
         protocol_name: "uniswap-v2",
         contracts: &["UniswapV2Factory", "UniswapV2Router02"],
+        chain_contracts: &[],
+        optional_contracts: &[],
+        patterns: &[],
+        exclude: &[],
+        enforce_completeness: true,
+        rename: &[],
+        labeled: &[],
+        // createPair(address,address)
+        selectors: &[("UniswapV2Factory", "0xc9c65396")],
+        periphery: &[],
+        priority: None,
     },
     ProtocolConfig {
         protocol_name: "uniswap-v3",
@@ -71,6 +295,17 @@ const PROTOCOL_CONFIGS: &[ProtocolConfig] = &[
             "TickLens",
             "V3Migrator",
         ],
+        chain_contracts: &[],
+        optional_contracts: &[],
+        patterns: &[],
+        exclude: &[],
+        enforce_completeness: true,
+        rename: &[],
+        labeled: &[],
+        // createPool(address,address,uint24)
+        selectors: &[("UniswapV3Factory", "0xa1671295")],
+        periphery: &[],
+        priority: None,
     },
     ProtocolConfig {
         protocol_name: "uniswap-v4",
@@ -84,18 +319,49 @@ const PROTOCOL_CONFIGS: &[ProtocolConfig] = &[
             "WstETHHook",
             "WstETHRoutingHook",
         ],
+        chain_contracts: &[],
+        optional_contracts: &[],
+        patterns: &[],
+        exclude: &[],
+        enforce_completeness: true,
+        rename: &[],
+        labeled: &[],
+        selectors: &[],
+        periphery: &["PositionManager", "StateView", "V4Quoter"],
+        priority: None,
     },
     ProtocolConfig {
         protocol_name: "universal-router",
         contracts: &["UniversalRouter"],
+        chain_contracts: &[],
+        optional_contracts: &[],
+        patterns: &[],
+        exclude: &[],
+        enforce_completeness: true,
+        rename: &[],
+        labeled: &[],
+        selectors: &[],
+        periphery: &[],
+        priority: None,
     },
     ProtocolConfig {
         protocol_name: "permit2",
         contracts: &["Permit2"],
+        chain_contracts: &[],
+        optional_contracts: &[],
+        patterns: &[],
+        exclude: &[],
+        enforce_completeness: true,
+        rename: &[],
+        labeled: &[],
+        selectors: &[],
+        periphery: &[],
+        priority: None,
     },
 ];
 
-fn parse_chain_id(chain_id_str: &str) -> Result<u64, std::io::Error> {
+/// Decimal chain id of a deployments file
+pub fn parse_chain_id(chain_id_str: &str) -> Result<u64, std::io::Error> {
     chain_id_str.parse().map_err(|_| {
         std::io::Error::new(
             std::io::ErrorKind::InvalidData,
@@ -104,90 +370,239 @@ fn parse_chain_id(chain_id_str: &str) -> Result<u64, std::io::Error> {
     })
 }
 
-fn validate_protocol_configs_for_duplicate_definitions() -> Result<(), ParseError> {
-    let mut contract_to_protocols: HashMap<&str, Vec<&str>> = HashMap::new();
+/// Fails if a contract is defined by more than one of the bundled protocol configs, unless
+/// each of those configs sets a distinct `priority` to resolve the collision
+pub fn validate_protocol_configs_for_duplicate_definitions() -> Result<(), ParseError> {
+    match find_duplicate_definitions(PROTOCOL_CONFIGS) {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
 
-    for config in PROTOCOL_CONFIGS {
-        for &contract in config.contracts {
-            contract_to_protocols
+/// Every contract defined by more than one of the configs, reported together. A contract
+/// claimed only by configs with distinct priorities isn't a duplicate, the highest one wins it.
+fn find_duplicate_definitions(configs: &[ProtocolConfig]) -> Option<ParseError> {
+    let mut contract_to_configs: BTreeMap<&str, Vec<&ProtocolConfig>> = BTreeMap::new();
+
+    for config in configs {
+        for contract in config.all_contracts() {
+            contract_to_configs
                 .entry(contract)
-                .or_insert_with(Vec::new)
-                .push(config.protocol_name);
+                .or_default()
+                .push(config);
         }
     }
 
-    for (contract_name, protocols) in contract_to_protocols {
-        if protocols.len() > 1 {
-            return Err(ParseError::DuplicateContracts {
-                contract_name: contract_name.to_string(),
-                protocols: protocols.iter().map(|s| s.to_string()).collect(),
-            });
-        }
+    let duplicates: BTreeMap<String, Vec<String>> = contract_to_configs
+        .into_iter()
+        .filter(|(_, configs)| configs.len() > 1 && !has_distinct_priorities(configs))
+        .map(|(contract_name, configs)| {
+            let protocols: Vec<&str> = configs.iter().map(|config| config.protocol_name).collect();
+            warn!(
+                contract = contract_name,
+                protocols = ?protocols,
+                "Contract is defined in multiple protocols"
+            );
+            (
+                contract_name.to_string(),
+                protocols.iter().map(|s| s.to_string()).collect(),
+            )
+        })
+        .collect();
+
+    if duplicates.is_empty() {
+        return None;
     }
 
-    Ok(())
+    Some(ParseError::DuplicateContracts { duplicates })
+}
+
+fn has_distinct_priorities(configs: &[&ProtocolConfig]) -> bool {
+    let mut priorities = HashSet::new();
+    configs.iter().all(|config| {
+        config
+            .priority
+            .is_some_and(|priority| priorities.insert(priority))
+    })
 }
 
 fn build_response(
+    path_to_deployments: &str,
     protocol_chains: HashMap<&str, ChainDeployments>,
+    mut protocol_metadata: ProtocolsMetadata,
 ) -> Result<Vec<ProtocolDeployments>, ParseError> {
     let mut result = Vec::new();
     for config in PROTOCOL_CONFIGS {
         let chains = protocol_chains.get(config.protocol_name).unwrap();
         if !chains.is_empty() {
-            result.push(ProtocolDeployments {
-                protocol_name: config.protocol_name.to_string(),
-                chains: chains.clone(),
-            });
+            let mut deployments = ProtocolDeployments::new(config.protocol_name, chains.clone());
+            config.apply_required_contracts(&mut deployments);
+            deployments.metadata = protocol_metadata
+                .remove(config.protocol_name)
+                .unwrap_or_default();
+            meta::apply(path_to_deployments, &mut deployments)?;
+            meta::apply_statuses(path_to_deployments, &mut deployments)?;
+            result.push(deployments);
         }
     }
 
     Ok(result)
 }
 
-fn try_to_find_missing_contracts(protocol_chains: &ProtocolsDeployments) -> Result<(), ParseError> {
-    for config in PROTOCOL_CONFIGS {
+/// Drops, logging them, the chains missing a contract they require
+fn drop_incomplete_chains(
+    configs: &[ProtocolConfig],
+    protocol_chains: &mut ProtocolsDeployments,
+    protocol_metadata: &mut ProtocolsMetadata,
+) {
+    for config in configs {
+        let Some(chains) = protocol_chains.get_mut(config.protocol_name) else {
+            continue;
+        };
+
+        chains.retain(|chain_id, contracts| {
+            let is_complete = config
+                .required_contracts_of(*chain_id)
+                .iter()
+                .all(|contract| contracts.contains_key(*contract));
+            if !is_complete {
+                info!(
+                    protocol = config.protocol_name,
+                    chain_id, "Dropping chain missing required contracts"
+                );
+            }
+            is_complete
+        });
+        if let Some(metadata) = protocol_metadata.get_mut(config.protocol_name) {
+            metadata.retain(|chain_id, _| chains.contains_key(chain_id));
+        }
+    }
+}
+
+fn try_to_find_missing_contracts(
+    protocol_chains: &ProtocolsDeployments,
+    warnings: &mut Vec<Warning>,
+) -> Result<(), ParseError> {
+    match find_missing_contracts(PROTOCOL_CONFIGS, protocol_chains, warnings)
+        .into_iter()
+        .next()
+    {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Contracts of each protocol found on none of its chains without an override, and
+/// contracts a chain with an override lacks from it
+fn find_missing_contracts(
+    configs: &[ProtocolConfig],
+    protocol_chains: &ProtocolsDeployments,
+    warnings: &mut Vec<Warning>,
+) -> Vec<ParseError> {
+    let mut errors = Vec::new();
+
+    for config in configs {
         let chains: &HashMap<u64, ChainContracts> = protocol_chains
             .get(config.protocol_name)
             .expect("Protocol not found");
 
+        let has_override =
+            |chain_id: ChainId| config.chain_contracts.iter().any(|(id, _)| *id == chain_id);
+
         let mut found_contracts: HashSet<&str> = HashSet::new();
+        for (_, contracts) in chains
+            .iter()
+            .filter(|(chain_id, _)| !has_override(**chain_id))
+        {
+            found_contracts.extend(contracts.keys().map(String::as_str));
+        }
 
-        for (_chain_id, contracts) in chains {
-            for contract_name in contracts.keys() {
-                found_contracts.insert(contract_name.as_str());
-            }
+        let mut missing_contracts = Vec::new();
+        if chains.keys().any(|chain_id| !has_override(*chain_id)) {
+            missing_contracts.push((
+                None,
+                config
+                    .contracts
+                    .iter()
+                    .filter(|contract| !found_contracts.contains(**contract))
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>(),
+            ));
+        }
+        for (chain_id, required) in config.chain_contracts {
+            let Some(contracts) = chains.get(chain_id) else {
+                continue;
+            };
+            missing_contracts.push((
+                Some(*chain_id),
+                required
+                    .iter()
+                    .filter(|contract| !contracts.contains_key(**contract))
+                    .map(|s| s.to_string())
+                    .collect(),
+            ));
         }
 
-        let missing: Vec<String> = config
-            .contracts
-            .iter()
-            .filter(|&&contract| !found_contracts.contains(contract))
-            .map(|s| s.to_string())
-            .collect();
+        for (chain_id, missing) in missing_contracts {
+            if missing.is_empty() {
+                continue;
+            }
 
-        if !missing.is_empty() {
-            return Err(ParseError::MissingContracts {
+            if !config.enforce_completeness {
+                warn!(
+                    protocol = config.protocol_name,
+                    chain_id,
+                    contracts = ?missing,
+                    "Missing contracts for protocol not enforcing completeness"
+                );
+                warnings.push(Warning {
+                    kind: WarningKind::MissingContracts,
+                    protocol: Some(config.protocol_name.to_string()),
+                    chain_id,
+                    detail: format!("missing contracts: {}", missing.join(", ")),
+                });
+                continue;
+            }
+
+            errors.push(ParseError::MissingContracts {
                 protocol_name: config.protocol_name.to_string(),
+                chain_id,
                 contracts: missing,
             });
         }
     }
 
-    Ok(())
+    errors
 }
 
-fn read_deployments(path_to_deployments: &str) -> Result<Vec<UniswapDeployment>, std::io::Error> {
-    let entries = std::fs::read_dir(path_to_deployments)?;
-    let mut deployments = Vec::new();
-    for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
-        let deployment: UniswapDeployment =
-            serde_json::from_reader(BufReader::new(File::open(&path)?))?;
-        deployments.push(deployment);
-    }
-    Ok(deployments)
+/// Reads a single file holding either one deployment or an array of them
+fn read_deployments_file(path_to_file: &str) -> Result<Vec<UniswapDeployment>, ParseError> {
+    let contents = std::fs::read_to_string(path_to_file)?;
+    let to_parse_error = |source| ParseError::SerdeError {
+        path: path_to_file.to_string(),
+        source,
+    };
+
+    if contents.trim_start().starts_with('[') {
+        serde_json::from_str(&contents).map_err(to_parse_error)
+    } else {
+        serde_json::from_str(&contents)
+            .map(|deployment| vec![deployment])
+            .map_err(to_parse_error)
+    }
+}
+
+fn read_deployments(path_to_deployments: &str) -> Result<Vec<UniswapDeployment>, ParseError> {
+    if !source_files::is_tarball(path_to_deployments) && Path::new(path_to_deployments).is_file() {
+        return read_deployments_file(path_to_deployments);
+    }
+
+    let files = source_files::read_json_files(path_to_deployments, |_| true)?;
+
+    Ok(files
+        .into_iter()
+        .map(|(_, deployment)| deployment)
+        .collect())
 }
 
 fn init_protocol_chains() -> ProtocolsDeployments {
@@ -200,74 +615,415 @@ fn init_protocol_chains() -> ProtocolsDeployments {
     protocol_chains
 }
 
-pub fn parse(path_to_deployments: &str) -> Result<Vec<ProtocolDeployments>, ParseError> {
+/// `path_to_deployments` is either a directory of per-chain files, a `.tar.gz` of one, or a
+/// single JSON file with one deployment object or an array of them
+pub fn parse(
+    path_to_deployments: &str,
+    warnings: &mut Vec<Warning>,
+) -> Result<Vec<ProtocolDeployments>, ParseError> {
+    parse_with_code(
+        path_to_deployments,
+        &ParseOptions::default(),
+        warnings,
+        None,
+    )
+}
+
+/// Like `parse`, with `options`, additionally recognizing contracts by the function
+/// selectors their bytecode from `code` exposes
+pub fn parse_with_code(
+    path_to_deployments: &str,
+    options: &ParseOptions,
+    warnings: &mut Vec<Warning>,
+    code: Option<&dyn CodeProvider>,
+) -> Result<Vec<ProtocolDeployments>, ParseError> {
+    parse_source(
+        path_to_deployments,
+        path_to_deployments,
+        options,
+        warnings,
+        code,
+    )
+}
+
+/// Parses a single per-chain file of a deployments directory, e.g. to reparse just the file
+/// that changed, with the companion files of its directory
+pub fn parse_file(
+    path_to_file: &str,
+    options: &ParseOptions,
+    warnings: &mut Vec<Warning>,
+    code: Option<&dyn CodeProvider>,
+) -> Result<Vec<ProtocolDeployments>, ParseError> {
+    let folder = Path::new(path_to_file).parent().map_or_else(
+        || ".".to_string(),
+        |parent| parent.to_string_lossy().into_owned(),
+    );
+
+    parse_source(path_to_file, &folder, options, warnings, code)
+}
+
+/// Parses `source` into protocols, reading their companion files from `folder`
+fn parse_source(
+    source: &str,
+    folder: &str,
+    options: &ParseOptions,
+    warnings: &mut Vec<Warning>,
+    code: Option<&dyn CodeProvider>,
+) -> Result<Vec<ProtocolDeployments>, ParseError> {
     validate_protocol_configs_for_duplicate_definitions()?;
 
+    let (mut protocol_chains, mut protocol_metadata) = assign_contracts(source, warnings, code)?;
+
+    if options.drop_incomplete_chains {
+        drop_incomplete_chains(
+            PROTOCOL_CONFIGS,
+            &mut protocol_chains,
+            &mut protocol_metadata,
+        );
+    }
+    try_to_find_missing_contracts(&protocol_chains, warnings)?;
+
+    build_response(folder, protocol_chains, protocol_metadata)
+}
+
+/// Names of the protocols the uniswap deployments are split into
+pub fn protocol_names() -> impl Iterator<Item = &'static str> {
+    PROTOCOL_CONFIGS.iter().map(|config| config.protocol_name)
+}
+
+/// Like `parse`, but keeps going past validation failures and reports all of them,
+/// along with whatever could be parsed
+pub fn validate(path_to_deployments: &str) -> (Vec<ProtocolDeployments>, Vec<ParseError>) {
+    let mut errors: Vec<_> = find_duplicate_definitions(PROTOCOL_CONFIGS)
+        .into_iter()
+        .collect();
+    let mut warnings = Vec::new();
+
+    let (protocol_chains, protocol_metadata) =
+        match assign_contracts(path_to_deployments, &mut warnings, None) {
+            Ok(assigned) => assigned,
+            Err(err) => {
+                errors.push(err);
+                return (Vec::new(), errors);
+            }
+        };
+
+    errors.extend(find_missing_contracts(
+        PROTOCOL_CONFIGS,
+        &protocol_chains,
+        &mut warnings,
+    ));
+
+    match build_response(path_to_deployments, protocol_chains, protocol_metadata) {
+        Ok(deployments) => (deployments, errors),
+        Err(err) => {
+            errors.push(err);
+            (Vec::new(), errors)
+        }
+    }
+}
+
+fn assign_contracts(
+    path_to_deployments: &str,
+    warnings: &mut Vec<Warning>,
+    code: Option<&dyn CodeProvider>,
+) -> Result<(ProtocolsDeployments, ProtocolsMetadata), ParseError> {
     let mut protocol_chains: ProtocolsDeployments = init_protocol_chains();
+    let mut protocol_metadata = ProtocolsMetadata::new();
 
     let deployments = read_deployments(path_to_deployments)?;
 
     for chain_deployments in deployments {
         let chain_id: u64 = parse_chain_id(&chain_deployments.chain_id)?;
 
-        let mut chain_protocol_contracts: HashMap<ProtocolName, ChainContracts> = HashMap::new();
+        let assigned = assign_chain_contracts(
+            PROTOCOL_CONFIGS,
+            chain_id,
+            chain_deployments.latest,
+            warnings,
+            code,
+        );
+
+        for (protocol_name, metadata) in assigned.metadata {
+            protocol_metadata
+                .entry(protocol_name)
+                .or_default()
+                .insert(chain_id, metadata);
+        }
 
         for config in PROTOCOL_CONFIGS {
-            chain_protocol_contracts.insert(config.protocol_name, ChainContracts::new());
+            let contracts = assigned.contracts.get(config.protocol_name).unwrap();
+            if !contracts.is_empty() {
+                protocol_chains
+                    .get_mut(config.protocol_name)
+                    .unwrap()
+                    .insert(chain_id, contracts.to_owned());
+            }
         }
+    }
 
-        for (name, contract) in chain_deployments.latest {
-            let mut matched = false;
+    Ok((protocol_chains, protocol_metadata))
+}
 
-            for config in PROTOCOL_CONFIGS {
-                if !config.contracts.iter().any(|&c| c == name.as_str()) {
-                    continue;
-                }
+fn assign_chain_contracts(
+    configs: &[ProtocolConfig],
+    chain_id: u64,
+    latest: HashMap<String, ContractDeployment>,
+    warnings: &mut Vec<Warning>,
+    code: Option<&dyn CodeProvider>,
+) -> AssignedChain {
+    let mut chain_protocol_contracts: HashMap<ProtocolName, ChainContracts> = HashMap::new();
+    let mut labeled: HashMap<(ProtocolName, &str), Vec<LabeledAddress>> = HashMap::new();
 
-                chain_protocol_contracts
-                    .get_mut(config.protocol_name)
-                    .expect("Not found protocol")
-                    .insert(name.clone(), contract.address.clone());
+    let mut chain_protocol_metadata: HashMap<ProtocolName, ChainMetadata> = HashMap::new();
+
+    for config in configs {
+        chain_protocol_contracts.insert(config.protocol_name, ChainContracts::new());
+    }
+
+    let mut unmatched = Vec::new();
+    let configs_by_priority = ProtocolConfig::by_priority(configs);
+    // Source name each assigned canonical name was taken from
+    let mut source_names: HashMap<(ProtocolName, String), String> = HashMap::new();
+
+    let mut latest: Vec<_> = latest.into_iter().collect();
+    latest.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (name, contract) in latest {
+        let Some(address) = contract.address else {
+            debug!(contract = %name, chain_id = %chain_id, "Skipping contract without address");
+            continue;
+        };
+
+        let mut matched = false;
+
+        for config in &configs_by_priority {
+            if let Some((index, canonical_name, label)) = config.label(&name) {
+                labeled
+                    .entry((config.protocol_name, canonical_name))
+                    .or_default()
+                    .push((index, label, address.clone()));
 
                 matched = true;
                 break;
             }
 
-            if !matched {
-                debug!(
-                    contract = %name,
-                    chain_id = %chain_id,
-                    "Contract not assigned to any protocol"
+            let Some(canonical_name) = config.resolve(&name) else {
+                continue;
+            };
+            matched = true;
+
+            let key = (config.protocol_name, canonical_name.clone());
+            if let Some(taken_from) = source_names.get(&key) {
+                let (kept, dropped) = if config.rename_rank(&name) < config.rename_rank(taken_from)
+                {
+                    (name.clone(), taken_from.clone())
+                } else {
+                    (taken_from.clone(), name.clone())
+                };
+                warn!(
+                    protocol = config.protocol_name,
+                    chain_id,
+                    contract = %canonical_name,
+                    kept = %kept,
+                    dropped = %dropped,
+                    "Source contracts rename to the same contract"
                 );
+                warnings.push(Warning {
+                    kind: WarningKind::RenameCollision,
+                    protocol: Some(config.protocol_name.to_string()),
+                    chain_id: Some(chain_id),
+                    detail: format!(
+                        "'{}' and '{}' both resolve to '{}', keeping '{}'",
+                        kept, dropped, canonical_name, kept
+                    ),
+                });
+                if kept != name {
+                    break;
+                }
             }
-        }
+            source_names.insert(key, name.clone());
 
-        for config in PROTOCOL_CONFIGS {
-            let contracts = chain_protocol_contracts.get(config.protocol_name).unwrap();
-            if !contracts.is_empty() {
-                protocol_chains
-                    .get_mut(config.protocol_name)
-                    .unwrap()
-                    .insert(chain_id, contracts.to_owned());
+            let periphery_version = contract
+                .version
+                .clone()
+                .filter(|_| config.periphery.contains(&canonical_name.as_str()));
+            if let Some(metadata) = chain_protocol_metadata.get_mut(config.protocol_name) {
+                metadata.remove(&canonical_name);
+            }
+            if contract.compiler_version.is_some()
+                || contract.block_created.is_some()
+                || periphery_version.is_some()
+            {
+                chain_protocol_metadata
+                    .entry(config.protocol_name)
+                    .or_default()
+                    .insert(
+                        canonical_name.clone(),
+                        ContractMetadata {
+                            compiler_version: contract.compiler_version.clone(),
+                            block_created: contract.block_created,
+                            periphery_version,
+                            ..ContractMetadata::default()
+                        },
+                    );
             }
+
+            chain_protocol_contracts
+                .get_mut(config.protocol_name)
+                .expect("Not found protocol")
+                .insert(canonical_name, address.clone());
+
+            break;
+        }
+
+        if !matched {
+            unmatched.push((name, address));
+        }
+    }
+
+    // Recognized by selector only after every named contract is in place, so a contract
+    // present under its usual name is never displaced
+    unmatched.sort();
+    for (name, address) in unmatched {
+        let recognized = code.and_then(|code| {
+            match_by_selector(configs, chain_id, &address, code, &chain_protocol_contracts)
+        });
+        if let Some((protocol_name, canonical_name)) = recognized {
+            debug!(
+                contract = %name,
+                canonical_name,
+                chain_id = %chain_id,
+                "Contract recognized by its selector"
+            );
+            chain_protocol_contracts
+                .get_mut(protocol_name)
+                .expect("Not found protocol")
+                .insert(canonical_name.to_string(), address);
+            continue;
         }
+
+        debug!(
+            contract = %name,
+            chain_id = %chain_id,
+            "Contract not assigned to any protocol"
+        );
+        warnings.push(Warning {
+            kind: WarningKind::UnassignedContract,
+            protocol: None,
+            chain_id: Some(chain_id),
+            detail: format!("contract '{}' not assigned to any protocol", name),
+        });
     }
 
-    try_to_find_missing_contracts(&protocol_chains)?;
+    for ((protocol_name, canonical_name), mut versions) in labeled {
+        versions.sort_by_key(|(index, _, _)| *index);
+        let (_, _, newest_address) = versions.last().expect("Labeled contract without versions");
 
-    let result = build_response(protocol_chains)?;
+        chain_protocol_contracts
+            .get_mut(protocol_name)
+            .expect("Not found protocol")
+            .insert(canonical_name.to_string(), newest_address.clone());
 
-    Ok(result)
+        chain_protocol_metadata
+            .entry(protocol_name)
+            .or_default()
+            .insert(
+                canonical_name.to_string(),
+                ContractMetadata {
+                    versions: Some(
+                        versions
+                            .into_iter()
+                            .map(|(_, label, address)| (label.to_string(), address))
+                            .collect(),
+                    ),
+                    ..ContractMetadata::default()
+                },
+            );
+    }
+
+    AssignedChain {
+        contracts: chain_protocol_contracts,
+        metadata: chain_protocol_metadata,
+    }
+}
+
+/// Protocol and canonical name of the first selector-identified contract, still missing on
+/// the chain, whose selector the bytecode at `address` exposes
+fn match_by_selector(
+    configs: &[ProtocolConfig],
+    chain_id: ChainId,
+    address: &str,
+    code: &dyn CodeProvider,
+    assigned: &HashMap<ProtocolName, ChainContracts>,
+) -> Option<(ProtocolName, &'static str)> {
+    let candidates: Vec<_> = configs
+        .iter()
+        .flat_map(|config| {
+            config
+                .selectors
+                .iter()
+                .map(move |(canonical_name, selector)| {
+                    (config.protocol_name, *canonical_name, *selector)
+                })
+        })
+        .filter(|(protocol_name, canonical_name, _)| {
+            !assigned
+                .get(protocol_name)
+                .is_some_and(|contracts| contracts.contains_key(*canonical_name))
+        })
+        .collect();
+    if candidates.is_empty() {
+        return None;
+    }
+
+    let bytecode = match code.code(chain_id, address) {
+        Ok(bytecode) => bytecode?,
+        Err(err) => {
+            warn!(chain_id, address, error = %err, "Failed to read contract bytecode");
+            return None;
+        }
+    };
+
+    candidates
+        .into_iter()
+        .find(|(_, _, selector)| {
+            bytecode::parse_selector(selector)
+                .is_some_and(|selector| bytecode::exposes_selector(&bytecode, selector))
+        })
+        .map(|(protocol_name, canonical_name, _)| (protocol_name, canonical_name))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// A config requiring `contracts` on every chain and enforcing completeness, with no
+    /// other rules
+    fn protocol_config(
+        protocol_name: &'static str,
+        contracts: &'static [&'static str],
+    ) -> ProtocolConfig {
+        ProtocolConfig {
+            protocol_name,
+            contracts,
+            chain_contracts: &[],
+            optional_contracts: &[],
+            patterns: &[],
+            exclude: &[],
+            enforce_completeness: true,
+            rename: &[],
+            labeled: &[],
+            selectors: &[],
+            periphery: &[],
+            priority: None,
+        }
+    }
+
     #[test]
     fn test_parse_uniswap() {
         let path = "source/uniswap/deployments";
-        let res = parse(path);
+        let res = parse(path, &mut Vec::new());
         assert!(res.is_ok());
 
         let protocols = res.unwrap();
@@ -293,7 +1049,7 @@ mod tests {
     #[test]
     fn test_parse_uniswap_specific_chains() {
         let path = "source/uniswap/deployments";
-        let res = parse(path);
+        let res = parse(path, &mut Vec::new());
         assert!(res.is_ok());
 
         let protocols = res.unwrap();
@@ -314,4 +1070,577 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_parse_uniswap_tarball_matches_directory() {
+        let from_directory = parse("tests/fixtures/uniswap/deployments", &mut Vec::new()).unwrap();
+        let from_tarball =
+            parse("tests/fixtures/uniswap/deployments.tar.gz", &mut Vec::new()).unwrap();
+
+        assert_eq!(from_directory.len(), 5);
+        assert_eq!(from_tarball.len(), from_directory.len());
+
+        for (tarball, directory) in from_tarball.iter().zip(&from_directory) {
+            assert_eq!(tarball.protocol_name, directory.protocol_name);
+            assert_eq!(tarball.chains, directory.chains);
+        }
+    }
+
+    #[test]
+    fn test_parse_uniswap_accepts_address_field_aliases() {
+        let plain = parse("tests/fixtures/uniswap/deployments", &mut Vec::new()).unwrap();
+        let aliased = parse(
+            "tests/fixtures/aliases/uniswap/deployments",
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(aliased.len(), plain.len());
+
+        for (aliased, plain) in aliased.iter().zip(&plain) {
+            assert_eq!(aliased.protocol_name, plain.protocol_name);
+            assert_eq!(aliased.chains, plain.chains);
+        }
+    }
+
+    #[test]
+    fn test_completeness_ignores_missing_optional_contracts() {
+        let config = ProtocolConfig {
+            optional_contracts: &["SwapRouter"],
+            ..protocol_config("uniswap-v3", &["UniswapV3Factory", "QuoterV2"])
+        };
+        let contracts = |names: &[&str]| -> ChainContracts {
+            names
+                .iter()
+                .map(|name| (name.to_string(), "0x01".to_string()))
+                .collect()
+        };
+        let mut deployments = ProtocolDeployments::new(
+            "uniswap-v3",
+            HashMap::from([
+                (
+                    1,
+                    contracts(&["UniswapV3Factory", "QuoterV2", "SwapRouter"]),
+                ),
+                (8453, contracts(&["UniswapV3Factory", "QuoterV2"])),
+                (10, contracts(&["UniswapV3Factory"])),
+            ]),
+        );
+
+        config.apply_required_contracts(&mut deployments);
+
+        // Base has no SwapRouter, which is optional
+        assert_eq!(deployments.completeness(8453), Some(1.0));
+        assert_eq!(deployments.completeness(1), Some(1.0));
+        assert_eq!(deployments.completeness(10), Some(0.5));
+    }
+
+    #[test]
+    fn test_chain_override_requires_only_its_subset() {
+        let config = ProtocolConfig {
+            chain_contracts: &[(130, &["PoolManager"])],
+            ..protocol_config("uniswap-v4", &["PoolManager", "PositionManager"])
+        };
+        let pool_manager = ChainContracts::from([("PoolManager".to_string(), "0x01".to_string())]);
+        let mut deployments = ProtocolDeployments::new(
+            "uniswap-v4",
+            HashMap::from([(1, pool_manager.clone()), (130, pool_manager)]),
+        );
+
+        config.apply_required_contracts(&mut deployments);
+
+        assert_eq!(deployments.completeness(130), Some(1.0));
+        assert_eq!(deployments.completeness(1), Some(0.5));
+        assert_eq!(deployments.required_contracts_of(1).len(), 2);
+    }
+
+    fn protocol_chains_with(protocol_name: ProtocolName, names: &[&str]) -> ProtocolsDeployments {
+        let contracts: ChainContracts = names
+            .iter()
+            .map(|name| (name.to_string(), "0x01".to_string()))
+            .collect();
+
+        HashMap::from([(protocol_name, HashMap::from([(1, contracts)]))])
+    }
+
+    #[test]
+    fn test_missing_contract_fails_enforcing_protocol() {
+        let configs = [protocol_config(
+            "uniswap-v4",
+            &["PoolManager", "PositionManager"],
+        )];
+        let protocol_chains = protocol_chains_with("uniswap-v4", &["PoolManager"]);
+
+        let errors = find_missing_contracts(&configs, &protocol_chains, &mut Vec::new());
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            ParseError::MissingContracts { contracts, .. } if contracts == &["PositionManager"]
+        ));
+    }
+
+    #[test]
+    fn test_only_chains_with_override_are_checked_on_their_own() {
+        let configs = [ProtocolConfig {
+            chain_contracts: &[(130, &["PoolManager", "StateView"])],
+            ..protocol_config("uniswap-v4", &["PoolManager", "PositionManager"])
+        }];
+        let contracts = |names: &[&str]| -> ChainContracts {
+            names
+                .iter()
+                .map(|name| (name.to_string(), "0x01".to_string()))
+                .collect()
+        };
+        // Mainnet lacks PositionManager, which another chain without override has
+        let protocol_chains = HashMap::from([(
+            "uniswap-v4",
+            HashMap::from([
+                (1, contracts(&["PoolManager"])),
+                (10, contracts(&["PoolManager", "PositionManager"])),
+                (130, contracts(&["PoolManager", "PositionManager"])),
+            ]),
+        )]);
+
+        let errors = find_missing_contracts(&configs, &protocol_chains, &mut Vec::new());
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            ParseError::MissingContracts { chain_id: Some(130), contracts, .. }
+                if contracts == &["StateView"]
+        ));
+    }
+
+    #[test]
+    fn test_bundled_configs_accept_committed_deployments() {
+        let protocol_chains: ProtocolsDeployments = PROTOCOL_CONFIGS
+            .iter()
+            .map(|config| {
+                let path = format!("deployments/{}", config.protocol_name);
+                let chains = source_files::read_json_files::<ChainContracts>(&path, |_| true)
+                    .unwrap()
+                    .into_iter()
+                    .map(|(path, contracts)| (source_files::chain_id_of(&path).unwrap(), contracts))
+                    .collect();
+                (config.protocol_name, chains)
+            })
+            .collect();
+        assert!(protocol_chains.values().all(|chains| !chains.is_empty()));
+
+        let mut warnings = Vec::new();
+        let errors = find_missing_contracts(PROTOCOL_CONFIGS, &protocol_chains, &mut warnings);
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_missing_contract_passes_non_enforcing_protocol() {
+        let configs = [ProtocolConfig {
+            enforce_completeness: false,
+            ..protocol_config("uniswap-v4", &["PoolManager", "PositionManager"])
+        }];
+        let protocol_chains = protocol_chains_with("uniswap-v4", &["PoolManager"]);
+
+        let mut warnings = Vec::new();
+
+        assert!(find_missing_contracts(&configs, &protocol_chains, &mut warnings).is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::MissingContracts);
+    }
+
+    #[test]
+    fn test_rename_applies_only_within_its_protocol() {
+        let configs = [
+            protocol_config("uniswap-v2", &["Factory"]),
+            ProtocolConfig {
+                rename: &[("FactoryV2", "Factory")],
+                ..protocol_config("sushiswap-v2", &["Factory"])
+            },
+        ];
+        let latest = HashMap::from([(
+            "FactoryV2".to_string(),
+            ContractDeployment {
+                address: Some("0x01".to_string()),
+                compiler_version: None,
+                block_created: None,
+                version: None,
+            },
+        )]);
+
+        let assigned = assign_chain_contracts(&configs, 1, latest, &mut Vec::new(), None);
+
+        assert!(assigned.contracts["uniswap-v2"].is_empty());
+        assert_eq!(
+            assigned.contracts["sushiswap-v2"],
+            ChainContracts::from([("Factory".to_string(), "0x01".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_rename_collision_keeps_canonical_then_first_rename() {
+        let configs = [ProtocolConfig {
+            rename: &[("FactoryV2", "Factory"), ("AFactory", "Factory")],
+            ..protocol_config("sushiswap-v2", &["Factory"])
+        }];
+        let deployment = |address: &str| ContractDeployment {
+            address: Some(address.to_string()),
+            compiler_version: None,
+            block_created: None,
+            version: None,
+        };
+
+        let renamed_only = HashMap::from([
+            ("AFactory".to_string(), deployment("0x02")),
+            ("FactoryV2".to_string(), deployment("0x01")),
+        ]);
+        let mut warnings = Vec::new();
+        let assigned = assign_chain_contracts(&configs, 1, renamed_only, &mut warnings, None);
+        assert_eq!(assigned.contracts["sushiswap-v2"]["Factory"], "0x01");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::RenameCollision);
+        assert_eq!(
+            warnings[0].detail,
+            "'FactoryV2' and 'AFactory' both resolve to 'Factory', keeping 'FactoryV2'"
+        );
+
+        let with_canonical = HashMap::from([
+            ("Factory".to_string(), deployment("0x03")),
+            ("AFactory".to_string(), deployment("0x02")),
+        ]);
+        let assigned = assign_chain_contracts(&configs, 1, with_canonical, &mut Vec::new(), None);
+        assert_eq!(assigned.contracts["sushiswap-v2"]["Factory"], "0x03");
+    }
+
+    #[test]
+    fn test_compiler_version_is_kept_as_metadata() {
+        let configs = [protocol_config(
+            "uniswap-v3",
+            &["UniswapV3Factory", "SwapRouter", "QuoterV2"],
+        )];
+        let latest: HashMap<String, ContractDeployment> = serde_json::from_str(
+            r#"{
+                "UniswapV3Factory": { "address": "0x01", "compilerVersion": "v0.7.6+commit.7338295f" },
+                "SwapRouter": { "address": "0x02", "solcVersion": "0.7.6", "blockNumber": 12369621 },
+                "QuoterV2": { "address": "0x03" }
+            }"#,
+        )
+        .unwrap();
+
+        let assigned = assign_chain_contracts(&configs, 1, latest, &mut Vec::new(), None);
+
+        let metadata = &assigned.metadata["uniswap-v3"];
+        assert_eq!(
+            metadata["UniswapV3Factory"].compiler_version.as_deref(),
+            Some("v0.7.6+commit.7338295f")
+        );
+        assert_eq!(
+            metadata["SwapRouter"].compiler_version.as_deref(),
+            Some("0.7.6")
+        );
+        assert_eq!(metadata["SwapRouter"].block_created, Some(12369621));
+        assert!(!metadata.contains_key("QuoterV2"));
+    }
+
+    #[test]
+    fn test_chains_on_different_periphery_versions() {
+        let v4 = PROTOCOL_CONFIGS
+            .iter()
+            .find(|config| config.protocol_name == "uniswap-v4")
+            .unwrap();
+        let latest = |version: &str| -> HashMap<String, ContractDeployment> {
+            serde_json::from_value(serde_json::json!({
+                "PoolManager": { "address": "0x01", "version": "v1.0.0" },
+                "PositionManager": { "address": "0x02", "version": version },
+                "StateView": { "address": "0x03", "version": version }
+            }))
+            .unwrap()
+        };
+
+        let mut deployments = ProtocolDeployments::new("uniswap-v4", HashMap::new());
+        for (chain_id, version) in [(1, "v1.0.0"), (8453, "v1.0.1")] {
+            let mut assigned = assign_chain_contracts(
+                std::slice::from_ref(v4),
+                chain_id,
+                latest(version),
+                &mut Vec::new(),
+                None,
+            );
+            deployments
+                .chains
+                .insert(chain_id, assigned.contracts.remove("uniswap-v4").unwrap());
+            deployments
+                .metadata
+                .insert(chain_id, assigned.metadata.remove("uniswap-v4").unwrap());
+        }
+
+        // PoolManager is core, its version isn't the periphery's
+        assert!(!deployments.metadata[&1].contains_key("PoolManager"));
+        assert_eq!(deployments.periphery_version(1).as_deref(), Some("v1.0.0"));
+        assert_eq!(
+            deployments.periphery_version(8453).as_deref(),
+            Some("v1.0.1")
+        );
+    }
+
+    #[test]
+    fn test_higher_priority_wins_contested_contract() {
+        let config = |protocol_name, priority| ProtocolConfig {
+            priority,
+            ..protocol_config(protocol_name, &["Factory"])
+        };
+        let configs = [
+            config("uniswap-v2", Some(1)),
+            config("sushiswap-v2", Some(2)),
+        ];
+        let latest = HashMap::from([(
+            "Factory".to_string(),
+            ContractDeployment {
+                address: Some("0x01".to_string()),
+                compiler_version: None,
+                block_created: None,
+                version: None,
+            },
+        )]);
+
+        let assigned = assign_chain_contracts(&configs, 1, latest, &mut Vec::new(), None);
+
+        assert!(assigned.contracts["uniswap-v2"].is_empty());
+        assert_eq!(assigned.contracts["sushiswap-v2"]["Factory"], "0x01");
+        assert!(find_duplicate_definitions(&configs).is_none());
+        assert!(
+            find_duplicate_definitions(&[
+                config("uniswap-v2", Some(1)),
+                config("fork-v2", Some(1))
+            ])
+            .is_some()
+        );
+        assert!(
+            find_duplicate_definitions(&[config("uniswap-v2", Some(1)), config("fork-v2", None)])
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_duplicate_definitions_are_reported_together() {
+        let config = |protocol_name, contracts| protocol_config(protocol_name, contracts);
+        let configs = [
+            config("uniswap-v2", &["Router", "Quoter", "Factory"]),
+            config("uniswap-v3", &["Router", "Pool"]),
+            config("uniswap-v4", &["Quoter"]),
+        ];
+
+        let Some(ParseError::DuplicateContracts { duplicates }) =
+            find_duplicate_definitions(&configs)
+        else {
+            panic!("Expected duplicate contracts");
+        };
+
+        assert_eq!(
+            duplicates,
+            BTreeMap::from([
+                (
+                    "Quoter".to_string(),
+                    vec!["uniswap-v2".to_string(), "uniswap-v4".to_string()]
+                ),
+                (
+                    "Router".to_string(),
+                    vec!["uniswap-v2".to_string(), "uniswap-v3".to_string()]
+                ),
+            ])
+        );
+        assert!(find_duplicate_definitions(&configs[1..]).is_none());
+    }
+
+    #[test]
+    fn test_exclude_vetoes_pattern_match() {
+        let configs = [ProtocolConfig {
+            patterns: &["*Router"],
+            exclude: &["Mock*"],
+            ..protocol_config("uniswap-v3", &[])
+        }];
+        let latest = ["SwapRouter", "MockRouter"]
+            .into_iter()
+            .map(|name| {
+                (
+                    name.to_string(),
+                    ContractDeployment {
+                        address: Some("0x01".to_string()),
+                        compiler_version: None,
+                        block_created: None,
+                        version: None,
+                    },
+                )
+            })
+            .collect();
+        let mut warnings = Vec::new();
+
+        let assigned = assign_chain_contracts(&configs, 1, latest, &mut warnings, None);
+
+        assert_eq!(
+            assigned.contracts["uniswap-v3"],
+            ChainContracts::from([("SwapRouter".to_string(), "0x01".to_string())])
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::UnassignedContract);
+    }
+
+    /// Bytecode of a factory dispatching `createPool` at 0x02, nothing elsewhere
+    struct MockRpc;
+
+    impl CodeProvider for MockRpc {
+        fn code(
+            &self,
+            _chain_id: ChainId,
+            address: &str,
+        ) -> Result<Option<Vec<u8>>, std::io::Error> {
+            let code = match address {
+                "0x02" => "60e060020a6000350463a167129514",
+                _ => "6080604052",
+            };
+            Ok(bytecode::decode_hex(code))
+        }
+    }
+
+    #[test]
+    fn test_renamed_factory_is_recognized_by_selector() {
+        let configs = [ProtocolConfig {
+            selectors: &[("UniswapV3Factory", "0xa1671295")],
+            ..protocol_config("uniswap-v3", &["UniswapV3Factory"])
+        }];
+        let latest = || -> HashMap<String, ContractDeployment> {
+            [("Helper", "0x01"), ("V3CoreFactory", "0x02")]
+                .into_iter()
+                .map(|(name, address)| {
+                    (
+                        name.to_string(),
+                        ContractDeployment {
+                            address: Some(address.to_string()),
+                            compiler_version: None,
+                            block_created: None,
+                            version: None,
+                        },
+                    )
+                })
+                .collect()
+        };
+
+        let mut warnings = Vec::new();
+        let assigned = assign_chain_contracts(&configs, 1, latest(), &mut warnings, Some(&MockRpc));
+
+        assert_eq!(
+            assigned.contracts["uniswap-v3"],
+            ChainContracts::from([("UniswapV3Factory".to_string(), "0x02".to_string())])
+        );
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].detail.contains("Helper"));
+
+        // Without bytecode, matching stays by name
+        let assigned = assign_chain_contracts(&configs, 1, latest(), &mut Vec::new(), None);
+        assert!(assigned.contracts["uniswap-v3"].is_empty());
+    }
+
+    #[test]
+    fn test_labeled_contract_keeps_every_version() {
+        let configs = [ProtocolConfig {
+            labeled: &[
+                ("UniversalRouterV1", ("UniversalRouter", "v1")),
+                ("UniversalRouterV2", ("UniversalRouter", "v2")),
+            ],
+            ..protocol_config("universal-router", &["UniversalRouter"])
+        }];
+        let latest = [("UniversalRouterV2", "0x02"), ("UniversalRouterV1", "0x01")]
+            .into_iter()
+            .map(|(name, address)| {
+                (
+                    name.to_string(),
+                    ContractDeployment {
+                        address: Some(address.to_string()),
+                        compiler_version: None,
+                        block_created: None,
+                        version: None,
+                    },
+                )
+            })
+            .collect();
+
+        let assigned = assign_chain_contracts(&configs, 1, latest, &mut Vec::new(), None);
+
+        assert_eq!(
+            assigned.contracts["universal-router"],
+            ChainContracts::from([("UniversalRouter".to_string(), "0x02".to_string())])
+        );
+        assert_eq!(
+            assigned.metadata["universal-router"]["UniversalRouter"].versions,
+            Some(BTreeMap::from([
+                ("v1".to_string(), "0x01".to_string()),
+                ("v2".to_string(), "0x02".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_skips_null_addresses() {
+        let result = parse(
+            "tests/fixtures/placeholders/uniswap/deployments",
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        let permit2 = result
+            .iter()
+            .find(|deployments| deployments.protocol_name == "permit2")
+            .unwrap();
+        assert!(permit2.chains[&1].contains_key("Permit2"));
+        assert!(!permit2.chains.contains_key(&8453));
+    }
+
+    #[test]
+    fn test_parse_nested_deployments_matches_root() {
+        let from_nested = parse("tests/fixtures/nested/uniswap", &mut Vec::new()).unwrap();
+        let from_root = parse("tests/fixtures/uniswap/deployments", &mut Vec::new()).unwrap();
+
+        assert_eq!(from_nested.len(), from_root.len());
+        for (nested_protocol, root_protocol) in from_nested.iter().zip(&from_root) {
+            assert_eq!(nested_protocol.protocol_name, root_protocol.protocol_name);
+            assert_eq!(nested_protocol.chains, root_protocol.chains);
+        }
+    }
+
+    #[test]
+    fn test_parse_array_file() {
+        let from_array = parse(
+            "tests/fixtures/uniswap-array/deployments.json",
+            &mut Vec::new(),
+        )
+        .unwrap();
+        let from_dir = parse("tests/fixtures/uniswap/deployments", &mut Vec::new()).unwrap();
+
+        assert_eq!(from_array.len(), from_dir.len());
+        for (array_protocol, dir_protocol) in from_array.iter().zip(&from_dir) {
+            assert_eq!(array_protocol.protocol_name, dir_protocol.protocol_name);
+            assert_eq!(array_protocol.chains, dir_protocol.chains);
+        }
+        let permit2 = from_array
+            .iter()
+            .find(|protocol_deployments| protocol_deployments.protocol_name == "permit2")
+            .unwrap();
+        assert!(permit2.chains.contains_key(&1));
+        assert!(permit2.chains.contains_key(&8453));
+    }
+
+    #[test]
+    fn test_malformed_file_error_names_the_file() {
+        let err = parse(
+            "tests/fixtures/malformed/uniswap/deployments",
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("tests/fixtures/malformed/uniswap/deployments/1.json")
+        );
+    }
 }
@@ -0,0 +1,190 @@
+use std::fmt::{self, Display};
+
+use crate::{
+    balancer,
+    chains::ChainAliases,
+    normalize,
+    types::ProtocolDeployments,
+    uniswap,
+    warnings::{Warning, address_anomalies},
+};
+
+/// A single problem found in the source data
+#[derive(Debug)]
+pub struct Issue {
+    pub source: &'static str,
+    pub message: String,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.source, self.message)
+    }
+}
+
+/// Runs both parsers, folds aliased chains and runs every validator, collecting all issues
+/// instead of stopping at the first
+pub fn validate(
+    balancer_repo_path: &str,
+    uniswap_deployments_path: &str,
+    chain_aliases: &ChainAliases,
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    match balancer::parse_versions(
+        &[balancer_repo_path],
+        &balancer::ParseOptions::default(),
+        &mut Vec::new(),
+    ) {
+        Ok(mut balancer_protocols) => {
+            for deployments in &mut balancer_protocols {
+                issues.extend(fold_chain_aliases("balancer", deployments, chain_aliases));
+                issues.extend(normalize_addresses("balancer", deployments));
+                issues.extend(check_addresses("balancer", deployments));
+            }
+        }
+        Err(err) => issues.push(Issue {
+            source: "balancer",
+            message: err.to_string(),
+        }),
+    }
+
+    let (mut uniswap_deployments, errors) = uniswap::validate(uniswap_deployments_path);
+
+    issues.extend(errors.into_iter().map(|err| Issue {
+        source: "uniswap",
+        message: err.to_string(),
+    }));
+
+    for deployments in &mut uniswap_deployments {
+        issues.extend(fold_chain_aliases("uniswap", deployments, chain_aliases));
+        issues.extend(normalize_addresses("uniswap", deployments));
+        issues.extend(check_addresses("uniswap", deployments));
+    }
+
+    issues
+}
+
+/// Contracts at different addresses on a chain and on a chain aliased to it
+fn fold_chain_aliases(
+    source: &'static str,
+    protocol_deployments: &mut ProtocolDeployments,
+    chain_aliases: &ChainAliases,
+) -> Vec<Issue> {
+    let warnings = normalize::fold_chain_aliases(protocol_deployments, chain_aliases);
+
+    warning_issues(source, protocol_deployments, warnings)
+}
+
+/// Normalization failures and warnings, e.g. an address prefixed for another chain
+fn normalize_addresses(
+    source: &'static str,
+    protocol_deployments: &mut ProtocolDeployments,
+) -> Vec<Issue> {
+    match normalize::addresses(protocol_deployments) {
+        Ok(warnings) => warning_issues(source, protocol_deployments, warnings),
+        Err(err) => vec![Issue {
+            source,
+            message: err.to_string(),
+        }],
+    }
+}
+
+fn check_addresses(source: &'static str, protocol_deployments: &ProtocolDeployments) -> Vec<Issue> {
+    warning_issues(
+        source,
+        protocol_deployments,
+        address_anomalies(protocol_deployments),
+    )
+}
+
+fn warning_issues(
+    source: &'static str,
+    protocol_deployments: &ProtocolDeployments,
+    warnings: Vec<Warning>,
+) -> Vec<Issue> {
+    warnings
+        .into_iter()
+        .map(|warning| Issue {
+            source,
+            message: format!(
+                "{} of protocol '{}' on chain {}",
+                warning.detail,
+                protocol_deployments.protocol_name,
+                warning.chain_id.unwrap_or_default()
+            ),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_valid_sources() {
+        let issues = validate(
+            "tests/fixtures/balancer",
+            "tests/fixtures/uniswap/deployments",
+            &ChainAliases::new(),
+        );
+
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_validate_collects_every_issue() {
+        let issues = validate(
+            "tests/fixtures/broken/balancer",
+            "tests/fixtures/broken/uniswap/deployments",
+            &ChainAliases::new(),
+        );
+
+        let messages: Vec<String> = issues.iter().map(|issue| issue.to_string()).collect();
+
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.starts_with("[balancer] invalid address '0xnotanaddress'"))
+        );
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.starts_with("[uniswap] zero address"))
+        );
+        assert!(
+            messages.contains(
+                &"[uniswap] address 'base:0x000000000022D473030F116dDEE9F6B43aC78BA3' of contract \
+              'QuoterV2' is prefixed for chain 8453 of protocol 'uniswap-v3' on chain 1"
+                    .to_string()
+            )
+        );
+        // uniswap-v2 and uniswap-v3 are on chain 1 without all their contracts
+        assert_eq!(
+            messages
+                .iter()
+                .filter(|m| m.contains("Missing contracts"))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_alias_collisions() {
+        // Chain 1 has its own uniswap contracts, folding 8453 into it collides on the names
+        // both have at different addresses
+        let issues = validate(
+            "tests/fixtures/balancer",
+            "tests/fixtures/uniswap/deployments",
+            &ChainAliases::from([(8453, 1)]),
+        );
+
+        assert!(
+            issues
+                .iter()
+                .any(|issue| issue.message.contains("on aliased chain 8453")),
+            "{:?}",
+            issues
+        );
+    }
+}
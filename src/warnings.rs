@@ -0,0 +1,304 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{
+    address::{is_valid_address, is_zero_address},
+    chains,
+    types::{ChainId, ProtocolDeployments},
+};
+
+pub const WARNINGS_FILE_NAME: &str = "warnings.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WarningKind {
+    /// Source contract matching no protocol config
+    UnassignedContract,
+    /// Contracts missing for a protocol that doesn't enforce completeness
+    MissingContracts,
+    /// Suspicious value in the parsed data, such as an invalid or zero address
+    Anomaly,
+    /// Chain id missing from the bundled known-chains table
+    UnknownChain,
+    /// Chain-prefixed address whose prefix names another chain than its file
+    ChainMismatch,
+    /// Source entry left out because it couldn't be classified
+    Quarantined,
+    /// Contracts of one protocol on one chain sharing an address, usually a copy-paste error
+    DuplicateAddress,
+    /// Source entry of a version the parser doesn't know yet, parsed like the known ones
+    UnknownVersion,
+    /// Source contracts of a protocol renaming to the same canonical name, only one is kept
+    RenameCollision,
+    /// Network of a source left out, e.g. because it's disabled
+    SkippedNetwork,
+    /// Source deployment with a status the parser doesn't know, left out
+    UnknownStatus,
+    /// Contract at different addresses on a chain and on a chain aliased to it
+    AliasCollision,
+    /// Newest deployment of the source older than `--max-age`
+    StaleSource,
+}
+
+/// Soft issue found during a run, which doesn't fail it. Ordered by kind, protocol, chain
+/// and detail, as written to `warnings.json`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub protocol: Option<String>,
+    pub chain_id: Option<ChainId>,
+    pub detail: String,
+}
+
+/// Invalid and zero addresses of the deployments, ordered by chain
+pub fn address_anomalies(protocol_deployments: &ProtocolDeployments) -> Vec<Warning> {
+    let mut chain_ids: Vec<_> = protocol_deployments.chains.keys().collect();
+    chain_ids.sort();
+
+    let mut warnings = Vec::new();
+
+    for chain_id in chain_ids {
+        for (name, address) in &protocol_deployments.chains[chain_id] {
+            let problem = if !is_valid_address(address) {
+                "invalid address"
+            } else if is_zero_address(address) {
+                "zero address"
+            } else {
+                continue;
+            };
+
+            warnings.push(Warning {
+                kind: WarningKind::Anomaly,
+                protocol: Some(protocol_deployments.protocol_name.clone()),
+                chain_id: Some(*chain_id),
+                detail: format!("{} '{}' for contract '{}'", problem, address, name),
+            });
+        }
+    }
+
+    warnings
+}
+
+/// Contract names sharing an address within a chain of the protocol, ordered by chain
+pub fn duplicate_addresses(protocol_deployments: &ProtocolDeployments) -> Vec<Warning> {
+    let mut chain_ids: Vec<_> = protocol_deployments.chains.keys().collect();
+    chain_ids.sort();
+
+    let mut warnings = Vec::new();
+
+    for chain_id in chain_ids {
+        let mut names_by_address: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        for (name, address) in &protocol_deployments.chains[chain_id] {
+            names_by_address
+                .entry(address.to_lowercase())
+                .or_default()
+                .push(name);
+        }
+
+        for (address, names) in names_by_address {
+            if names.len() < 2 {
+                continue;
+            }
+
+            warnings.push(Warning {
+                kind: WarningKind::DuplicateAddress,
+                protocol: Some(protocol_deployments.protocol_name.clone()),
+                chain_id: Some(*chain_id),
+                detail: format!("contracts {} share address '{}'", names.join(", "), address),
+            });
+        }
+    }
+
+    warnings
+}
+
+/// Required contracts each chain of the deployments lacks, ordered by chain
+pub fn missing_contracts(protocol_deployments: &ProtocolDeployments) -> Vec<Warning> {
+    let mut chain_ids: Vec<_> = protocol_deployments.chains.keys().collect();
+    chain_ids.sort();
+
+    chain_ids
+        .into_iter()
+        .filter_map(|chain_id| {
+            let missing = protocol_deployments.missing_contracts(*chain_id);
+            if missing.is_empty() {
+                return None;
+            }
+
+            let names: Vec<&str> = missing.iter().map(|name| name.as_str()).collect();
+            Some(Warning {
+                kind: WarningKind::MissingContracts,
+                protocol: Some(protocol_deployments.protocol_name.clone()),
+                chain_id: Some(*chain_id),
+                detail: format!("missing contracts: {}", names.join(", ")),
+            })
+        })
+        .collect()
+}
+
+/// Chain ids of the deployments missing from the known-chains table, ordered by id
+pub fn unknown_chains(protocol_deployments: &ProtocolDeployments) -> Vec<Warning> {
+    let mut chain_ids: Vec<_> = protocol_deployments
+        .chains
+        .keys()
+        .filter(|chain_id| !chains::is_known(**chain_id))
+        .collect();
+    chain_ids.sort();
+
+    chain_ids
+        .into_iter()
+        .map(|chain_id| Warning {
+            kind: WarningKind::UnknownChain,
+            protocol: Some(protocol_deployments.protocol_name.clone()),
+            chain_id: Some(*chain_id),
+            detail: format!("unrecognized chain id {}", chain_id),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::types::ChainContracts;
+
+    #[test]
+    fn test_address_anomalies() {
+        let contracts = ChainContracts::from([
+            ("Broken".to_string(), "0x1234".to_string()),
+            (
+                "Permit2".to_string(),
+                "0x000000000022D473030F116dDEE9F6B43aC78BA3".to_string(),
+            ),
+            (
+                "Zero".to_string(),
+                "0x0000000000000000000000000000000000000000".to_string(),
+            ),
+        ]);
+        let deployments = ProtocolDeployments::new("permit2", HashMap::from([(1, contracts)]));
+
+        let warnings = address_anomalies(&deployments);
+
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(
+            warnings[0].detail,
+            "invalid address '0x1234' for contract 'Broken'"
+        );
+        assert!(warnings[1].detail.starts_with("zero address"));
+        assert_eq!(
+            serde_json::to_value(&warnings[1]).unwrap(),
+            serde_json::json!({
+                "kind": "anomaly",
+                "protocol": "permit2",
+                "chain_id": 1,
+                "detail": "zero address '0x0000000000000000000000000000000000000000' for contract 'Zero'"
+            })
+        );
+    }
+
+    #[test]
+    fn test_duplicate_addresses() {
+        let contracts = ChainContracts::from([
+            ("Router".to_string(), "0xAbC1".to_string()),
+            ("BatchRouter".to_string(), "0xabc1".to_string()),
+            ("Vault".to_string(), "0xabc2".to_string()),
+        ]);
+        let other_chain = ChainContracts::from([("Router".to_string(), "0xabc1".to_string())]);
+        let deployments = ProtocolDeployments::new(
+            "balancer-v3",
+            HashMap::from([(1, contracts), (10, other_chain)]),
+        );
+
+        let warnings = duplicate_addresses(&deployments);
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::DuplicateAddress);
+        assert_eq!(warnings[0].chain_id, Some(1));
+        assert_eq!(
+            warnings[0].detail,
+            "contracts BatchRouter, Router share address '0xabc1'"
+        );
+    }
+
+    #[test]
+    fn test_missing_contracts_per_chain() {
+        let mut deployments = ProtocolDeployments::new(
+            "balancer-v3",
+            HashMap::from([
+                (
+                    1,
+                    ChainContracts::from([("Vault".to_string(), "0x01".to_string())]),
+                ),
+                (
+                    10,
+                    ChainContracts::from([("Router".to_string(), "0x02".to_string())]),
+                ),
+            ]),
+        );
+        deployments.required_contracts = vec!["Vault".to_string()];
+
+        let warnings = missing_contracts(&deployments);
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::MissingContracts);
+        assert_eq!(warnings[0].chain_id, Some(10));
+        assert_eq!(warnings[0].detail, "missing contracts: Vault");
+    }
+
+    #[test]
+    fn test_warnings_sort_by_kind_protocol_chain_and_detail() {
+        let warning = |kind, chain_id, detail: &str| Warning {
+            kind,
+            protocol: None,
+            chain_id: Some(chain_id),
+            detail: detail.to_string(),
+        };
+        let mut warnings = [
+            warning(WarningKind::Anomaly, 1, "b"),
+            warning(WarningKind::UnassignedContract, 10, "a"),
+            warning(WarningKind::Anomaly, 1, "a"),
+            warning(WarningKind::UnassignedContract, 1, "z"),
+        ];
+
+        warnings.sort();
+
+        let order: Vec<_> = warnings
+            .iter()
+            .map(|warning| {
+                (
+                    warning.kind,
+                    warning.chain_id.unwrap(),
+                    warning.detail.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (WarningKind::UnassignedContract, 1, "z"),
+                (WarningKind::UnassignedContract, 10, "a"),
+                (WarningKind::Anomaly, 1, "a"),
+                (WarningKind::Anomaly, 1, "b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_chains() {
+        let deployments = ProtocolDeployments::new(
+            "permit2",
+            HashMap::from([
+                (1, ChainContracts::new()),
+                (123456789, ChainContracts::new()),
+            ]),
+        );
+
+        let warnings = unknown_chains(&deployments);
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::UnknownChain);
+        assert_eq!(warnings[0].chain_id, Some(123456789));
+    }
+}
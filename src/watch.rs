@@ -0,0 +1,226 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use notify_debouncer_full::{
+    DebounceEventResult, DebouncedEvent, Debouncer, RecommendedCache, new_debouncer,
+    notify::{self, EventKind, RecommendedWatcher, RecursiveMode},
+};
+use thiserror::Error;
+use tracing::debug;
+
+/// Quiet time after the last change before `--watch` reparses, so a checkout touching many
+/// files triggers a single reparse
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Error)]
+pub enum WatchError {
+    #[error("Failed to watch the sources: {0}")]
+    Notify(#[from] notify::Error),
+
+    #[error("Failed to watch the sources: {}", errors.join("; "))]
+    Events { errors: Vec<String> },
+
+    #[error("Failed to resolve a watched path: {0}")]
+    Path(#[from] std::io::Error),
+
+    #[error("The file watcher stopped")]
+    Stopped,
+}
+
+/// A watched source path, with the forms the platform may report its changes under
+struct Root {
+    path: PathBuf,
+    reported_as: Vec<PathBuf>,
+}
+
+impl Root {
+    /// `changed` as a path under this root, as it was given, if it is one
+    fn relate(&self, changed: &Path) -> Option<PathBuf> {
+        self.reported_as.iter().find_map(|reported_as| {
+            let relative = changed.strip_prefix(reported_as).ok()?;
+            Some(match relative.as_os_str().is_empty() {
+                true => self.path.clone(),
+                false => self.path.join(relative),
+            })
+        })
+    }
+}
+
+/// Watches source files and folders with the platform's file notifications, reporting the
+/// changed paths once no further change came in for the debounce delay
+pub struct Watcher {
+    roots: Vec<Root>,
+    events: Receiver<DebounceEventResult>,
+    // Stops watching when dropped
+    _debouncer: Debouncer<RecommendedWatcher, RecommendedCache>,
+}
+
+impl Watcher {
+    /// Watches the files and folders at `roots`, skipping the ones that don't exist
+    pub fn new(
+        roots: impl IntoIterator<Item = impl Into<PathBuf>>,
+        debounce: Duration,
+    ) -> Result<Self, WatchError> {
+        let (sender, events) = mpsc::channel();
+        let mut debouncer = new_debouncer(debounce, None, sender)?;
+
+        let mut watched = Vec::new();
+        for path in roots.into_iter().map(Into::into) {
+            let Ok(metadata) = fs::metadata(&path) else {
+                debug!(path = %path.display(), "Not watching missing source");
+                continue;
+            };
+
+            // A file is watched through its folder, so replacing it, as editors and `git`
+            // do, doesn't end the watch
+            let reported_as = [std::path::absolute(&path)?, fs::canonicalize(&path)?];
+            if metadata.is_dir() {
+                debouncer.watch(&reported_as[0], RecursiveMode::Recursive)?;
+            } else if let Some(folder) = reported_as[0].parent() {
+                debouncer.watch(folder, RecursiveMode::NonRecursive)?;
+            }
+
+            watched.push(Root {
+                path,
+                reported_as: reported_as.into(),
+            });
+        }
+
+        Ok(Self {
+            roots: watched,
+            events,
+            _debouncer: debouncer,
+        })
+    }
+
+    /// Blocks until source paths changed, returning them under the roots they were given as
+    pub fn next_change(&self) -> Result<Vec<PathBuf>, WatchError> {
+        loop {
+            let events = self.events.recv().map_err(|_| WatchError::Stopped)?;
+            let changed = self.changed_paths(events)?;
+            if !changed.is_empty() {
+                return Ok(changed);
+            }
+        }
+    }
+
+    /// Like [`Self::next_change`], giving up once `timeout` passed without a change
+    pub fn next_change_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<Option<Vec<PathBuf>>, WatchError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let events = match self.events.recv_timeout(remaining) {
+                Ok(events) => events,
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => return Err(WatchError::Stopped),
+            };
+            let changed = self.changed_paths(events)?;
+            if !changed.is_empty() {
+                return Ok(Some(changed));
+            }
+        }
+    }
+
+    /// Paths under a root that `events` modified, ordered and deduplicated. Reads, which the
+    /// parse itself causes, and folders, reported along with the files changing in them, are
+    /// left out.
+    fn changed_paths(&self, events: DebounceEventResult) -> Result<Vec<PathBuf>, WatchError> {
+        let events = events.map_err(|errors| WatchError::Events {
+            errors: errors.iter().map(ToString::to_string).collect(),
+        })?;
+
+        let mut changed: Vec<_> = events
+            .iter()
+            .filter(|event| !matches!(event.kind, EventKind::Access(_)))
+            .flat_map(|event: &DebouncedEvent| &event.paths)
+            .filter(|path| !path.is_dir())
+            .filter_map(|path| self.roots.iter().find_map(|root| root.relate(path)))
+            .collect();
+        changed.sort();
+        changed.dedup();
+
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Registry;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "evm-dex-index-watch-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_file_root_only_reports_its_own_changes() {
+        let dir = temp_dir("file-root");
+        let csv = dir.join("extra.csv");
+        fs::write(&csv, "protocol,chain_id,contract,address\n").unwrap();
+        let watcher = Watcher::new([&csv, &dir.join("missing")], DEBOUNCE).unwrap();
+
+        fs::write(dir.join("notes.txt"), "not a source").unwrap();
+        fs::write(&csv, "protocol,chain_id,contract,address\n\n").unwrap();
+
+        assert_eq!(
+            watcher.next_change_timeout(TIMEOUT).unwrap(),
+            Some(vec![csv])
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_changed_source_file_triggers_reparse() {
+        let repo = temp_dir("balancer");
+        fs::create_dir_all(repo.join("addresses")).unwrap();
+        for file_name in [".supported-networks.json", "base.json", "mainnet.json"] {
+            fs::copy(
+                format!("tests/fixtures/balancer/addresses/{}", file_name),
+                repo.join("addresses").join(file_name),
+            )
+            .unwrap();
+        }
+        let builder = Registry::builder().balancer_path(repo.to_string_lossy());
+        let mut registry = builder.run().unwrap();
+        let watcher = Watcher::new([&repo], DEBOUNCE).unwrap();
+
+        let moved = "0x00000000000000000000000000000000000000aa";
+        let mainnet = repo.join("addresses/mainnet.json");
+        let contents = fs::read_to_string(&mainnet)
+            .unwrap()
+            .replace("0xBA12222222228d8Ba445958a75a0704d566BF2C8", moved);
+        fs::write(&mainnet, contents).unwrap();
+
+        let changed = watcher.next_change_timeout(TIMEOUT).unwrap().unwrap();
+        assert_eq!(changed, vec![mainnet.clone()]);
+
+        let changed: Vec<_> = changed.iter().map(|path| path.to_string_lossy()).collect();
+        let changed: Vec<&str> = changed.iter().map(AsRef::as_ref).collect();
+        builder
+            .reparse_changed(&mut registry, &changed, &mut Vec::new())
+            .unwrap();
+        assert_eq!(
+            registry.get("balancer-v2").unwrap().chains[&1]["Vault"],
+            moved
+        );
+
+        fs::remove_dir_all(repo).unwrap();
+    }
+}
@@ -1,31 +0,0 @@
-use std::{
-    fs::{self, File},
-    io::BufWriter,
-    path::Path,
-};
-
-use crate::types::ProtocolDeployments;
-
-pub fn write(
-    folder: &str,
-    protocol_deployments: ProtocolDeployments,
-) -> Result<(), std::io::Error> {
-    if !Path::new(folder).exists() {
-        fs::create_dir_all(folder)?;
-    }
-
-    let protocol_path = format!("{}/{}", folder, protocol_deployments.protocol_name);
-
-    if !Path::new(&protocol_path).exists() {
-        fs::create_dir_all(&protocol_path)?;
-    }
-
-    for (chain_id, contracts) in protocol_deployments.chains {
-        let path = format!("{}/{}.json", protocol_path, chain_id);
-        let file = File::create(path)?;
-        let mut writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(&mut writer, &contracts)?;
-    }
-
-    Ok(())
-}
@@ -0,0 +1,69 @@
+use std::{fs, path::Path};
+
+use csv::{QuoteStyle, WriterBuilder};
+
+use super::rows::rows;
+use crate::{chains, types::ProtocolDeployments};
+
+pub const CSV_FILE_NAME: &str = "deployments.csv";
+
+/// Writes every contract as a `protocol,chain_id,chain_name,contract,address` row of a
+/// single CSV file. Text fields are always quoted, numbers never.
+pub fn write(
+    folder: &str,
+    protocols: &[ProtocolDeployments],
+) -> Result<Vec<String>, std::io::Error> {
+    if !Path::new(folder).exists() {
+        fs::create_dir_all(folder)?;
+    }
+
+    let mut writer = WriterBuilder::new()
+        .quote_style(QuoteStyle::NonNumeric)
+        .from_path(format!("{}/{}", folder, CSV_FILE_NAME))?;
+
+    writer.write_record(["protocol", "chain_id", "chain_name", "contract", "address"])?;
+    for row in rows(protocols) {
+        writer.write_record([
+            row.protocol,
+            &row.chain_id.to_string(),
+            chains::name(row.chain_id).unwrap_or_default(),
+            row.contract_name,
+            row.address,
+        ])?;
+    }
+    writer.flush()?;
+
+    Ok(vec![CSV_FILE_NAME.to_string()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uniswap;
+
+    #[test]
+    fn test_csv_header_and_row() {
+        let folder = std::env::temp_dir()
+            .join(format!("evm-dex-index-csv-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = fs::remove_dir_all(&folder);
+        let protocols =
+            uniswap::parse("tests/fixtures/uniswap/deployments", &mut Vec::new()).unwrap();
+
+        write(&folder, &protocols).unwrap();
+
+        let contents = fs::read_to_string(format!("{}/{}", folder, CSV_FILE_NAME)).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(
+            lines[0],
+            r#""protocol","chain_id","chain_name","contract","address""#
+        );
+        assert!(lines.contains(
+            &r#""permit2",8453,"Base","Permit2","0x000000000022D473030F116dDEE9F6B43aC78BA3""#
+        ));
+        assert_eq!(lines.len(), 41);
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
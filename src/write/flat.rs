@@ -0,0 +1,158 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+use super::{JsonOptions, json};
+use crate::types::{ChainContracts, ChainId, ContractAddress, ProtocolDeployments};
+
+pub const FLAT_FOLDER: &str = "flat";
+
+/// Protocols defining each contract name of a chain, with the address they define it with
+type NameOccurrences<'a> = BTreeMap<&'a str, Vec<(&'a str, &'a ContractAddress)>>;
+
+/// Merges every protocol's contracts per chain, without protocol nesting.
+///
+/// A name used by several protocols on the same chain stays as is when they all agree on the
+/// address, otherwise each occurrence is namespaced as `<protocol>:<name>`.
+pub fn merge_by_chain(protocols: &[ProtocolDeployments]) -> BTreeMap<ChainId, ChainContracts> {
+    let mut occurrences: BTreeMap<ChainId, NameOccurrences> = BTreeMap::new();
+
+    for protocol in protocols {
+        for (chain_id, contracts) in &protocol.chains {
+            let chain = occurrences.entry(*chain_id).or_default();
+            for (name, address) in contracts {
+                chain
+                    .entry(name.as_str())
+                    .or_default()
+                    .push((protocol.protocol_name.as_str(), address));
+            }
+        }
+    }
+
+    occurrences
+        .into_iter()
+        .map(|(chain_id, names)| {
+            let mut contracts = ChainContracts::new();
+
+            for (name, occurrences) in names {
+                let addresses: BTreeSet<&ContractAddress> =
+                    occurrences.iter().map(|(_, address)| *address).collect();
+
+                if addresses.len() == 1 {
+                    contracts.insert(name.to_string(), occurrences[0].1.clone());
+                    continue;
+                }
+
+                for (protocol_name, address) in occurrences {
+                    contracts.insert(format!("{}:{}", protocol_name, name), address.clone());
+                }
+            }
+
+            (chain_id, contracts)
+        })
+        .collect()
+}
+
+/// Writes `flat/<chain_id>.json` per chain, returning paths relative to `folder`
+pub fn write(
+    folder: &str,
+    protocols: &[ProtocolDeployments],
+    json_options: &JsonOptions,
+) -> Result<Vec<String>, std::io::Error> {
+    let flat_path = format!("{}/{}", folder, FLAT_FOLDER);
+
+    if !Path::new(&flat_path).exists() {
+        fs::create_dir_all(&flat_path)?;
+    }
+
+    let mut written = Vec::new();
+
+    for (chain_id, contracts) in merge_by_chain(protocols) {
+        let path = format!("{}/{}.json", flat_path, chain_id);
+        json::write_file(path, &contracts, json_options)?;
+        written.push(format!("{}/{}.json", FLAT_FOLDER, chain_id));
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn protocol(
+        protocol_name: &str,
+        chain_id: ChainId,
+        contracts: &[(&str, &str)],
+    ) -> ProtocolDeployments {
+        let contracts = contracts
+            .iter()
+            .map(|(name, address)| (name.to_string(), address.to_string()))
+            .collect();
+
+        ProtocolDeployments::new(protocol_name, HashMap::from([(chain_id, contracts)]))
+    }
+
+    #[test]
+    fn test_merge_two_protocols_on_one_chain() {
+        let protocols = [
+            protocol(
+                "uniswap-v3",
+                1,
+                &[(
+                    "UniswapV3Factory",
+                    "0x1F98431c8aD98523631AE4a59f267346ea31F984",
+                )],
+            ),
+            protocol(
+                "permit2",
+                1,
+                &[("Permit2", "0x000000000022D473030F116dDEE9F6B43aC78BA3")],
+            ),
+        ];
+
+        let flat = merge_by_chain(&protocols);
+
+        assert_eq!(flat.len(), 1);
+        assert_eq!(
+            flat[&1],
+            ChainContracts::from([
+                (
+                    "Permit2".to_string(),
+                    "0x000000000022D473030F116dDEE9F6B43aC78BA3".to_string()
+                ),
+                (
+                    "UniswapV3Factory".to_string(),
+                    "0x1F98431c8aD98523631AE4a59f267346ea31F984".to_string()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_merge_namespaces_conflicting_names() {
+        let protocols = [
+            protocol(
+                "balancer-v2",
+                1,
+                &[("Vault", "0xBA12222222228d8Ba445958a75a0704d566BF2C8")],
+            ),
+            protocol(
+                "balancer-v3",
+                1,
+                &[("Vault", "0xbA1333333333a1BA1108E8412f11850A5C319bA9")],
+            ),
+        ];
+
+        let flat = merge_by_chain(&protocols);
+
+        assert_eq!(
+            flat[&1].keys().collect::<Vec<_>>(),
+            ["balancer-v2:Vault", "balancer-v3:Vault"]
+        );
+    }
+}
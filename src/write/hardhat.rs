@@ -0,0 +1,138 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+};
+
+use serde::Serialize;
+
+use super::{
+    json::{self, JsonOptions},
+    typescript::read_abi,
+};
+use crate::{
+    chains,
+    types::{ChainId, ProtocolDeployments},
+};
+
+pub const HARDHAT_FOLDER_NAME: &str = "deployments";
+
+const CHAIN_ID_FILE_NAME: &str = ".chainId";
+
+/// A deployment file as hardhat-deploy reads it
+#[derive(Serialize)]
+struct HardhatDeployment<'a> {
+    address: &'a str,
+    abi: serde_json::Value,
+}
+
+/// Writes `deployments/<network>/<Contract>.json` with the address and ABI (from `abis_dir`,
+/// empty otherwise) of every contract, and the chain id in `deployments/<network>/.chainId`.
+/// A name several protocols use on the chain becomes `<protocol>_<Contract>`.
+pub fn write(
+    folder: &str,
+    protocols: &[ProtocolDeployments],
+    abis_dir: Option<&str>,
+    json_options: &JsonOptions,
+) -> Result<Vec<String>, std::io::Error> {
+    let mut chains: BTreeMap<ChainId, Vec<(&str, &str, &str)>> = BTreeMap::new();
+    for protocol_deployments in protocols {
+        for (chain_id, contracts) in &protocol_deployments.chains {
+            let chain = chains.entry(*chain_id).or_default();
+            for (name, address) in contracts {
+                chain.push((&protocol_deployments.protocol_name, name, address));
+            }
+        }
+    }
+
+    let mut written = Vec::new();
+    for (chain_id, mut contracts) in chains {
+        contracts.sort();
+        let network = format!("{}/{}", HARDHAT_FOLDER_NAME, chains::network_name(chain_id));
+        fs::create_dir_all(format!("{}/{}", folder, network))?;
+
+        let chain_id_path = format!("{}/{}", network, CHAIN_ID_FILE_NAME);
+        fs::write(
+            format!("{}/{}", folder, chain_id_path),
+            chain_id.to_string(),
+        )?;
+        written.push(chain_id_path);
+
+        let mut name_counts: HashMap<&str, usize> = HashMap::new();
+        for (_, name, _) in &contracts {
+            *name_counts.entry(name).or_default() += 1;
+        }
+
+        for (protocol_name, name, address) in contracts {
+            let abi = match abis_dir {
+                Some(abis_dir) => read_abi(abis_dir, name)?,
+                None => None,
+            };
+            let deployment = HardhatDeployment {
+                address,
+                abi: abi.unwrap_or_else(|| serde_json::Value::Array(Vec::new())),
+            };
+
+            let file_name = if name_counts[name] > 1 {
+                format!("{}_{}", protocol_name, name)
+            } else {
+                name.to_string()
+            };
+            let path = format!("{}/{}.json", network, file_name);
+            json::write_file(format!("{}/{}", folder, path), &deployment, json_options)?;
+            written.push(path);
+        }
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{types::ChainContracts, uniswap};
+
+    #[test]
+    fn test_writes_network_folder_per_chain() {
+        let folder = std::env::temp_dir()
+            .join(format!("evm-dex-index-hardhat-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = fs::remove_dir_all(&folder);
+        let mut protocols =
+            uniswap::parse("tests/fixtures/uniswap/deployments", &mut Vec::new()).unwrap();
+        protocols.push(ProtocolDeployments::new(
+            "fork-v3",
+            HashMap::from([(
+                1,
+                ChainContracts::from([("SwapRouter".to_string(), "0x02".to_string())]),
+            )]),
+        ));
+
+        let written = write(&folder, &protocols, None, &JsonOptions::default()).unwrap();
+
+        let mainnet = format!("{}/deployments/mainnet", folder);
+        assert_eq!(
+            fs::read_to_string(format!("{}/.chainId", mainnet)).unwrap(),
+            "1"
+        );
+        let factory: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(format!("{}/UniswapV3Factory.json", mainnet)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            factory,
+            serde_json::json!({
+                "address": "0x1F98431c8aD98523631AE4a59f267346ea31F984",
+                "abi": []
+            })
+        );
+        // Both protocols have a SwapRouter on mainnet
+        assert!(written.contains(&"deployments/mainnet/fork-v3_SwapRouter.json".to_string()));
+        assert!(written.contains(&"deployments/mainnet/uniswap-v3_SwapRouter.json".to_string()));
+        assert!(written.contains(&"deployments/base/.chainId".to_string()));
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+use std::{fs, path::Path, str::FromStr};
+
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+
+/// How JSON output files are serialized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// Escape every non-ASCII character as `\uXXXX`
+    pub ascii_only: bool,
+    pub indent: Indent,
+}
+
+/// Indentation of each nesting level of pretty-printed JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    Spaces(u8),
+    Tab,
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Self::Spaces(2)
+    }
+}
+
+impl Indent {
+    fn as_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Spaces(count) => vec![b' '; *count as usize],
+            Self::Tab => vec![b'\t'],
+        }
+    }
+}
+
+/// `tab`, or a number of spaces, e.g. `4`
+impl FromStr for Indent {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value == "tab" {
+            return Ok(Self::Tab);
+        }
+
+        value
+            .parse()
+            .map(Self::Spaces)
+            .map_err(|_| format!("expected `tab` or a number of spaces, got '{}'", value))
+    }
+}
+
+/// Pretty-prints `value` into a new file at `path`
+pub fn write_file<T: Serialize + ?Sized>(
+    path: impl AsRef<Path>,
+    value: &T,
+    options: &JsonOptions,
+) -> Result<(), std::io::Error> {
+    let indent = options.indent.as_bytes();
+    let mut serializer =
+        serde_json::Serializer::with_formatter(Vec::new(), PrettyFormatter::with_indent(&indent));
+    value.serialize(&mut serializer)?;
+    let mut json =
+        String::from_utf8(serializer.into_inner()).expect("serde_json only writes valid UTF-8");
+
+    if options.ascii_only {
+        json = escape_non_ascii(&json);
+    }
+
+    fs::write(path, json)
+}
+
+/// Non-ASCII characters only occur inside JSON strings, so escaping them anywhere is safe
+fn escape_non_ascii(json: &str) -> String {
+    let mut escaped = String::with_capacity(json.len());
+
+    for c in json.chars() {
+        if c.is_ascii() {
+            escaped.push(c);
+            continue;
+        }
+
+        let mut utf16 = [0; 2];
+        for unit in c.encode_utf16(&mut utf16) {
+            escaped.push_str(&format!("\\u{:04x}", unit));
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn test_ascii_only_escapes_non_ascii_contract_names() {
+        let path = std::env::temp_dir().join(format!(
+            "evm-dex-index-ascii-only-{}.json",
+            std::process::id()
+        ));
+        let contracts = BTreeMap::from([("Vaultё🦄", "0x01")]);
+
+        let options = JsonOptions {
+            ascii_only: true,
+            ..JsonOptions::default()
+        };
+        write_file(&path, &contracts, &options).unwrap();
+
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(written.is_ascii());
+        assert!(written.contains(r#""Vault\u0451\ud83e\udd84": "0x01""#));
+        let parsed: BTreeMap<String, String> = serde_json::from_str(&written).unwrap();
+        assert_eq!(parsed["Vaultё🦄"], "0x01");
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_default_keeps_non_ascii() {
+        assert_eq!(escape_non_ascii("\"a\""), "\"a\"");
+
+        let path = std::env::temp_dir().join(format!(
+            "evm-dex-index-non-ascii-{}.json",
+            std::process::id()
+        ));
+
+        write_file(
+            &path,
+            &BTreeMap::from([("Vaultё", "0x01")]),
+            &JsonOptions::default(),
+        )
+        .unwrap();
+
+        assert!(fs::read_to_string(&path).unwrap().contains("Vaultё"));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_indent_is_configurable() {
+        assert_eq!("tab".parse(), Ok(Indent::Tab));
+        assert!("wide".parse::<Indent>().is_err());
+
+        let path =
+            std::env::temp_dir().join(format!("evm-dex-index-indent-{}.json", std::process::id()));
+        let contracts = BTreeMap::from([("Vault", BTreeMap::from([("address", "0x01")]))]);
+        let options = JsonOptions {
+            indent: "4".parse().unwrap(),
+            ..JsonOptions::default()
+        };
+
+        write_file(&path, &contracts, &options).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n    \"Vault\": {\n        \"address\": \"0x01\"\n    }\n}"
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,90 @@
+/// Where the per-protocol files of the `json` format are placed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Layout {
+    /// `<protocol>/<chain_id>.json`, one folder per protocol
+    #[default]
+    Nested,
+    /// `<protocol>__<chain_id>.json`, every file in the output folder itself
+    FlatFiles,
+}
+
+impl Layout {
+    /// Path relative to the output folder of the file with the nested `relative_path`
+    pub fn file_path(self, relative_path: &str) -> String {
+        match self {
+            Layout::Nested => relative_path.to_string(),
+            Layout::FlatFiles => encode_flat_file_name(relative_path),
+        }
+    }
+}
+
+/// Joins the path components with `__`, escaping `_` as `_u` so the name decodes unambiguously
+pub fn encode_flat_file_name(relative_path: &str) -> String {
+    let mut encoded = String::with_capacity(relative_path.len());
+
+    for c in relative_path.chars() {
+        match c {
+            '_' => encoded.push_str("_u"),
+            '/' => encoded.push_str("__"),
+            c => encoded.push(c),
+        }
+    }
+
+    encoded
+}
+
+/// Nested path of a flat file name, `None` if it isn't a valid encoding
+pub fn decode_flat_file_name(file_name: &str) -> Option<String> {
+    let mut decoded = String::with_capacity(file_name.len());
+    let mut chars = file_name.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '_' {
+            decoded.push(c);
+            continue;
+        }
+
+        match chars.next()? {
+            '_' => decoded.push('/'),
+            'u' => decoded.push('_'),
+            _ => return None,
+        }
+    }
+
+    Some(decoded)
+}
+
+/// Path of a written file as in the nested layout, decoding flat file names
+pub fn nested_path(file_path: &str) -> String {
+    if file_path.contains('/') {
+        return file_path.to_string();
+    }
+
+    decode_flat_file_name(file_path).unwrap_or_else(|| file_path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flat_file_names_round_trip() {
+        for path in [
+            "uniswap-v3/1.json",
+            "balancer-v3/enriched/8453.json",
+            "some_protocol/latest.json",
+            "a_/_b__c.json",
+        ] {
+            let encoded = encode_flat_file_name(path);
+            assert!(!encoded.contains('/'));
+            assert_eq!(decode_flat_file_name(&encoded).as_deref(), Some(path));
+        }
+
+        assert_eq!(
+            encode_flat_file_name("uniswap-v3/1.json"),
+            "uniswap-v3__1.json"
+        );
+        assert_eq!(decode_flat_file_name("bad_x.json"), None);
+    }
+}
@@ -0,0 +1,579 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::BufReader,
+    path::Path,
+};
+
+use serde::Serialize;
+
+use crate::{
+    groups, pattern,
+    progress::ProgressEvent,
+    types::{ChainContracts, ChainSignatures, EnrichedChainContracts, ProtocolDeployments},
+};
+
+mod csv;
+mod flat;
+mod hardhat;
+mod json;
+mod layout;
+mod ndjson;
+#[cfg(feature = "parquet")]
+mod parquet;
+mod pointer;
+mod rows;
+mod subgraph;
+mod typescript;
+mod yaml;
+
+pub use hardhat::HARDHAT_FOLDER_NAME;
+pub use json::{Indent, JsonOptions};
+pub use layout::{Layout, decode_flat_file_name, encode_flat_file_name, nested_path};
+pub use ndjson::NDJSON_FILE_NAME;
+pub use pointer::{LATEST_FILE_NAME, LatestPointer};
+pub use subgraph::SUBGRAPH_FOLDER_NAME;
+pub use typescript::TYPESCRIPT_FILE_NAME;
+
+/// Key of the enriched chain files mapping each contract to its deployment signature
+pub const SOURCES_KEY: &str = "_sources";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum OutputFormat {
+    /// `<protocol>/<chain_id>.json` per protocol and chain
+    #[default]
+    Json,
+    /// `flat/<chain_id>.json` with every protocol's contracts merged per chain
+    FlatByChain,
+    /// Single `deployments.csv` with a row per contract
+    Csv,
+    /// Single `deployments.ndjson` with an object per contract on each line, for streaming
+    Ndjson,
+    /// Single `deployments.ts` module with an `as const` object per protocol, for wagmi / abitype
+    #[cfg_attr(feature = "cli", value(alias = "ts"))]
+    Typescript,
+    /// `deployments/<network>/<Contract>.json` and `.chainId` per chain, for hardhat-deploy
+    Hardhat,
+    /// `<protocol>/<chain_id>.yaml` per protocol and chain, the YAML version of `Json`
+    Yaml,
+    /// `subgraph/<protocol>.yaml` with subgraph manifest data sources, lowercased addresses and
+    /// `startBlock` where known
+    Subgraph,
+    /// Single `deployments.parquet` table with a row per contract
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+/// Writes all protocols in the given format, returning paths relative to `folder`.
+/// `layout` places the files of `Json`, `abis_dir` holds ABIs inlined by `Typescript`.
+/// Reports every written file to `on_progress`, protocol by protocol for `Json`.
+pub fn write_all(
+    folder: &str,
+    format: OutputFormat,
+    layout: Layout,
+    abis_dir: Option<&str>,
+    json_options: &JsonOptions,
+    protocols: Vec<ProtocolDeployments>,
+    on_progress: &mut dyn FnMut(ProgressEvent),
+) -> Result<Vec<String>, std::io::Error> {
+    let written = match format {
+        OutputFormat::Json => {
+            let mut written = Vec::new();
+            for protocol_deployments in protocols {
+                let protocol_written =
+                    write_with_layout(folder, protocol_deployments, json_options, layout)?;
+                for path in &protocol_written {
+                    on_progress(ProgressEvent::FileWritten { path: path.clone() });
+                }
+                written.extend(protocol_written);
+            }
+            return Ok(written);
+        }
+        OutputFormat::FlatByChain => flat::write(folder, &protocols, json_options)?,
+        OutputFormat::Csv => csv::write(folder, &protocols)?,
+        OutputFormat::Ndjson => ndjson::write(folder, &protocols)?,
+        OutputFormat::Typescript => typescript::write(folder, &protocols, abis_dir)?,
+        OutputFormat::Hardhat => hardhat::write(folder, &protocols, abis_dir, json_options)?,
+        OutputFormat::Subgraph => subgraph::write(folder, &protocols)?,
+        OutputFormat::Yaml => yaml::write(folder, &protocols)?,
+        #[cfg(feature = "parquet")]
+        OutputFormat::Parquet => parquet::write(folder, &protocols)?,
+    };
+
+    for path in &written {
+        on_progress(ProgressEvent::FileWritten { path: path.clone() });
+    }
+
+    Ok(written)
+}
+
+/// Like `write_all`, writing the protocols named in `formats` in their own format and every
+/// other one in `default_format`. Each format is written once, for all of its protocols.
+#[allow(clippy::too_many_arguments)]
+pub fn write_all_by_protocol(
+    folder: &str,
+    default_format: OutputFormat,
+    formats: &[(String, OutputFormat)],
+    layout: Layout,
+    abis_dir: Option<&str>,
+    json_options: &JsonOptions,
+    protocols: Vec<ProtocolDeployments>,
+    on_progress: &mut dyn FnMut(ProgressEvent),
+) -> Result<Vec<String>, std::io::Error> {
+    let mut by_format: Vec<(OutputFormat, Vec<ProtocolDeployments>)> = Vec::new();
+    for protocol_deployments in protocols {
+        let format = formats
+            .iter()
+            .rev()
+            .find(|(protocol_name, _)| *protocol_name == protocol_deployments.protocol_name)
+            .map_or(default_format, |(_, format)| *format);
+
+        match by_format
+            .iter_mut()
+            .find(|(existing, _)| *existing == format)
+        {
+            Some((_, protocols)) => protocols.push(protocol_deployments),
+            None => by_format.push((format, vec![protocol_deployments])),
+        }
+    }
+
+    let mut written = Vec::new();
+    for (format, protocols) in by_format {
+        written.extend(write_all(
+            folder,
+            format,
+            layout,
+            abis_dir,
+            json_options,
+            protocols,
+            on_progress,
+        )?);
+    }
+
+    Ok(written)
+}
+
+/// Returns the paths of the written files, relative to `folder`
+pub fn write(
+    folder: &str,
+    protocol_deployments: ProtocolDeployments,
+    json_options: &JsonOptions,
+) -> Result<Vec<String>, std::io::Error> {
+    write_with_layout(folder, protocol_deployments, json_options, Layout::Nested)
+}
+
+/// Like `write`, placing the files as `layout` says
+pub fn write_with_layout(
+    folder: &str,
+    protocol_deployments: ProtocolDeployments,
+    json_options: &JsonOptions,
+    layout: Layout,
+) -> Result<Vec<String>, std::io::Error> {
+    let protocol_name = &protocol_deployments.protocol_name;
+    let mut written = Vec::new();
+
+    for (chain_id, contracts) in &protocol_deployments.chains {
+        written.push(write_protocol_file(
+            folder,
+            layout,
+            &format!("{}/{}.json", protocol_name, chain_id),
+            contracts,
+            json_options,
+        )?);
+    }
+
+    if !protocol_deployments.metadata.is_empty()
+        || !protocol_deployments.signatures.is_empty()
+        || groups::has_groups(protocol_name)
+    {
+        written.extend(write_enriched(
+            folder,
+            layout,
+            &protocol_deployments,
+            json_options,
+        )?);
+    }
+
+    written.push(write_protocol_file(
+        folder,
+        layout,
+        &format!("{}/{}", protocol_name, LATEST_FILE_NAME),
+        &pointer::latest(&protocol_deployments),
+        json_options,
+    )?);
+
+    Ok(written)
+}
+
+/// Writes the file at the nested `relative_path` where `layout` places it,
+/// returning its path relative to `folder`
+fn write_protocol_file<T: Serialize>(
+    folder: &str,
+    layout: Layout,
+    relative_path: &str,
+    value: &T,
+    json_options: &JsonOptions,
+) -> Result<String, std::io::Error> {
+    let file_path = layout.file_path(relative_path);
+    let path = Path::new(folder).join(&file_path);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    json::write_file(path, value, json_options)?;
+
+    Ok(file_path)
+}
+
+fn write_enriched(
+    folder: &str,
+    layout: Layout,
+    protocol_deployments: &ProtocolDeployments,
+    json_options: &JsonOptions,
+) -> Result<Vec<String>, std::io::Error> {
+    let mut written = Vec::new();
+
+    for chain_id in protocol_deployments.chains.keys() {
+        let Some(contracts) = protocol_deployments.enriched_chain(*chain_id) else {
+            continue;
+        };
+
+        // Groups sit next to the flat contracts, e.g. `routers` of balancer v3
+        let mut value = serde_json::to_value(&contracts)?;
+        for (group_name, members) in groups::group(
+            &protocol_deployments.protocol_name,
+            &protocol_deployments.chains[chain_id],
+        ) {
+            value[group_name] = serde_json::to_value(members)?;
+        }
+        if let Some(signatures) = protocol_deployments.signatures.get(chain_id) {
+            value[SOURCES_KEY] = serde_json::to_value(signatures)?;
+        }
+
+        written.push(write_protocol_file(
+            folder,
+            layout,
+            &format!(
+                "{}/enriched/{}.json",
+                protocol_deployments.protocol_name, chain_id
+            ),
+            &value,
+            json_options,
+        )?);
+    }
+
+    Ok(written)
+}
+
+/// Keeps only contracts whose name matches one of `patterns` (`*` wildcard), along with
+/// their metadata, and drops the chains left without contracts
+pub fn retain_contracts(protocol_deployments: &mut ProtocolDeployments, patterns: &[String]) {
+    let is_kept = |name: &str| {
+        patterns
+            .iter()
+            .any(|pattern| pattern::matches(pattern, name))
+    };
+
+    for contracts in protocol_deployments.chains.values_mut() {
+        contracts.retain(|name, _| is_kept(name));
+    }
+    protocol_deployments
+        .chains
+        .retain(|_, contracts| !contracts.is_empty());
+
+    let chains = &protocol_deployments.chains;
+    protocol_deployments.metadata.retain(|chain_id, metadata| {
+        metadata.retain(|name, _| is_kept(name));
+        chains.contains_key(chain_id)
+    });
+    protocol_deployments
+        .candidates
+        .retain(|chain_id, candidates| {
+            candidates.retain(|name, _| is_kept(name));
+            chains.contains_key(chain_id)
+        });
+    protocol_deployments
+        .signatures
+        .retain(|chain_id, signatures| {
+            signatures.retain(|name, _| is_kept(name));
+            chains.contains_key(chain_id)
+        });
+}
+
+/// Writes a single pretty-printed JSON file at the root of the output folder, returning its name
+pub fn write_root_file<T: Serialize>(
+    folder: &str,
+    file_name: &str,
+    value: &T,
+    json_options: &JsonOptions,
+) -> Result<String, std::io::Error> {
+    if !Path::new(folder).exists() {
+        fs::create_dir_all(folder)?;
+    }
+
+    json::write_file(format!("{}/{}", folder, file_name), value, json_options)?;
+
+    Ok(file_name.to_string())
+}
+
+/// Reads every written JSON file back, failing on the first one that doesn't deserialize.
+/// `<chain_id>.json` files must hold chain contracts, enriched ones enriched contracts.
+pub fn verify(folder: &str, written: &[String]) -> Result<(), std::io::Error> {
+    for relative_path in written {
+        let path = Path::new(folder).join(relative_path);
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+
+        let nested_path = nested_path(relative_path);
+        let nested_path = Path::new(&nested_path);
+        let is_chain_file = nested_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.parse::<u64>().is_ok());
+        let is_enriched = nested_path
+            .parent()
+            .and_then(|parent| parent.file_name())
+            .is_some_and(|parent| parent == "enriched");
+
+        let reader = BufReader::new(File::open(&path)?);
+        let result = match (is_chain_file, is_enriched) {
+            (true, true) => verify_enriched(reader),
+            (true, false) => serde_json::from_reader::<_, ChainContracts>(reader).map(drop),
+            (false, _) => serde_json::from_reader::<_, serde_json::Value>(reader).map(drop),
+        };
+
+        result.map_err(|err| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{}: {}", relative_path, err),
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Every entry of an enriched chain file is an enriched contract, the members of a group
+/// or the deployment signatures under `_sources`
+fn verify_enriched(reader: impl std::io::Read) -> Result<(), serde_json::Error> {
+    let mut entries: BTreeMap<String, serde_json::Value> = serde_json::from_reader(reader)?;
+    if let Some(signatures) = entries.remove(SOURCES_KEY) {
+        serde_json::from_value::<ChainSignatures>(signatures)?;
+    }
+    let (groups, contracts): (BTreeMap<_, _>, BTreeMap<_, _>) = entries
+        .into_iter()
+        .partition(|(name, _)| groups::is_group_name(name));
+
+    for members in groups.into_values() {
+        serde_json::from_value::<ChainContracts>(members)?;
+    }
+    serde_json::from_value::<EnrichedChainContracts>(serde_json::Value::Object(
+        contracts.into_iter().collect(),
+    ))
+    .map(drop)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn written_fixture(name: &str) -> (String, Vec<String>) {
+        let folder = std::env::temp_dir()
+            .join(format!("evm-dex-index-{}-{}", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = fs::remove_dir_all(&folder);
+
+        let contracts = ChainContracts::from([(
+            "Permit2".to_string(),
+            "0x000000000022D473030F116dDEE9F6B43aC78BA3".to_string(),
+        )]);
+        let protocol_deployments =
+            ProtocolDeployments::new("permit2", HashMap::from([(1, contracts)]));
+        let written = write(&folder, protocol_deployments, &JsonOptions::default()).unwrap();
+
+        (folder, written)
+    }
+
+    #[test]
+    fn test_verify_accepts_written_files() {
+        let (folder, written) = written_fixture("verify-ok");
+
+        assert!(verify(&folder, &written).is_ok());
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_verify_detects_corrupted_file() {
+        let (folder, written) = written_fixture("verify-corrupted");
+        fs::write(format!("{}/permit2/1.json", folder), "{\"Permit2\": ").unwrap();
+
+        let err = verify(&folder, &written).unwrap_err();
+
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("permit2/1.json"));
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_write_emits_latest_pointer() {
+        let (folder, written) = written_fixture("latest-pointer");
+
+        assert!(written.contains(&"permit2/latest.json".to_string()));
+        let pointer: LatestPointer =
+            serde_json::from_slice(&fs::read(format!("{}/permit2/latest.json", folder)).unwrap())
+                .unwrap();
+        assert_eq!(pointer.hash.len(), 64);
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_retain_contracts_keeps_only_matching_names() {
+        let mut protocols =
+            crate::uniswap::parse("tests/fixtures/uniswap/deployments", &mut Vec::new()).unwrap();
+
+        for protocol_deployments in &mut protocols {
+            retain_contracts(protocol_deployments, &["*Factory".to_string()]);
+        }
+
+        let names: Vec<&String> = protocols
+            .iter()
+            .flat_map(|protocol_deployments| protocol_deployments.chains.values())
+            .flat_map(|contracts| contracts.keys())
+            .collect();
+        assert!(!names.is_empty());
+        assert!(names.iter().all(|name| name.ends_with("Factory")));
+
+        let permit2 = protocols
+            .iter()
+            .find(|protocol_deployments| protocol_deployments.protocol_name == "permit2")
+            .unwrap();
+        assert!(permit2.chains.is_empty());
+    }
+
+    #[test]
+    fn test_enriched_balancer_v3_groups_routers() {
+        let folder = std::env::temp_dir()
+            .join(format!("evm-dex-index-groups-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = fs::remove_dir_all(&folder);
+        let (_, v3) = crate::balancer::parse("tests/fixtures/balancer").unwrap();
+
+        let written = write(&folder, v3, &JsonOptions::default()).unwrap();
+
+        let enriched: serde_json::Value = serde_json::from_slice(
+            &fs::read(format!("{}/balancer-v3/enriched/1.json", folder)).unwrap(),
+        )
+        .unwrap();
+        let batch_router = &enriched["BatchRouter"]["address"];
+        assert!(batch_router.is_string());
+        assert_eq!(&enriched["routers"]["BatchRouter"], batch_router);
+        assert!(verify(&folder, &written).is_ok());
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_enriched_records_deployment_signatures() {
+        let folder = std::env::temp_dir()
+            .join(format!("evm-dex-index-sources-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = fs::remove_dir_all(&folder);
+        let (v2, _) = crate::balancer::parse("tests/fixtures/balancer").unwrap();
+
+        let written = write(&folder, v2, &JsonOptions::default()).unwrap();
+
+        let enriched: serde_json::Value = serde_json::from_slice(
+            &fs::read(format!("{}/balancer-v2/enriched/1.json", folder)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(enriched[SOURCES_KEY]["Vault"], "20210418-vault");
+        assert_eq!(
+            enriched[SOURCES_KEY]["TimelockController"],
+            "20221124-timelock-controller"
+        );
+        assert!(verify(&folder, &written).is_ok());
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_flat_files_layout() {
+        let folder = std::env::temp_dir()
+            .join(format!("evm-dex-index-flat-files-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = fs::remove_dir_all(&folder);
+        let (_, v3) = crate::balancer::parse("tests/fixtures/balancer").unwrap();
+
+        let mut written =
+            write_with_layout(&folder, v3, &JsonOptions::default(), Layout::FlatFiles).unwrap();
+        written.sort();
+
+        assert_eq!(
+            written,
+            [
+                "balancer-v3__1.json",
+                "balancer-v3__8453.json",
+                "balancer-v3__enriched__1.json",
+                "balancer-v3__enriched__8453.json",
+                "balancer-v3__latest.json"
+            ]
+        );
+        assert!(
+            fs::read_dir(&folder)
+                .unwrap()
+                .all(|entry| entry.unwrap().path().is_file())
+        );
+        assert_eq!(
+            decode_flat_file_name(&written[0]).as_deref(),
+            Some("balancer-v3/1.json")
+        );
+        assert!(verify(&folder, &written).is_ok());
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn test_write_all_reports_every_written_file() {
+        let folder = std::env::temp_dir()
+            .join(format!("evm-dex-index-progress-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = fs::remove_dir_all(&folder);
+        let protocols =
+            crate::uniswap::parse("tests/fixtures/uniswap/deployments", &mut Vec::new()).unwrap();
+        let mut events = Vec::new();
+
+        let written = write_all(
+            &folder,
+            OutputFormat::Json,
+            Layout::Nested,
+            None,
+            &JsonOptions::default(),
+            protocols,
+            &mut |event| events.push(event),
+        )
+        .unwrap();
+
+        // 5 protocols, each with 2 chain files, their enriched copies and a latest pointer
+        assert_eq!(events.len(), 25);
+        assert_eq!(events.len(), written.len());
+        assert!(
+            events
+                .iter()
+                .all(|event| matches!(event, ProgressEvent::FileWritten { .. }))
+        );
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use serde::Serialize;
+
+use super::rows::rows;
+use crate::types::{ChainId, ProtocolDeployments};
+
+pub const NDJSON_FILE_NAME: &str = "deployments.ndjson";
+
+#[derive(Serialize)]
+struct Line<'a> {
+    protocol: &'a str,
+    chain_id: ChainId,
+    contract: &'a str,
+    address: &'a str,
+}
+
+/// Writes every contract as a `{protocol, chain_id, contract, address}` object on its own
+/// line of a single NDJSON file, sorted like the CSV rows
+pub fn write(
+    folder: &str,
+    protocols: &[ProtocolDeployments],
+) -> Result<Vec<String>, std::io::Error> {
+    if !Path::new(folder).exists() {
+        fs::create_dir_all(folder)?;
+    }
+
+    let mut writer = BufWriter::new(File::create(format!("{}/{}", folder, NDJSON_FILE_NAME))?);
+    for row in rows(protocols) {
+        let line = Line {
+            protocol: row.protocol,
+            chain_id: row.chain_id,
+            contract: row.contract_name,
+            address: row.address,
+        };
+        serde_json::to_writer(&mut writer, &line)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+
+    Ok(vec![NDJSON_FILE_NAME.to_string()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uniswap;
+
+    #[test]
+    fn test_ndjson_lines_are_valid_json() {
+        let folder = std::env::temp_dir()
+            .join(format!("evm-dex-index-ndjson-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = fs::remove_dir_all(&folder);
+        let protocols =
+            uniswap::parse("tests/fixtures/uniswap/deployments", &mut Vec::new()).unwrap();
+
+        write(&folder, &protocols).unwrap();
+
+        let contents = fs::read_to_string(format!("{}/{}", folder, NDJSON_FILE_NAME)).unwrap();
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let contract_count: usize = protocols
+            .iter()
+            .flat_map(|protocol| protocol.chains.values())
+            .map(|contracts| contracts.len())
+            .sum();
+        assert_eq!(lines.len(), contract_count);
+        assert!(lines.contains(&serde_json::json!({
+            "protocol": "permit2",
+            "chain_id": 8453,
+            "contract": "Permit2",
+            "address": "0x000000000022D473030F116dDEE9F6B43aC78BA3",
+        })));
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
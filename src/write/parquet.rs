@@ -0,0 +1,96 @@
+use std::{
+    fs::{self, File},
+    path::Path,
+    sync::Arc,
+};
+
+use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+
+use super::rows::rows;
+use crate::types::ProtocolDeployments;
+
+pub const PARQUET_FILE_NAME: &str = "deployments.parquet";
+
+/// Writes every contract as a `(protocol, chain_id, contract_name, address)` row of a single table
+pub fn write(
+    folder: &str,
+    protocols: &[ProtocolDeployments],
+) -> Result<Vec<String>, std::io::Error> {
+    if !Path::new(folder).exists() {
+        fs::create_dir_all(folder)?;
+    }
+
+    let rows = rows(protocols);
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("protocol", DataType::Utf8, false),
+        Field::new("chain_id", DataType::UInt64, false),
+        Field::new("contract_name", DataType::Utf8, false),
+        Field::new("address", DataType::Utf8, false),
+    ]));
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|row| row.protocol),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            rows.iter().map(|row| row.chain_id),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|row| row.contract_name),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|row| row.address),
+        )),
+    ];
+
+    let batch = RecordBatch::try_new(schema.clone(), columns).map_err(std::io::Error::other)?;
+
+    let file = File::create(format!("{}/{}", folder, PARQUET_FILE_NAME))?;
+    let mut writer = ArrowWriter::try_new(file, schema, None).map_err(std::io::Error::other)?;
+    writer.write(&batch).map_err(std::io::Error::other)?;
+    writer.close().map_err(std::io::Error::other)?;
+
+    Ok(vec![PARQUET_FILE_NAME.to_string()])
+}
+
+#[cfg(test)]
+mod tests {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+    use crate::uniswap;
+
+    #[test]
+    fn test_parquet_reads_back_every_contract() {
+        let folder = std::env::temp_dir()
+            .join(format!("evm-dex-index-parquet-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = fs::remove_dir_all(&folder);
+
+        let protocols =
+            uniswap::parse("tests/fixtures/uniswap/deployments", &mut Vec::new()).unwrap();
+
+        let written = write(&folder, &protocols).unwrap();
+        assert_eq!(written, [PARQUET_FILE_NAME]);
+
+        let file = File::open(format!("{}/{}", folder, PARQUET_FILE_NAME)).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let mut row_count = 0;
+        for batch in reader {
+            let batch = batch.unwrap();
+            assert_eq!(batch.num_columns(), 4);
+            row_count += batch.num_rows();
+        }
+        assert_eq!(row_count, 40);
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
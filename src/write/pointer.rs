@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{checksums::sha256_hex, types::ProtocolDeployments};
+
+pub const LATEST_FILE_NAME: &str = "latest.json";
+
+/// Lets polling clients detect whether a protocol changed before fetching its chain files
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatestPointer {
+    pub generated_at: String,
+    /// SHA-256 of the protocol's chains and metadata, independent of map ordering
+    pub hash: String,
+}
+
+pub fn content_hash(protocol_deployments: &ProtocolDeployments) -> String {
+    let chains: BTreeMap<_, _> = protocol_deployments.chains.iter().collect();
+    let metadata: BTreeMap<_, _> = protocol_deployments.metadata.iter().collect();
+
+    let contents = serde_json::to_vec(&(chains, metadata)).expect("Deployments always serialize");
+    sha256_hex(&contents)
+}
+
+/// Pointer to the protocol's current contents, generated now
+pub fn latest(protocol_deployments: &ProtocolDeployments) -> LatestPointer {
+    LatestPointer {
+        generated_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        hash: content_hash(protocol_deployments),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::types::ChainContracts;
+
+    fn deployments(vault_address: &str) -> ProtocolDeployments {
+        let chains = (1..=8)
+            .map(|chain_id| {
+                let contracts = ChainContracts::from([
+                    ("Router".to_string(), "0x02".to_string()),
+                    ("Vault".to_string(), vault_address.to_string()),
+                ]);
+                (chain_id, contracts)
+            })
+            .collect::<HashMap<_, _>>();
+
+        ProtocolDeployments::new("balancer-v3", chains)
+    }
+
+    #[test]
+    fn test_content_hash_is_stable_for_same_data() {
+        assert_eq!(
+            content_hash(&deployments("0x01")),
+            content_hash(&deployments("0x01"))
+        );
+    }
+
+    #[test]
+    fn test_content_hash_changes_with_data() {
+        assert_ne!(
+            content_hash(&deployments("0x01")),
+            content_hash(&deployments("0x03"))
+        );
+    }
+}
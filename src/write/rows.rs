@@ -0,0 +1,77 @@
+use crate::types::{ChainId, ProtocolDeployments};
+
+/// A single contract deployment, as a row of tabular formats
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct Row<'a> {
+    pub protocol: &'a str,
+    pub chain_id: ChainId,
+    pub contract_name: &'a str,
+    pub address: &'a str,
+}
+
+/// Every contract of every protocol, sorted by protocol, chain id and contract name
+pub(super) fn rows(protocols: &[ProtocolDeployments]) -> Vec<Row<'_>> {
+    let mut rows: Vec<Row> = protocols
+        .iter()
+        .flat_map(|protocol| {
+            protocol
+                .chains
+                .iter()
+                .flat_map(move |(chain_id, contracts)| {
+                    contracts.iter().map(move |(name, address)| Row {
+                        protocol: &protocol.protocol_name,
+                        chain_id: *chain_id,
+                        contract_name: name,
+                        address,
+                    })
+                })
+        })
+        .collect();
+
+    rows.sort_by(|a, b| {
+        (a.protocol, a.chain_id, a.contract_name).cmp(&(b.protocol, b.chain_id, b.contract_name))
+    });
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::types::ChainContracts;
+
+    #[test]
+    fn test_rows_are_sorted() {
+        let contracts = ChainContracts::from([
+            ("Router".to_string(), "0x02".to_string()),
+            ("Factory".to_string(), "0x01".to_string()),
+        ]);
+        let protocols = [
+            ProtocolDeployments::new("uniswap-v2", HashMap::from([(10, contracts.clone())])),
+            ProtocolDeployments::new(
+                "balancer-v2",
+                HashMap::from([(10, contracts.clone()), (1, contracts)]),
+            ),
+        ];
+
+        let rows = rows(&protocols);
+
+        let keys: Vec<_> = rows
+            .iter()
+            .map(|row| (row.protocol, row.chain_id, row.contract_name))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                ("balancer-v2", 1, "Factory"),
+                ("balancer-v2", 1, "Router"),
+                ("balancer-v2", 10, "Factory"),
+                ("balancer-v2", 10, "Router"),
+                ("uniswap-v2", 10, "Factory"),
+                ("uniswap-v2", 10, "Router"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use std::fs;
+
+use serde::Serialize;
+use tracing::warn;
+
+use crate::{chains, types::ProtocolDeployments};
+
+pub const SUBGRAPH_FOLDER_NAME: &str = "subgraph";
+
+/// The `dataSources` of a subgraph manifest
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    data_sources: Vec<DataSource>,
+}
+
+#[derive(Debug, Serialize)]
+struct DataSource {
+    kind: &'static str,
+    name: String,
+    network: &'static str,
+    source: Source,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Source {
+    address: String,
+    abi: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_block: Option<u64>,
+}
+
+/// Writes `subgraph/<protocol>.yaml` per protocol, holding the `dataSources` of a subgraph
+/// manifest: one per chain and contract, with the address lowercased as The Graph requires and
+/// the block the contract was deployed at as `startBlock` when the source records it
+pub fn write(
+    folder: &str,
+    protocols: &[ProtocolDeployments],
+) -> Result<Vec<String>, std::io::Error> {
+    fs::create_dir_all(format!("{}/{}", folder, SUBGRAPH_FOLDER_NAME))?;
+
+    let mut written = Vec::new();
+    for protocol_deployments in protocols {
+        let path = format!(
+            "{}/{}.yaml",
+            SUBGRAPH_FOLDER_NAME, protocol_deployments.protocol_name
+        );
+        let yaml = serde_yaml::to_string(&manifest(protocol_deployments))
+            .map_err(std::io::Error::other)?;
+        fs::write(format!("{}/{}", folder, path), yaml)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// Data sources of a protocol, ordered by chain id and contract name. Chains The Graph has no
+/// network id for are left out with a warning.
+fn manifest(protocol_deployments: &ProtocolDeployments) -> Manifest {
+    let mut data_sources = Vec::new();
+
+    let mut chain_ids: Vec<_> = protocol_deployments.chains.keys().copied().collect();
+    chain_ids.sort();
+
+    for chain_id in chain_ids {
+        let Some(network) = chains::graph_network(chain_id) else {
+            warn!(
+                protocol = protocol_deployments.protocol_name,
+                chain_id, "Skipping chain without a subgraph network id"
+            );
+            continue;
+        };
+        let mut contracts: Vec<_> = protocol_deployments.chains[&chain_id].iter().collect();
+        contracts.sort();
+
+        for (name, address) in contracts {
+            let start_block = protocol_deployments
+                .metadata
+                .get(&chain_id)
+                .and_then(|metadata| metadata.get(name))
+                .and_then(|contract_metadata| contract_metadata.block_created);
+
+            data_sources.push(DataSource {
+                kind: "ethereum/contract",
+                name: format!("{}-{}", name, network),
+                network,
+                source: Source {
+                    address: address.to_lowercase(),
+                    abi: name.clone(),
+                    start_block,
+                },
+            });
+        }
+    }
+
+    Manifest { data_sources }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::types::{ChainContracts, ChainMetadata, ContractMetadata};
+
+    #[test]
+    fn test_data_sources_have_lowercase_address_and_start_block() {
+        let factory = ChainContracts::from([(
+            "UniswapV3Factory".to_string(),
+            "0x1F98431c8aD98523631AE4a59f267346ea31F984".to_string(),
+        )]);
+        let mut protocol_deployments = ProtocolDeployments::new(
+            "uniswap-v3",
+            HashMap::from([
+                (
+                    137,
+                    ChainContracts::from([
+                        (
+                            "UniswapV3Factory".to_string(),
+                            "0x1F98431c8aD98523631AE4a59f267346ea31F984".to_string(),
+                        ),
+                        (
+                            "QuoterV2".to_string(),
+                            "0x61fFE014bA17989E743c5F6cB21bF9697530B21e".to_string(),
+                        ),
+                    ]),
+                ),
+                // No subgraph network id
+                (123456789, factory),
+            ]),
+        );
+        protocol_deployments.metadata.insert(
+            137,
+            ChainMetadata::from([(
+                "UniswapV3Factory".to_string(),
+                ContractMetadata {
+                    block_created: Some(22757547),
+                    ..ContractMetadata::default()
+                },
+            )]),
+        );
+
+        let yaml = serde_yaml::to_string(&manifest(&protocol_deployments)).unwrap();
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+
+        let data_sources = parsed["dataSources"].as_sequence().unwrap();
+        assert_eq!(data_sources.len(), 2);
+        let quoter = &data_sources[0];
+        assert_eq!(quoter["kind"], "ethereum/contract");
+        assert_eq!(quoter["name"], "QuoterV2-matic");
+        assert_eq!(quoter["network"], "matic");
+        assert_eq!(
+            quoter["source"]["address"],
+            "0x61ffe014ba17989e743c5f6cb21bf9697530b21e"
+        );
+        assert!(quoter["source"].get("startBlock").is_none());
+        let factory = &data_sources[1];
+        assert_eq!(factory["source"]["abi"], "UniswapV3Factory");
+        assert_eq!(factory["source"]["startBlock"], 22757547);
+    }
+}
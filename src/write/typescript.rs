@@ -0,0 +1,178 @@
+use std::{collections::BTreeMap, fmt::Write as _, fs, io::ErrorKind, path::Path};
+
+use crate::types::{ChainId, ContractAddress, ProtocolDeployments};
+
+pub const TYPESCRIPT_FILE_NAME: &str = "deployments.ts";
+
+/// Writes a single `deployments.ts` exporting an `as const` object per protocol, with each
+/// contract's chain-keyed addresses and, when `abis_dir` has a `<contract>.json` for it, its ABI
+pub fn write(
+    folder: &str,
+    protocols: &[ProtocolDeployments],
+    abis_dir: Option<&str>,
+) -> Result<Vec<String>, std::io::Error> {
+    if !Path::new(folder).exists() {
+        fs::create_dir_all(folder)?;
+    }
+
+    let mut module = String::from("// Generated by evm-dex-index, do not edit\n");
+
+    let mut protocols: Vec<_> = protocols.iter().collect();
+    protocols.sort_by(|a, b| a.protocol_name.cmp(&b.protocol_name));
+
+    for protocol_deployments in protocols {
+        let mut contracts: BTreeMap<&str, BTreeMap<ChainId, &ContractAddress>> = BTreeMap::new();
+        for (chain_id, chain_contracts) in &protocol_deployments.chains {
+            for (name, address) in chain_contracts {
+                contracts
+                    .entry(name)
+                    .or_default()
+                    .insert(*chain_id, address);
+            }
+        }
+
+        writeln!(
+            module,
+            "\nexport const {} = {{",
+            identifier(&protocol_deployments.protocol_name)
+        )
+        .expect("Writing to a String never fails");
+
+        for (name, addresses) in contracts {
+            writeln!(module, "  {}: {{\n    address: {{", quoted(name))
+                .expect("Writing to a String never fails");
+            for (chain_id, address) in addresses {
+                writeln!(module, "      {}: {},", chain_id, quoted(address))
+                    .expect("Writing to a String never fails");
+            }
+            module.push_str("    },\n");
+
+            if let Some(abi) = abis_dir
+                .map(|dir| read_abi(dir, name))
+                .transpose()?
+                .flatten()
+            {
+                writeln!(module, "    abi: {},", abi).expect("Writing to a String never fails");
+            }
+            module.push_str("  },\n");
+        }
+
+        module.push_str("} as const;\n");
+    }
+
+    fs::write(format!("{}/{}", folder, TYPESCRIPT_FILE_NAME), module)?;
+
+    Ok(vec![TYPESCRIPT_FILE_NAME.to_string()])
+}
+
+/// ABI in `<abis_dir>/<contract>.json`, either a bare ABI array or a build artifact with an
+/// `abi` field. `None` when there's no such file.
+pub(super) fn read_abi(
+    abis_dir: &str,
+    contract_name: &str,
+) -> Result<Option<serde_json::Value>, std::io::Error> {
+    let path = format!("{}/{}.json", abis_dir, contract_name);
+    let contents = match fs::read(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    let value: serde_json::Value = serde_json::from_slice(&contents)?;
+    let abi = match value {
+        serde_json::Value::Object(mut artifact) => artifact.remove("abi"),
+        abi @ serde_json::Value::Array(_) => Some(abi),
+        _ => None,
+    };
+
+    let Some(abi @ serde_json::Value::Array(_)) = abi else {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("{}: no ABI array", path),
+        ));
+    };
+
+    Ok(Some(abi))
+}
+
+/// camelCase identifier of a protocol name, e.g. `uniswap-v3` → `uniswapV3`
+fn identifier(protocol_name: &str) -> String {
+    let mut identifier = String::new();
+
+    for (index, word) in protocol_name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .enumerate()
+    {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            if index == 0 {
+                identifier.push(first.to_ascii_lowercase());
+            } else {
+                identifier.push(first.to_ascii_uppercase());
+            }
+            identifier.extend(chars);
+        }
+    }
+
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+
+    identifier
+}
+
+/// Double-quoted string literal, valid in both JSON and TypeScript
+fn quoted(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uniswap;
+
+    #[test]
+    fn test_identifier() {
+        assert_eq!(identifier("uniswap-v3"), "uniswapV3");
+        assert_eq!(identifier("pancakeswap-infinity"), "pancakeswapInfinity");
+        assert_eq!(identifier("1inch"), "_1inch");
+    }
+
+    #[test]
+    fn test_typescript_module() {
+        let folder = std::env::temp_dir()
+            .join(format!("evm-dex-index-typescript-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = fs::remove_dir_all(&folder);
+        let abis_dir = format!("{}/abis", folder);
+        fs::create_dir_all(&abis_dir).unwrap();
+        fs::write(
+            format!("{}/Permit2.json", abis_dir),
+            r#"{ "abi": [{ "type": "function", "name": "DOMAIN_SEPARATOR", "inputs": [] }] }"#,
+        )
+        .unwrap();
+        let protocols =
+            uniswap::parse("tests/fixtures/uniswap/deployments", &mut Vec::new()).unwrap();
+
+        write(&folder, &protocols, Some(&abis_dir)).unwrap();
+
+        let module = fs::read_to_string(format!("{}/{}", folder, TYPESCRIPT_FILE_NAME)).unwrap();
+        assert!(module.contains(
+            "export const permit2 = {\n  \"Permit2\": {\n    address: {\n      1: \"0x000000000022D473030F116dDEE9F6B43aC78BA3\",\n"
+        ));
+        assert!(
+            module.contains(
+                r#"    abi: [{"inputs":[],"name":"DOMAIN_SEPARATOR","type":"function"}],"#
+            )
+        );
+        assert!(module.contains("export const uniswapV3 = {"));
+        assert_eq!(
+            module.matches("} as const;").count(),
+            module.matches("export const").count()
+        );
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,94 @@
+use std::{collections::BTreeMap, fs};
+
+use crate::types::{ChainContracts, ProtocolDeployments};
+
+/// Writes `<protocol>/<chain_id>.yaml` per protocol and chain, mapping each contract to its
+/// address like the JSON chain files, with contracts sorted by name
+pub fn write(
+    folder: &str,
+    protocols: &[ProtocolDeployments],
+) -> Result<Vec<String>, std::io::Error> {
+    let mut written = Vec::new();
+
+    for protocol_deployments in protocols {
+        let protocol_name = &protocol_deployments.protocol_name;
+        fs::create_dir_all(format!("{}/{}", folder, protocol_name))?;
+
+        let mut chain_ids: Vec<_> = protocol_deployments.chains.keys().copied().collect();
+        chain_ids.sort();
+
+        for chain_id in chain_ids {
+            let path = format!("{}/{}.yaml", protocol_name, chain_id);
+            fs::write(
+                format!("{}/{}", folder, path),
+                chain_yaml(&protocol_deployments.chains[&chain_id])?,
+            )?;
+            written.push(path);
+        }
+    }
+
+    Ok(written)
+}
+
+/// A `Name: address` mapping of the contracts, sorted by name
+fn chain_yaml(contracts: &ChainContracts) -> Result<String, std::io::Error> {
+    let contracts: BTreeMap<_, _> = contracts.iter().collect();
+
+    serde_yaml::to_string(&contracts).map_err(std::io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_yaml_round_trips_to_same_deployments() {
+        let folder = std::env::temp_dir()
+            .join(format!("evm-dex-index-yaml-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = fs::remove_dir_all(&folder);
+        let protocol_deployments = ProtocolDeployments::new(
+            "balancer-v2",
+            HashMap::from([
+                (
+                    1,
+                    ChainContracts::from([
+                        (
+                            "Vault".to_string(),
+                            "0xBA12222222228d8Ba445958a75a0704d566BF2C8".to_string(),
+                        ),
+                        (
+                            "Vault-v2".to_string(),
+                            "0x0000000000000000000000000000000000000001".to_string(),
+                        ),
+                        ("Yes".to_string(), "0x02".to_string()),
+                        ("Weighted Pool".to_string(), "0x03".to_string()),
+                    ]),
+                ),
+                (
+                    8453,
+                    ChainContracts::from([("Vault".to_string(), "0x1e5".to_string())]),
+                ),
+            ]),
+        );
+
+        let written = write(&folder, std::slice::from_ref(&protocol_deployments)).unwrap();
+
+        assert_eq!(written, ["balancer-v2/1.yaml", "balancer-v2/8453.yaml"]);
+        let chains = [1, 8453]
+            .into_iter()
+            .map(|chain_id| {
+                let path = format!("{}/balancer-v2/{}.yaml", folder, chain_id);
+                let yaml = fs::read_to_string(path).unwrap();
+                (chain_id, serde_yaml::from_str(&yaml).unwrap())
+            })
+            .collect();
+        let read_back = ProtocolDeployments::new("balancer-v2", chains);
+        assert_eq!(read_back.chains, protocol_deployments.chains);
+
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,852 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+fn empty_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("evm-dex-index-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_missing_source_exits_with_parse_error_code() {
+    let dir = empty_dir("missing-source");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Failed to parse balancer deployments"));
+    assert!(!stderr.contains("panicked"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_validate_reports_every_issue_of_broken_source() {
+    let output_dir = empty_dir("validate-output");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "validate",
+            "--balancer-path",
+            "tests/fixtures/broken/balancer",
+            "--uniswap-path",
+            "tests/fixtures/broken/uniswap/deployments",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[balancer] invalid address '0xnotanaddress'"));
+    assert!(stderr.contains("[uniswap] zero address"));
+    assert!(stderr.contains("Missing contracts for protocol 'uniswap-v3'"));
+
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 0);
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_watch_is_rejected_with_subcommand() {
+    for subcommand in ["validate", "doctor"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+            .args(["--watch", subcommand])
+            .output()
+            .unwrap();
+
+        assert_eq!(output.status.code(), Some(2));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(&format!(
+            "--watch can't be used with the '{}' subcommand",
+            subcommand
+        )));
+    }
+}
+
+#[test]
+fn test_validate_passes_on_valid_source() {
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "validate",
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn test_doctor_passes_on_valid_setup() {
+    let output_dir = empty_dir("doctor-ok");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "doctor",
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+            "--delta",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.lines().all(|line| line.starts_with("[ok]")));
+    assert!(stdout.contains("git is available"));
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 0);
+
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_doctor_reports_missing_source() {
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "doctor",
+            "--balancer-path",
+            "tests/fixtures/does-not-exist",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[fail] balancer source 'tests/fixtures/does-not-exist' is readable"));
+    assert!(stdout.contains("[ok]   uniswap source"));
+}
+
+#[test]
+fn test_flat_by_chain_format() {
+    let output_dir = empty_dir("flat-by-chain");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+            "--format",
+            "flat-by-chain",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+
+    let mainnet: serde_json::Value =
+        serde_json::from_slice(&fs::read(output_dir.join("flat/1.json")).unwrap()).unwrap();
+    assert_eq!(
+        mainnet["Permit2"],
+        "0x000000000022D473030F116dDEE9F6B43aC78BA3"
+    );
+    assert!(mainnet.get("balancer-v3:Vault").is_some());
+    assert!(!output_dir.join("permit2").exists());
+
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_warnings_file_collects_soft_issues() {
+    let output_dir = empty_dir("warnings");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+            "tests/fixtures/warnings/uniswap/deployments",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+
+    let warnings: Vec<serde_json::Value> =
+        serde_json::from_slice(&fs::read(output_dir.join("warnings.json")).unwrap()).unwrap();
+    assert_eq!(warnings.len(), 3);
+    assert_eq!(warnings[0]["kind"], "unassigned-contract");
+    assert_eq!(warnings[0]["chain_id"], 1);
+    // Base lacks v3 routers and vault extensions mainnet has
+    assert_eq!(warnings[1]["kind"], "missing-contracts");
+    assert_eq!(warnings[1]["protocol"], "balancer-v3");
+    assert_eq!(warnings[1]["chain_id"], 8453);
+    assert_eq!(warnings[2]["kind"], "anomaly");
+    assert_eq!(warnings[2]["protocol"], "uniswap-v4");
+
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_assert_no_zero_addresses_fails_run() {
+    let output_dir = empty_dir("zero-addresses");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+            "tests/fixtures/warnings/uniswap/deployments",
+            "--assert-no-zero-addresses",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("uniswap-v4 on chain 1: WETHHook"));
+    assert!(!output_dir.join("index.json").exists());
+
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_balancer_ref_is_recorded_in_index() {
+    let repo = empty_dir("balancer-ref-repo");
+    let output_dir = empty_dir("balancer-ref-output");
+
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .arg("-C")
+            .arg(&repo)
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    };
+
+    fs::create_dir_all(repo.join("addresses")).unwrap();
+    for entry in fs::read_dir("tests/fixtures/balancer/addresses").unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), repo.join("addresses").join(entry.file_name())).unwrap();
+    }
+    git(&["init", "--quiet"]);
+    git(&["add", "-A"]);
+    git(&["commit", "--quiet", "-m", "fixture"]);
+    git(&["tag", "v1"]);
+    // Only the tagged tree can be parsed
+    fs::remove_dir_all(repo.join("addresses")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args(["--balancer-path"])
+        .arg(&repo)
+        .args([
+            "--balancer-ref",
+            "v1",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let index: serde_json::Value =
+        serde_json::from_slice(&fs::read(output_dir.join("index.json")).unwrap()).unwrap();
+    assert_eq!(index["protocols"]["balancer-v2"]["source_ref"], "v1");
+    assert!(index["protocols"]["permit2"].get("source_ref").is_none());
+
+    fs::remove_dir_all(repo).unwrap();
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_source_commit_is_recorded_in_index() {
+    let repo = empty_dir("source-commit-repo");
+    let output_dir = empty_dir("source-commit-output");
+
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .arg("-C")
+            .arg(&repo)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    };
+
+    fs::create_dir_all(repo.join("addresses")).unwrap();
+    for entry in fs::read_dir("tests/fixtures/balancer/addresses").unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), repo.join("addresses").join(entry.file_name())).unwrap();
+    }
+    git(&["init", "--quiet"]);
+    git(&["add", "-A"]);
+    git(&["commit", "--quiet", "-m", "fixture"]);
+    let commit = git(&["rev-parse", "HEAD"]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args(["--balancer-path"])
+        .arg(&repo)
+        .args([
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+
+    let index: serde_json::Value =
+        serde_json::from_slice(&fs::read(output_dir.join("index.json")).unwrap()).unwrap();
+    let source = &index["protocols"]["balancer-v3"]["source"];
+    assert_eq!(source["commit"], commit.as_str());
+    assert_eq!(source["location"], repo.to_string_lossy().as_ref());
+
+    fs::remove_dir_all(repo).unwrap();
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_max_age_rejects_stale_source() {
+    let output_dir = empty_dir("max-age");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+            "--max-age",
+            "30",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Newest balancer deployment is from 2024-12-05"));
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 0);
+
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_max_age_only_looks_at_selected_chains() {
+    let output_dir = empty_dir("max-age-chains");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+            "--chains",
+            "8453",
+            "--max-age",
+            "30",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Newest balancer deployment is from 2024-12-04"));
+
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_max_age_warns_on_stale_source() {
+    let output_dir = empty_dir("max-age-warn");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+            "--max-age",
+            "30",
+            "--stale-source",
+            "warn",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    let warnings: Vec<serde_json::Value> =
+        serde_json::from_slice(&fs::read(output_dir.join("warnings.json")).unwrap()).unwrap();
+    assert!(
+        warnings
+            .iter()
+            .any(|warning| warning["kind"] == "stale-source")
+    );
+
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_env_var_is_fallback_for_flag() {
+    let env_dir = empty_dir("env-output");
+    let flag_dir = empty_dir("flag-output");
+
+    let run = |extra_args: &[&std::ffi::OsStr]| {
+        Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+            .env("EVM_DEX_INDEX_OUTPUT_DIR", &env_dir)
+            .env("EVM_DEX_INDEX_BALANCER_PATH", "tests/fixtures/balancer")
+            .args(["--uniswap-path", "tests/fixtures/uniswap/deployments"])
+            .args(extra_args)
+            .output()
+            .unwrap()
+    };
+
+    assert_eq!(run(&[]).status.code(), Some(0));
+    assert!(env_dir.join("index.json").exists());
+    fs::remove_dir_all(&env_dir).unwrap();
+
+    let output = run(&["--output-dir".as_ref(), flag_dir.as_os_str()]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(flag_dir.join("index.json").exists());
+    assert!(!env_dir.exists());
+
+    fs::remove_dir_all(flag_dir).unwrap();
+}
+
+#[test]
+fn test_protocol_denylist_wins_over_allowlist() {
+    let output_dir = empty_dir("protocol-lists");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+            "--protocol-allowlist",
+            "permit2,uniswap-v2",
+            "--protocol-denylist",
+            "permit2",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output_dir.join("uniswap-v2").exists());
+    assert!(!output_dir.join("permit2").exists());
+    assert!(!output_dir.join("balancer-v2").exists());
+
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_exclude_chains_wins_over_chains() {
+    let output_dir = empty_dir("exclude-chains");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+            "--pancakeswap-path",
+            "tests/fixtures/pancakeswap/deployments",
+            "--chains",
+            "1,56",
+            "--exclude-chains",
+            "56",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output_dir.join("uniswap-v2/1.json").exists());
+    assert!(!output_dir.join("uniswap-v2/8453.json").exists());
+    for entry in fs::read_dir(&output_dir).unwrap() {
+        let path = entry.unwrap().path();
+        assert!(!path.join("56.json").exists(), "{}", path.display());
+    }
+    assert!(!output_dir.join("pancakeswap-infinity").exists());
+
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_include_only_complete_chains() {
+    let run = |name: &str, args: &[&str]| {
+        let output_dir = empty_dir(name);
+        let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+            .args([
+                "--balancer-path",
+                "tests/fixtures/balancer",
+                "--uniswap-path",
+                "tests/fixtures/incomplete/uniswap/deployments",
+            ])
+            .args(args)
+            .arg("--output-dir")
+            .arg(&output_dir)
+            .output()
+            .unwrap();
+        (output, output_dir)
+    };
+
+    // Base lacks the required QuoterV2 of uniswap-v3, which mainnet has
+    let (output, output_dir) = run("complete-chains-off", &[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output_dir.join("uniswap-v3/8453.json").exists());
+    fs::remove_dir_all(output_dir).unwrap();
+
+    let (output, output_dir) = run("complete-chains-on", &["--include-only-complete-chains"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(!output_dir.join("uniswap-v3/8453.json").exists());
+    assert!(output_dir.join("uniswap-v3/1.json").exists());
+    assert!(output_dir.join("uniswap-v2/8453.json").exists());
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_rename_protocol() {
+    let run = |name: &str, renames: &[&str]| {
+        let output_dir = empty_dir(name);
+        let mut command = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"));
+        command.args([
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+        ]);
+        for rename in renames {
+            command.args(["--rename-protocol", rename]);
+        }
+        let output = command
+            .arg("--output-dir")
+            .arg(&output_dir)
+            .output()
+            .unwrap();
+        (output, output_dir)
+    };
+
+    let (output, output_dir) = run("rename-protocol", &["uniswap-v3=univ3"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output_dir.join("univ3/1.json").exists());
+    assert!(!output_dir.join("uniswap-v3").exists());
+    let index: serde_json::Value =
+        serde_json::from_slice(&fs::read(output_dir.join("index.json")).unwrap()).unwrap();
+    assert_eq!(index["protocols"]["univ3"]["family"], "uniswap");
+    fs::remove_dir_all(output_dir).unwrap();
+
+    let (output, output_dir) = run(
+        "rename-protocol-collision",
+        &["uniswap-v3=univ", "uniswap-v4=univ"],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'univ'"));
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 0);
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_check_snapshot() {
+    let dir = empty_dir("snapshot");
+    let snapshot_path = dir.join("snapshot.json");
+
+    let run = |args: &[&std::ffi::OsStr]| {
+        Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+            .args([
+                "--balancer-path",
+                "tests/fixtures/balancer",
+                "--uniswap-path",
+                "tests/fixtures/uniswap/deployments",
+                "--output-dir",
+            ])
+            .arg(dir.join("output"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&["--snapshot".as_ref(), snapshot_path.as_os_str()]);
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = run(&["--check-snapshot".as_ref(), snapshot_path.as_os_str()]);
+    assert_eq!(output.status.code(), Some(0));
+
+    let mut snapshot: serde_json::Value =
+        serde_json::from_slice(&fs::read(&snapshot_path).unwrap()).unwrap();
+    snapshot["permit2"]["1"]
+        .as_array_mut()
+        .unwrap()
+        .push("Permit3".into());
+    fs::write(&snapshot_path, snapshot.to_string()).unwrap();
+
+    let output = run(&["--check-snapshot".as_ref(), snapshot_path.as_os_str()]);
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(r#"permit2 on chain 1: missing ["Permit3"]"#));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+/// Contents of every file under `dir` by relative path, except the timestamped
+/// `latest.json` pointers and the checksums covering them
+fn output_files(dir: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+    let mut files = BTreeMap::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(current).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let file_name = path.file_name().unwrap();
+            if file_name == "latest.json" || file_name == "checksums.json" {
+                continue;
+            }
+            files.insert(
+                path.strip_prefix(dir).unwrap().to_path_buf(),
+                fs::read(&path).unwrap(),
+            );
+        }
+    }
+
+    files
+}
+
+#[test]
+fn test_single_worker_output_matches_default() {
+    let run = |name: &str, extra_args: &[&str]| {
+        let output_dir = empty_dir(name);
+        let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+            .args([
+                "--balancer-path",
+                "tests/fixtures/balancer",
+                "--uniswap-path",
+                "tests/fixtures/uniswap/deployments",
+            ])
+            .args(extra_args)
+            .arg("--output-dir")
+            .arg(&output_dir)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0));
+        output_dir
+    };
+
+    let default_dir = run("workers-default", &[]);
+    let single_dir = run("workers-single", &["--workers", "1"]);
+
+    let default_files = output_files(&default_dir);
+    assert!(!default_files.is_empty());
+    assert_eq!(default_files, output_files(&single_dir));
+
+    fs::remove_dir_all(default_dir).unwrap();
+    fs::remove_dir_all(single_dir).unwrap();
+}
+
+#[test]
+fn test_csv_source_is_merged_into_output() {
+    let output_dir = empty_dir("csv-source");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+            "--csv-source",
+            "tests/fixtures/csv-source/custom.csv",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    let contracts: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(output_dir.join("kyber-elastic/8453.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        contracts["Factory"],
+        "0x0000000000000000000000000000000000000002"
+    );
+    assert!(output_dir.join("maverick-v2/1.json").exists());
+    assert!(output_dir.join("uniswap-v3/1.json").exists());
+
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_format_for_writes_protocols_in_different_formats() {
+    let output_dir = empty_dir("format-for");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+            "--format-for",
+            "uniswap-v3=ts",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    let module = fs::read_to_string(output_dir.join("deployments.ts")).unwrap();
+    assert!(module.contains("uniswapV3"));
+    assert!(!module.contains("uniswapV2"));
+    assert!(output_dir.join("uniswap-v2/1.json").exists());
+    assert!(!output_dir.join("uniswap-v3").exists());
+
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_conflicting_balancer_mirrors_fail_run() {
+    let output_dir = empty_dir("source-conflicts");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "--balancer-path",
+            "tests/fixtures/multi-repo/monorepo",
+            "--balancer-extra-path",
+            "tests/fixtures/multi-repo/mirror",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Sources disagree on deployments"));
+    assert!(stderr.contains("0xBA12222222228d8Ba445958a75a0704d566BF2C8"));
+    assert!(stderr.contains("0xBA12222222228d8Ba445958a75a0704d566BF2C9"));
+
+    let _ = fs::remove_dir_all(output_dir);
+}
+
+#[test]
+fn test_unknown_balancer_version_is_written_as_own_protocol() {
+    let output_dir = empty_dir("balancer-v4");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "--balancer-path",
+            "tests/fixtures/v4/balancer",
+            "--uniswap-path",
+            "tests/fixtures/uniswap/deployments",
+            "--output-dir",
+        ])
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    let contracts: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(output_dir.join("balancer-v4/1.json")).unwrap())
+            .unwrap();
+    assert_eq!(
+        contracts["Vault"],
+        "0xBA14444444444444444444444444444444444444"
+    );
+    let warnings = fs::read_to_string(output_dir.join("warnings.json")).unwrap();
+    assert!(warnings.contains("20270101-v4-vault"));
+
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_deprecated_status_file_next_to_uniswap_deployments() {
+    let uniswap_dir = empty_dir("deprecated-status-source");
+    for file_name in ["1.json", "8453.json"] {
+        fs::copy(
+            Path::new("tests/fixtures/uniswap/deployments").join(file_name),
+            uniswap_dir.join(file_name),
+        )
+        .unwrap();
+    }
+    fs::write(
+        uniswap_dir.join("uniswap-v3.deprecated.json"),
+        r#"{ "1": ["TickLens"] }"#,
+    )
+    .unwrap();
+    let output_dir = empty_dir("deprecated-status-output");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-dex-index"))
+        .args([
+            "--balancer-path",
+            "tests/fixtures/balancer",
+            "--uniswap-path",
+        ])
+        .arg(&uniswap_dir)
+        .arg("--output-dir")
+        .arg(&output_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let enriched: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(output_dir.join("uniswap-v3/enriched/1.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(enriched["TickLens"]["status"], "deprecated");
+    assert_eq!(enriched["UniswapV3Factory"]["status"], "active");
+
+    fs::remove_dir_all(uniswap_dir).unwrap();
+    fs::remove_dir_all(output_dir).unwrap();
+}